@@ -0,0 +1,122 @@
+//! Parse and score a generated corpus, on one thread and on the full pool.
+//!
+//! ```text
+//! cargo bench --bench parse
+//! GRAVITY_BENCH_FILES=5000 cargo bench --bench parse
+//! ```
+
+// Only the parsing half of the backend is pulled in here. Its modules' tests
+// are built without their `#[test]` functions, which `cargo test` runs.
+#![allow(dead_code)]
+#![cfg_attr(test, allow(unused_imports))]
+
+#[path = "../src/complexity.rs"]
+mod complexity;
+#[path = "../src/models.rs"]
+mod models;
+#[path = "../src/parser.rs"]
+mod parser;
+
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+
+/// Files in the corpus unless `GRAVITY_BENCH_FILES` says otherwise.
+const DEFAULT_FILES: usize = 300;
+const FUNCTIONS_PER_FILE: usize = 12;
+
+fn parse_and_score(c: &mut Criterion) {
+    let files = std::env::var("GRAVITY_BENCH_FILES")
+        .ok()
+        .and_then(|n| n.parse().ok())
+        .unwrap_or(DEFAULT_FILES);
+    let root = std::env::temp_dir().join(format!("gravity-bench-{}", std::process::id()));
+    let paths = write_corpus(&root, files);
+
+    let available = std::thread::available_parallelism().map_or(1, |n| n.get());
+    let mut thread_counts = vec![1];
+    if available > 1 {
+        thread_counts.push(available);
+    }
+
+    let mut group = c.benchmark_group("parse_and_score");
+    group.throughput(Throughput::Elements(files as u64));
+    group.sample_size(10);
+    for threads in thread_counts {
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .build()
+            .expect("failed to build thread pool");
+        group.bench_with_input(BenchmarkId::from_parameter(format!("{threads} threads")), &paths, |b, paths| {
+            b.iter(|| {
+                pool.install(|| {
+                    let parsed = parser::parse_files(&root, paths, |_, _| {}).expect("parse failed");
+                    complexity::compute_all(&parsed)
+                })
+            })
+        });
+    }
+    group.finish();
+
+    let _ = std::fs::remove_dir_all(&root);
+}
+
+/// Writes `files` modules of branchy, nested code under `root/src`.
+fn write_corpus(root: &Path, files: usize) -> Vec<PathBuf> {
+    let src = root.join("src");
+    std::fs::create_dir_all(&src).expect("failed to create corpus dir");
+    (0..files)
+        .map(|i| {
+            let path = src.join(format!("module_{i}.rs"));
+            std::fs::write(&path, module_source(i)).expect("failed to write corpus file");
+            path
+        })
+        .collect()
+}
+
+fn module_source(i: usize) -> String {
+    let mut out = String::new();
+    let _ = writeln!(out, "//! Generated module {i}.\n");
+    let _ = writeln!(out, "use std::collections::HashMap;\nuse crate::module_{}::Item{};\n", i / 2, i / 2);
+    let _ = writeln!(out, "/// A record.\npub struct Item{i} {{\n    pub id: u64,\n    pub tags: Vec<String>,\n}}\n");
+    let _ = writeln!(out, "impl Item{i} {{");
+    for f in 0..FUNCTIONS_PER_FILE {
+        let _ = writeln!(
+            out,
+            r#"    /// Function {f}.
+    pub fn process_{f}(&self, input: &[u64], limit: u64) -> Result<u64, String> {{
+        let mut total = 0;
+        let mut seen: HashMap<u64, usize> = HashMap::new();
+        for (n, value) in input.iter().enumerate() {{
+            if *value > limit && n % 2 == 0 {{
+                match value % 3 {{
+                    0 => total += value,
+                    1 if self.id > 10 || self.tags.is_empty() => total -= 1,
+                    _ => {{
+                        while total > limit {{
+                            total /= 2;
+                        }}
+                    }}
+                }}
+            }} else if *value == 0 {{
+                return Err(format!("zero at {{n}}"));
+            }}
+            *seen.entry(*value).or_default() += 1;
+        }}
+        let extra = self.tags.iter().map(|t| t.len() as u64).sum::<u64>();
+        Ok(total + extra + self.helper_{f}(limit)?)
+    }}
+
+    fn helper_{f}(&self, limit: u64) -> Result<u64, String> {{
+        if limit == 0 {{ Err("empty".into()) }} else {{ Ok(limit.min(self.id)) }}
+    }}
+"#
+        );
+    }
+    let _ = writeln!(out, "}}");
+    out
+}
+
+criterion_group!(benches, parse_and_score);
+criterion_main!(benches);
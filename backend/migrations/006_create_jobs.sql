@@ -0,0 +1,16 @@
+-- Migration: Create jobs table
+CREATE TABLE IF NOT EXISTS jobs (
+    id               UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    project_id       UUID REFERENCES projects(id) ON DELETE CASCADE,
+    status           TEXT NOT NULL DEFAULT 'queued',
+    request          JSONB NOT NULL,
+    files_total      INTEGER NOT NULL DEFAULT 0,
+    files_parsed     INTEGER NOT NULL DEFAULT 0,
+    functions_found  INTEGER NOT NULL DEFAULT 0,
+    result           JSONB,
+    error            TEXT,
+    created_at       TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at       TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_jobs_status ON jobs(status);
@@ -0,0 +1,38 @@
+-- Migration: Create analysis_runs table and key every entity row by run
+CREATE TABLE IF NOT EXISTS analysis_runs (
+    id                UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    project_id        UUID NOT NULL REFERENCES projects(id) ON DELETE CASCADE,
+    commit_sha        TEXT,
+    analyzer_version  TEXT NOT NULL,
+    created_at        TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_analysis_runs_project_id ON analysis_runs(project_id, created_at DESC);
+
+-- Rows written before runs existed become a single run per project.
+INSERT INTO analysis_runs (id, project_id, analyzer_version, created_at)
+SELECT uuid_generate_v4(), p.id, 'unknown', p.updated_at
+FROM projects p
+WHERE EXISTS (SELECT 1 FROM files f WHERE f.project_id = p.id);
+
+ALTER TABLE files        ADD COLUMN IF NOT EXISTS run_id UUID REFERENCES analysis_runs(id) ON DELETE CASCADE;
+ALTER TABLE functions    ADD COLUMN IF NOT EXISTS run_id UUID REFERENCES analysis_runs(id) ON DELETE CASCADE;
+ALTER TABLE dependencies ADD COLUMN IF NOT EXISTS run_id UUID REFERENCES analysis_runs(id) ON DELETE CASCADE;
+ALTER TABLE complexities ADD COLUMN IF NOT EXISTS run_id UUID REFERENCES analysis_runs(id) ON DELETE CASCADE;
+
+UPDATE files        t SET run_id = r.id FROM analysis_runs r WHERE r.project_id = t.project_id;
+UPDATE functions    t SET run_id = r.id FROM analysis_runs r WHERE r.project_id = t.project_id;
+UPDATE dependencies t SET run_id = r.id FROM analysis_runs r WHERE r.project_id = t.project_id;
+UPDATE complexities t SET run_id = r.id FROM analysis_runs r WHERE r.project_id = t.project_id;
+
+ALTER TABLE files        ALTER COLUMN run_id SET NOT NULL;
+ALTER TABLE functions    ALTER COLUMN run_id SET NOT NULL;
+ALTER TABLE dependencies ALTER COLUMN run_id SET NOT NULL;
+ALTER TABLE complexities ALTER COLUMN run_id SET NOT NULL;
+
+CREATE INDEX IF NOT EXISTS idx_files_run_id        ON files(run_id);
+CREATE INDEX IF NOT EXISTS idx_functions_run_id    ON functions(run_id);
+CREATE INDEX IF NOT EXISTS idx_dependencies_run_id ON dependencies(run_id);
+CREATE INDEX IF NOT EXISTS idx_complexities_run_id ON complexities(run_id);
+
+ALTER TABLE jobs ADD COLUMN IF NOT EXISTS run_id UUID REFERENCES analysis_runs(id) ON DELETE SET NULL;
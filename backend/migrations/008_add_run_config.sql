@@ -0,0 +1,2 @@
+-- Migration: Keep the gravity.toml each run was analyzed with
+ALTER TABLE analysis_runs ADD COLUMN IF NOT EXISTS config JSONB NOT NULL DEFAULT '{}';
@@ -0,0 +1,11 @@
+-- Migration: One dependency row per leaf of a `use` tree
+ALTER TABLE dependencies ADD COLUMN IF NOT EXISTS path       TEXT NOT NULL DEFAULT '';
+ALTER TABLE dependencies ADD COLUMN IF NOT EXISTS alias      TEXT;
+ALTER TABLE dependencies ADD COLUMN IF NOT EXISTS is_glob    BOOLEAN NOT NULL DEFAULT FALSE;
+ALTER TABLE dependencies ADD COLUMN IF NOT EXISTS visibility TEXT;
+ALTER TABLE dependencies ADD COLUMN IF NOT EXISTS line       INTEGER NOT NULL DEFAULT 0;
+
+-- Older rows only kept the raw declaration in `target`.
+UPDATE dependencies SET path = target WHERE path = '';
+
+CREATE INDEX IF NOT EXISTS idx_dependencies_run_kind ON dependencies(run_id, kind);
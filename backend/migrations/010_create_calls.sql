@@ -0,0 +1,17 @@
+-- Migration: Create calls table (one row per call site)
+CREATE TABLE IF NOT EXISTS calls (
+    id              UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    project_id      UUID NOT NULL REFERENCES projects(id) ON DELETE CASCADE,
+    run_id          UUID NOT NULL REFERENCES analysis_runs(id) ON DELETE CASCADE,
+    caller_id       UUID NOT NULL REFERENCES functions(id) ON DELETE CASCADE,
+    -- NULL when the callee could not be resolved to a project function.
+    callee_id       UUID REFERENCES functions(id) ON DELETE CASCADE,
+    callee_name     TEXT NOT NULL,
+    is_method       BOOLEAN NOT NULL DEFAULT FALSE,
+    line            INTEGER NOT NULL DEFAULT 0,
+    created_at      TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_calls_run_id    ON calls(run_id);
+CREATE INDEX IF NOT EXISTS idx_calls_caller_id ON calls(caller_id);
+CREATE INDEX IF NOT EXISTS idx_calls_callee_id ON calls(callee_id);
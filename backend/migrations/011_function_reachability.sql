@@ -0,0 +1,9 @@
+-- Migration: Reachability-based dead code
+-- NULL when an entry point reaches the function; otherwise why none does.
+ALTER TABLE functions ADD COLUMN IF NOT EXISTS unreachable_reason TEXT;
+
+-- Functions named without being called, e.g. passed as a value or inside a macro.
+ALTER TABLE calls ADD COLUMN IF NOT EXISTS is_reference BOOLEAN NOT NULL DEFAULT FALSE;
+
+CREATE INDEX IF NOT EXISTS idx_functions_run_unreachable
+    ON functions(run_id) WHERE unreachable_reason IS NOT NULL;
@@ -0,0 +1,3 @@
+-- Migration: Type definitions per file, for abstractness
+ALTER TABLE files ADD COLUMN IF NOT EXISTS abstract_types INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE files ADD COLUMN IF NOT EXISTS concrete_types INTEGER NOT NULL DEFAULT 0;
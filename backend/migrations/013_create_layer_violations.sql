@@ -0,0 +1,15 @@
+-- Migration: Create layer_violations table (one row per offending use)
+CREATE TABLE IF NOT EXISTS layer_violations (
+    id              UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    project_id      UUID NOT NULL REFERENCES projects(id) ON DELETE CASCADE,
+    run_id          UUID NOT NULL REFERENCES analysis_runs(id) ON DELETE CASCADE,
+    from_layer      TEXT NOT NULL,
+    to_layer        TEXT NOT NULL,
+    file_path       TEXT NOT NULL,
+    line            INTEGER NOT NULL DEFAULT 0,
+    use_path        TEXT NOT NULL,
+    target_path     TEXT NOT NULL,
+    created_at      TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_layer_violations_run_id ON layer_violations(run_id);
@@ -0,0 +1,20 @@
+-- Migration: Create symbols table (items other than functions and uses)
+CREATE TABLE IF NOT EXISTS symbols (
+    id              UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    project_id      UUID NOT NULL REFERENCES projects(id) ON DELETE CASCADE,
+    run_id          UUID NOT NULL REFERENCES analysis_runs(id) ON DELETE CASCADE,
+    file_id         UUID NOT NULL REFERENCES files(id) ON DELETE CASCADE,
+    -- struct, enum, union, trait, impl, const, static, type, macro or mod
+    kind            TEXT NOT NULL,
+    name            TEXT NOT NULL,
+    line_start      INTEGER NOT NULL DEFAULT 0,
+    line_end        INTEGER NOT NULL DEFAULT 0,
+    visibility      TEXT,
+    -- For impls and associated items.
+    self_type       TEXT,
+    trait_name      TEXT,
+    created_at      TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_symbols_run_kind ON symbols(run_id, kind);
+CREATE INDEX IF NOT EXISTS idx_symbols_file_id  ON symbols(file_id);
@@ -0,0 +1,10 @@
+-- Migration: Qualified identity of functions
+ALTER TABLE functions ADD COLUMN IF NOT EXISTS module_path    TEXT;
+ALTER TABLE functions ADD COLUMN IF NOT EXISTS self_type      TEXT;
+ALTER TABLE functions ADD COLUMN IF NOT EXISTS trait_name     TEXT;
+ALTER TABLE functions ADD COLUMN IF NOT EXISTS qualified_name TEXT;
+
+UPDATE functions SET qualified_name = name WHERE qualified_name IS NULL;
+ALTER TABLE functions ALTER COLUMN qualified_name SET NOT NULL;
+
+CREATE INDEX IF NOT EXISTS idx_functions_run_qualified ON functions(run_id, qualified_name);
@@ -0,0 +1,2 @@
+-- Migration: Cognitive complexity next to the cyclomatic score
+ALTER TABLE complexities ADD COLUMN IF NOT EXISTS cognitive INTEGER NOT NULL DEFAULT 0;
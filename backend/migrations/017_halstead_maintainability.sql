@@ -0,0 +1,10 @@
+-- Migration: Halstead metrics and Maintainability Index per function and file
+ALTER TABLE complexities ADD COLUMN IF NOT EXISTS halstead_volume       FLOAT8 NOT NULL DEFAULT 0;
+ALTER TABLE complexities ADD COLUMN IF NOT EXISTS halstead_difficulty   FLOAT8 NOT NULL DEFAULT 0;
+ALTER TABLE complexities ADD COLUMN IF NOT EXISTS halstead_effort       FLOAT8 NOT NULL DEFAULT 0;
+ALTER TABLE complexities ADD COLUMN IF NOT EXISTS maintainability_index FLOAT8 NOT NULL DEFAULT 100;
+
+ALTER TABLE files ADD COLUMN IF NOT EXISTS halstead_volume       FLOAT8 NOT NULL DEFAULT 0;
+ALTER TABLE files ADD COLUMN IF NOT EXISTS halstead_difficulty   FLOAT8 NOT NULL DEFAULT 0;
+ALTER TABLE files ADD COLUMN IF NOT EXISTS halstead_effort       FLOAT8 NOT NULL DEFAULT 0;
+ALTER TABLE files ADD COLUMN IF NOT EXISTS maintainability_index FLOAT8 NOT NULL DEFAULT 100;
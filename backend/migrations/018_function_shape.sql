@@ -0,0 +1,8 @@
+-- Migration: Per-function size and shape metrics
+ALTER TABLE functions ADD COLUMN IF NOT EXISTS logical_lines  INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE functions ADD COLUMN IF NOT EXISTS params         INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE functions ADD COLUMN IF NOT EXISTS generic_params INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE functions ADD COLUMN IF NOT EXISTS max_nesting    INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE functions ADD COLUMN IF NOT EXISTS returns        INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE functions ADD COLUMN IF NOT EXISTS unsafe_blocks  INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE functions ADD COLUMN IF NOT EXISTS unwraps        INTEGER NOT NULL DEFAULT 0;
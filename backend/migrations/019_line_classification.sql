@@ -0,0 +1,7 @@
+-- Migration: Line classification and doc coverage per file
+ALTER TABLE files ADD COLUMN IF NOT EXISTS code_lines       INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE files ADD COLUMN IF NOT EXISTS comment_lines    INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE files ADD COLUMN IF NOT EXISTS doc_lines        INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE files ADD COLUMN IF NOT EXISTS blank_lines      INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE files ADD COLUMN IF NOT EXISTS public_items     INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE files ADD COLUMN IF NOT EXISTS documented_items INTEGER NOT NULL DEFAULT 0;
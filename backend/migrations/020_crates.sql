@@ -0,0 +1,36 @@
+-- Migration: Cargo packages of a run, the dependencies between them, and the package of each file
+ALTER TABLE files ADD COLUMN IF NOT EXISTS crate  TEXT;
+ALTER TABLE files ADD COLUMN IF NOT EXISTS target TEXT;
+
+CREATE INDEX IF NOT EXISTS idx_files_run_crate ON files(run_id, crate);
+
+CREATE TABLE IF NOT EXISTS crates (
+    id          UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    project_id  UUID NOT NULL REFERENCES projects(id) ON DELETE CASCADE,
+    run_id      UUID NOT NULL REFERENCES analysis_runs(id) ON DELETE CASCADE,
+    name        TEXT NOT NULL,
+    -- Manifest directory relative to the analysis root; '' for the root itself.
+    path        TEXT NOT NULL,
+    version     TEXT,
+    workspace   TEXT,
+    -- lib, build, bin:<name>, test:<name>, example:<name> or bench:<name>
+    targets     TEXT[] NOT NULL DEFAULT '{}',
+    created_at  TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_crates_run_id ON crates(run_id);
+
+CREATE TABLE IF NOT EXISTS crate_dependencies (
+    id          UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    project_id  UUID NOT NULL REFERENCES projects(id) ON DELETE CASCADE,
+    run_id      UUID NOT NULL REFERENCES analysis_runs(id) ON DELETE CASCADE,
+    from_crate  TEXT NOT NULL,
+    to_crate    TEXT NOT NULL,
+    -- normal, dev or build
+    kind        TEXT NOT NULL,
+    -- Whether to_crate is one of the run's crates rather than an external one.
+    is_local    BOOLEAN NOT NULL DEFAULT FALSE,
+    created_at  TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_crate_dependencies_run_id ON crate_dependencies(run_id);
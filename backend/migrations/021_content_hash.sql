@@ -0,0 +1,3 @@
+-- Migration: Content hash and parse result per file, for incremental re-analysis
+ALTER TABLE files ADD COLUMN IF NOT EXISTS content_hash TEXT;
+ALTER TABLE files ADD COLUMN IF NOT EXISTS parsed       JSONB;
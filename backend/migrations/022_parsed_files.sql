@@ -0,0 +1,23 @@
+-- Migration: Keep one parse result per project file, rewritten only when its content or the analyzer changes
+CREATE TABLE IF NOT EXISTS parsed_files (
+    project_id        UUID NOT NULL REFERENCES projects(id) ON DELETE CASCADE,
+    -- Relative to the analysis root, as in files.path.
+    path              TEXT NOT NULL,
+    content_hash      TEXT NOT NULL,
+    analyzer_version  TEXT NOT NULL,
+    parsed            JSONB NOT NULL,
+    updated_at        TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (project_id, path)
+);
+
+-- Carry over the latest parse of each file so the next run stays incremental.
+INSERT INTO parsed_files (project_id, path, content_hash, analyzer_version, parsed)
+SELECT DISTINCT ON (f.project_id, f.path)
+       f.project_id, f.path, f.content_hash, r.analyzer_version, f.parsed
+FROM files f
+JOIN analysis_runs r ON r.id = f.run_id
+WHERE f.parsed IS NOT NULL AND f.content_hash IS NOT NULL
+ORDER BY f.project_id, f.path, r.created_at DESC
+ON CONFLICT DO NOTHING;
+
+ALTER TABLE files DROP COLUMN IF EXISTS parsed;
@@ -0,0 +1,10 @@
+-- Migration: Let a run copy the rows of files unchanged since the run that stored their parse
+-- Position in the file's parse, by which copied calls find their callees again.
+ALTER TABLE functions ADD COLUMN IF NOT EXISTS ordinal INT;
+
+-- The run whose rows were written from `parsed`. Parses stored before this
+-- have none, and their files are written out again on the next run.
+ALTER TABLE parsed_files ADD COLUMN IF NOT EXISTS run_id UUID REFERENCES analysis_runs(id) ON DELETE SET NULL;
+
+CREATE INDEX IF NOT EXISTS idx_files_run_path ON files(run_id, path);
+CREATE INDEX IF NOT EXISTS idx_dependencies_file_id ON dependencies(file_id);
@@ -1,42 +1,65 @@
 use axum::{
-    extract::{Query, State},
-    http::StatusCode,
-    response::Json,
+    extract::{Path, Query, State},
+    http::{header, StatusCode},
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        IntoResponse, Json,
+    },
     routing::{get, post},
     Router,
 };
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use sqlx::PgPool;
+use std::convert::Infallible;
+use std::pin::Pin;
 use std::sync::Arc;
+use tokio_stream::{wrappers::BroadcastStream, Stream, StreamExt};
 use tracing::{error, info};
 use uuid::Uuid;
 
 use crate::{
-    complexity,
     db,
-    graph::DependencyGraph,
+    diff,
+    gates,
+    jobs::{self, JobEvents},
+    sarif,
+    watch,
     models::{
-        AnalyzeRequest, AnalyzeResponse, AnalysisSummary, ComplexityItem,
-        FileEntry, GraphData,
+        AnalysisRun, AnalyzeRequest, AnalysisSummary, ComplexityItem, DeadCodePage, DependencyCycle,
+        FileEntry, GateReport, GraphData, Job, JobAccepted, JobEvent, JobStatus, LayerViolation,
+        ModuleMetrics, RunDiff, SymbolEntry,
     },
-    parser,
 };
 
 #[derive(Clone)]
 pub struct AppState {
     pub pool: PgPool,
     pub analyze_path: String,
+    pub events: JobEvents,
+    pub watched: watch::Watched,
 }
 
-pub fn build_router(state: AppState) -> Router {
-    let shared = Arc::new(state);
-
+pub fn build_router(shared: Arc<AppState>) -> Router {
     Router::new()
         .route("/api/analyze",    post(analyze_handler))
+        .route("/api/jobs/:id",   get(job_handler))
+        .route("/api/analyze/:id/events", get(job_events_handler))
+        .route("/api/events",     get(updates_handler))
+        .route("/api/runs",       get(runs_handler))
         .route("/api/summary",    get(summary_handler))
         .route("/api/files",      get(files_handler))
         .route("/api/graph",      get(graph_handler))
+        .route("/api/crates/graph", get(crate_graph_handler))
+        .route("/api/callgraph",  get(callgraph_handler))
+        .route("/api/cycles",     get(cycles_handler))
+        .route("/api/metrics/modules", get(module_metrics_handler))
+        .route("/api/layers/violations", get(layer_violations_handler))
+        .route("/api/symbols",    get(symbols_handler))
         .route("/api/complexity", get(complexity_handler))
+        .route("/api/dead-code",  get(dead_code_handler))
+        .route("/api/diff",       get(diff_handler))
+        .route("/api/gates",      get(gates_handler))
+        .route("/api/export/sarif", get(sarif_handler))
         .route("/health",         get(health_handler))
         .with_state(shared)
 }
@@ -46,109 +69,178 @@ pub fn build_router(state: AppState) -> Router {
 pub struct ProjectQuery {
     pub project_id: Option<Uuid>,
 }
+
+/// Selects a run; defaults to the latest run of the (latest) project.
+#[derive(Debug, Deserialize)]
+pub struct RunQuery {
+    pub project_id: Option<Uuid>,
+    pub run_id: Option<Uuid>,
+}
+
+/// Calls followed from `root` when no `depth` is given.
+const DEFAULT_CALL_DEPTH: usize = 3;
+
+#[derive(Debug, Deserialize)]
+pub struct CallGraphQuery {
+    pub project_id: Option<Uuid>,
+    pub run_id: Option<Uuid>,
+    /// Function name to start from; the whole graph when absent.
+    pub root: Option<String>,
+    pub depth: Option<usize>,
+}
+
+/// Dead code entries per page when the query gives no `per_page`.
+const DEFAULT_PAGE_SIZE: i64 = 50;
+const MAX_PAGE_SIZE: i64 = 500;
+
+#[derive(Debug, Deserialize)]
+pub struct DeadCodeQuery {
+    pub project_id: Option<Uuid>,
+    pub run_id: Option<Uuid>,
+    /// 1-based.
+    pub page: Option<i64>,
+    pub per_page: Option<i64>,
+}
+
+/// Symbols returned when the query gives no `limit`.
+const DEFAULT_SYMBOL_LIMIT: i64 = 100;
+
+#[derive(Debug, Deserialize)]
+pub struct SymbolQuery {
+    pub project_id: Option<Uuid>,
+    pub run_id: Option<Uuid>,
+    /// Substring of the symbol name, matched case-insensitively.
+    pub q: Option<String>,
+    /// `struct`, `enum`, `trait`, `impl`, ...
+    pub kind: Option<String>,
+    pub limit: Option<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DiffQuery {
+    pub from: Uuid,
+    pub to: Uuid,
+}
 async fn analyze_handler(
     State(state): State<Arc<AppState>>,
     Json(req): Json<AnalyzeRequest>,
-) -> Result<Json<AnalyzeResponse>, (StatusCode, String)> {
-    let mut analyze_path = req.path.as_deref().unwrap_or(&state.analyze_path).to_owned();
-    let mut project_name = req
-        .project_name
-        .as_deref()
-        .unwrap_or("default")
-        .to_owned();
-
-    let mut temp_dir = None;
-
+) -> Result<(StatusCode, Json<JobAccepted>), (StatusCode, String)> {
     if let Some(url) = &req.github_url {
         if !url.starts_with("https://github.com/") {
             return Err((StatusCode::BAD_REQUEST, "Only GitHub URLs (https://github.com/...) are supported".into()));
         }
-
-        info!("Cloning GitHub repository: {url}");
-        
-        let id = Uuid::new_v4();
-        let path = std::env::temp_dir().join(format!("gravity-clone-{id}"));
-        
-        // Ensure parent exists
-        std::fs::create_dir_all(&path).map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, format!("Failed to create temp dir: {e}")))?;
-
-        let output = std::process::Command::new("git")
-            .args(["clone", "--depth", "1", url, path.to_str().unwrap()])
-            .output()
-            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, format!("Git clone failed to start: {e}")))?;
-
-        if !output.status.success() {
-            let err = String::from_utf8_lossy(&output.stderr);
-            error!("Git clone failed: {err}");
-            return Err((StatusCode::INTERNAL_SERVER_ERROR, format!("Git clone failed: {err}")));
+        if req.watch {
+            return Err((StatusCode::BAD_REQUEST, "Only local paths can be watched".into()));
         }
+    }
 
-        analyze_path = path.to_str().unwrap().to_owned();
-        temp_dir = Some(path);
-
-        // If project name was default, use repo name from URL
-        if project_name == "default" {
-            project_name = url.trim_end_matches('/').split('/').last().unwrap_or("default").to_owned();
-        }
+    if req.watch {
+        watch::start(state.clone(), req.clone())
+            .map_err(|e| (StatusCode::BAD_REQUEST, format!("{e:#}")))?;
     }
 
-    info!("Starting analysis of path: {analyze_path}");
-
-    let result = (|| -> anyhow::Result<AnalyzeResponse> {
-        let parsed_files = parser::parse_directory(&analyze_path)?;
-        let _dep_graph = DependencyGraph::from_parsed(&parsed_files);
-        let complexity_scores = complexity::compute_all(&parsed_files);
-        let functions_found: usize = parsed_files.iter().map(|f| f.functions.len()).sum();
-        
-        Ok(AnalyzeResponse {
-            project_id: Uuid::nil(), // Placeholder, will be updated
-            files_analyzed: parsed_files.len(),
-            functions_found,
-            message: format!("Analyzed {} files", parsed_files.len()),
-            parsed_files_internal: Some(parsed_files),
-            complexity_scores_internal: Some(complexity_scores),
-        })
-    })();
-
-    let mut res = result.map_err(|e| {
-        error!("Analysis error: {e}");
-        (StatusCode::INTERNAL_SERVER_ERROR, e.to_string())
-    })?;
-    let project = db::upsert_project(&state.pool, &project_name, &analyze_path)
+    let job = db::create_job(&state.pool, &req)
         .await
         .map_err(|e| {
-            error!("DB upsert error: {e}");
+            error!("DB job insert error: {e}");
             (StatusCode::INTERNAL_SERVER_ERROR, e.to_string())
         })?;
-    if let (Some(files), Some(scores)) = (res.parsed_files_internal.take(), res.complexity_scores_internal.take()) {
-        db::save_analysis(&state.pool, project.id, &files, &scores)
-            .await
-            .map_err(|e| {
-                error!("DB save error: {e}");
-                (StatusCode::INTERNAL_SERVER_ERROR, e.to_string())
-            })?;
-    }
 
-    res.project_id = project.id;
-    res.message = format!(
-        "Analysis complete: {} files, {} functions",
-        res.files_analyzed,
-        res.functions_found
-    );
-    if let Some(path) = temp_dir {
-        info!("Cleaning up temp directory: {:?}", path);
-        let _ = std::fs::remove_dir_all(path);
-    }
-    Ok(Json(res))
+    info!("Queued analysis job {}", job.id);
+    jobs::spawn(state.clone(), job.id, req);
+
+    Ok((
+        StatusCode::ACCEPTED,
+        Json(JobAccepted {
+            job_id: job.id,
+            status: job.status,
+        }),
+    ))
 }
 
-async fn summary_handler(
+async fn job_handler(
+    State(state): State<Arc<AppState>>,
+    Path(job_id): Path<Uuid>,
+) -> Result<Json<Job>, (StatusCode, String)> {
+    let job = db::fetch_job(&state.pool, job_id)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        .ok_or_else(|| (StatusCode::NOT_FOUND, format!("Job {job_id} not found")))?;
+
+    Ok(Json(job))
+}
+
+type EventStream = Pin<Box<dyn Stream<Item = Result<Event, Infallible>> + Send>>;
+
+/// Streams a job's progress as Server-Sent Events. The current state is sent
+/// first so late subscribers catch up; the stream ends when the job does.
+async fn job_events_handler(
+    State(state): State<Arc<AppState>>,
+    Path(job_id): Path<Uuid>,
+) -> Result<Sse<EventStream>, (StatusCode, String)> {
+    // Subscribe before reading the row so no event falls in between.
+    let live = state.events.subscribe(job_id);
+
+    let job = db::fetch_job(&state.pool, job_id)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        .ok_or_else(|| (StatusCode::NOT_FOUND, format!("Job {job_id} not found")))?;
+
+    let snapshot = match (job.status, job.result, job.error) {
+        (JobStatus::Done, Some(result), _) => JobEvent::Done { result: result.0 },
+        (JobStatus::Failed, _, error) => JobEvent::Failed {
+            error: error.unwrap_or_else(|| "Analysis failed".into()),
+        },
+        (status, _, _) => JobEvent::Phase { status },
+    };
+
+    let head = tokio_stream::once(snapshot);
+    let stream: EventStream = match live {
+        // Lagged receivers skip what they missed rather than ending the stream.
+        Some(rx) => Box::pin(
+            head.chain(BroadcastStream::new(rx).filter_map(Result::ok))
+                .map(to_sse_event),
+        ),
+        None => Box::pin(head.map(to_sse_event)),
+    };
+
+    Ok(Sse::new(stream).keep_alive(KeepAlive::default()))
+}
+
+/// Streams an `analyzed` event for every run stored from now on, so open
+/// dashboards can refresh, including after runs started by a watcher.
+async fn updates_handler(State(state): State<Arc<AppState>>) -> Sse<EventStream> {
+    let updates = BroadcastStream::new(state.events.subscribe_updates()).filter_map(Result::ok);
+    let stream: EventStream = Box::pin(updates.map(to_sse_event));
+    Sse::new(stream).keep_alive(KeepAlive::default())
+}
+
+fn to_sse_event(event: impl Serialize) -> Result<Event, Infallible> {
+    Ok(Event::default()
+        .json_data(&event)
+        .unwrap_or_else(|_| Event::default().comment("unserializable event")))
+}
+
+async fn runs_handler(
     State(state): State<Arc<AppState>>,
     Query(params): Query<ProjectQuery>,
-) -> Result<Json<AnalysisSummary>, (StatusCode, String)> {
+) -> Result<Json<Vec<AnalysisRun>>, (StatusCode, String)> {
     let project_id = resolve_project_id(&state.pool, params.project_id).await?;
 
-    let summary = db::fetch_summary(&state.pool, project_id)
+    let runs = db::fetch_runs(&state.pool, project_id)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    Ok(Json(runs))
+}
+
+async fn summary_handler(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<RunQuery>,
+) -> Result<Json<AnalysisSummary>, (StatusCode, String)> {
+    let run = resolve_run(&state.pool, &params).await?;
+
+    let summary = db::fetch_summary(&state.pool, &run)
         .await
         .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
 
@@ -156,11 +248,11 @@ async fn summary_handler(
 }
 async fn files_handler(
     State(state): State<Arc<AppState>>,
-    Query(params): Query<ProjectQuery>,
+    Query(params): Query<RunQuery>,
 ) -> Result<Json<Vec<FileEntry>>, (StatusCode, String)> {
-    let project_id = resolve_project_id(&state.pool, params.project_id).await?;
+    let run = resolve_run(&state.pool, &params).await?;
 
-    let files = db::fetch_files(&state.pool, project_id)
+    let files = db::fetch_files(&state.pool, run.id)
         .await
         .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
 
@@ -168,11 +260,77 @@ async fn files_handler(
 }
 async fn graph_handler(
     State(state): State<Arc<AppState>>,
-    Query(params): Query<ProjectQuery>,
+    Query(params): Query<RunQuery>,
 ) -> Result<Json<GraphData>, (StatusCode, String)> {
-    let project_id = resolve_project_id(&state.pool, params.project_id).await?;
+    let run = resolve_run(&state.pool, &params).await?;
+
+    let graph = db::fetch_graph(&state.pool, run.id)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    Ok(Json(graph))
+}
+async fn crate_graph_handler(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<RunQuery>,
+) -> Result<Json<GraphData>, (StatusCode, String)> {
+    let run = resolve_run(&state.pool, &params).await?;
+
+    let graph = db::fetch_crate_graph(&state.pool, run.id)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    Ok(Json(graph))
+}
+async fn module_metrics_handler(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<RunQuery>,
+) -> Result<Json<Vec<ModuleMetrics>>, (StatusCode, String)> {
+    let run = resolve_run(&state.pool, &params).await?;
+
+    let modules = db::fetch_module_metrics(&state.pool, run.id)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    Ok(Json(modules))
+}
+async fn layer_violations_handler(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<RunQuery>,
+) -> Result<Json<Vec<LayerViolation>>, (StatusCode, String)> {
+    let run = resolve_run(&state.pool, &params).await?;
+
+    let violations = db::fetch_layer_violations(&state.pool, run.id)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    Ok(Json(violations))
+}
+async fn cycles_handler(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<RunQuery>,
+) -> Result<Json<Vec<DependencyCycle>>, (StatusCode, String)> {
+    let run = resolve_run(&state.pool, &params).await?;
+
+    let cycles = db::fetch_cycles(&state.pool, run.id)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    Ok(Json(cycles))
+}
+async fn callgraph_handler(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<CallGraphQuery>,
+) -> Result<Json<GraphData>, (StatusCode, String)> {
+    let run_query = RunQuery {
+        project_id: params.project_id,
+        run_id: params.run_id,
+    };
+    let run = resolve_run(&state.pool, &run_query).await?;
 
-    let graph = db::fetch_graph(&state.pool, project_id)
+    let root = params.root.as_deref().filter(|r| !r.is_empty());
+    let depth = params.depth.unwrap_or(DEFAULT_CALL_DEPTH);
+    let graph = db::fetch_call_graph(&state.pool, run.id, root, depth)
         .await
         .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
 
@@ -180,16 +338,144 @@ async fn graph_handler(
 }
 async fn complexity_handler(
     State(state): State<Arc<AppState>>,
-    Query(params): Query<ProjectQuery>,
+    Query(params): Query<RunQuery>,
 ) -> Result<Json<Vec<ComplexityItem>>, (StatusCode, String)> {
-    let project_id = resolve_project_id(&state.pool, params.project_id).await?;
+    let run = resolve_run(&state.pool, &params).await?;
 
-    let items = db::fetch_complexities(&state.pool, project_id)
+    let items = db::fetch_complexities(&state.pool, run.id)
         .await
         .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
 
     Ok(Json(items))
 }
+async fn dead_code_handler(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<DeadCodeQuery>,
+) -> Result<Json<DeadCodePage>, (StatusCode, String)> {
+    let run_query = RunQuery {
+        project_id: params.project_id,
+        run_id: params.run_id,
+    };
+    let run = resolve_run(&state.pool, &run_query).await?;
+
+    let page = params.page.unwrap_or(1).max(1);
+    let per_page = params.per_page.unwrap_or(DEFAULT_PAGE_SIZE).clamp(1, MAX_PAGE_SIZE);
+    let offset = (page - 1)
+        .checked_mul(per_page)
+        .ok_or((StatusCode::BAD_REQUEST, format!("Page {page} is out of range")))?;
+    let items = db::fetch_dead_code(&state.pool, run.id, Some(per_page), offset)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    let total = db::count_dead_code(&state.pool, run.id)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    Ok(Json(DeadCodePage {
+        items,
+        total,
+        page,
+        per_page,
+    }))
+}
+async fn symbols_handler(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<SymbolQuery>,
+) -> Result<Json<Vec<SymbolEntry>>, (StatusCode, String)> {
+    let run_query = RunQuery {
+        project_id: params.project_id,
+        run_id: params.run_id,
+    };
+    let run = resolve_run(&state.pool, &run_query).await?;
+
+    let limit = params.limit.unwrap_or(DEFAULT_SYMBOL_LIMIT).clamp(1, MAX_PAGE_SIZE);
+    let symbols = db::fetch_symbols(
+        &state.pool,
+        run.id,
+        params.q.as_deref().filter(|q| !q.is_empty()),
+        params.kind.as_deref(),
+        limit,
+    )
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    Ok(Json(symbols))
+}
+async fn diff_handler(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<DiffQuery>,
+) -> Result<Json<RunDiff>, (StatusCode, String)> {
+    let mut runs = Vec::with_capacity(2);
+    for run_id in [params.from, params.to] {
+        let run = db::fetch_run(&state.pool, run_id)
+            .await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+            .ok_or_else(|| (StatusCode::NOT_FOUND, format!("Run {run_id} not found")))?;
+        runs.push(run);
+    }
+    if runs[0].project_id != runs[1].project_id {
+        return Err((StatusCode::BAD_REQUEST, "Runs belong to different projects".into()));
+    }
+
+    let from = db::fetch_run_snapshot(&state.pool, params.from)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    let to = db::fetch_run_snapshot(&state.pool, params.to)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    Ok(Json(diff::diff_runs(params.from, &from, params.to, &to)))
+}
+
+/// Evaluates the run's `gravity.toml` gates; new dead code is counted
+/// against the project's previous run.
+async fn gates_handler(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<RunQuery>,
+) -> Result<Json<GateReport>, (StatusCode, String)> {
+    let run = resolve_run(&state.pool, &params).await?;
+
+    let report = async {
+        let config = db::fetch_run_config(&state.pool, run.id).await?;
+        let snapshot = db::fetch_run_snapshot(&state.pool, run.id).await?;
+        let previous = db::fetch_previous_run(&state.pool, &run).await?;
+        let baseline = match &previous {
+            Some(prev) => Some(db::fetch_run_snapshot(&state.pool, prev.id).await?),
+            None => None,
+        };
+
+        let mut report = gates::evaluate(&config.gates, &snapshot, baseline.as_ref());
+        report.run_id = Some(run.id);
+        report.baseline_run_id = previous.map(|p| p.id);
+        anyhow::Ok(report)
+    }
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    Ok(Json(report))
+}
+
+async fn sarif_handler(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<RunQuery>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let run = resolve_run(&state.pool, &params).await?;
+
+    let config = db::fetch_run_config(&state.pool, run.id)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    let snapshot = db::fetch_run_snapshot(&state.pool, run.id)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, "application/sarif+json"),
+            (header::CONTENT_DISPOSITION, "attachment; filename=\"gravity.sarif\""),
+        ],
+        Json(sarif::export(&snapshot, &config.gates)),
+    ))
+}
+
 async fn health_handler() -> Json<serde_json::Value> {
     Json(serde_json::json!({ "status": "ok" }))
 }
@@ -215,3 +501,30 @@ async fn resolve_project_id(
         )
     })
 }
+
+async fn resolve_run(
+    pool: &PgPool,
+    params: &RunQuery,
+) -> Result<AnalysisRun, (StatusCode, String)> {
+    let run = match params.run_id {
+        Some(run_id) => db::fetch_run(pool, run_id)
+            .await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+            .filter(|run| params.project_id.is_none_or(|p| p == run.project_id))
+            .ok_or_else(|| (StatusCode::NOT_FOUND, format!("Run {run_id} not found")))?,
+        None => {
+            let project_id = resolve_project_id(pool, params.project_id).await?;
+            db::fetch_latest_run(pool, project_id)
+                .await
+                .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+                .ok_or_else(|| {
+                    (
+                        StatusCode::NOT_FOUND,
+                        "Project has no analysis runs. Run POST /api/analyze first.".into(),
+                    )
+                })?
+        }
+    };
+
+    Ok(run)
+}
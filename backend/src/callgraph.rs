@@ -0,0 +1,602 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use uuid::Uuid;
+
+use crate::models::{
+    CallSite, FunctionRef, GraphData, GraphEdge, GraphNode, ImportTarget, ParsedFile,
+    ParsedImport,
+};
+use crate::resolve::ModuleTree;
+
+/// Functions of the analysis, indexed the ways call sites name them.
+struct FunctionIndex<'a> {
+    /// (file, name) → free functions.
+    free: HashMap<(&'a str, &'a str), Vec<FunctionRef>>,
+    /// (owner type or trait, name) → methods.
+    methods: HashMap<(&'a str, &'a str), Vec<FunctionRef>>,
+    /// name → methods of any owner.
+    method_names: HashMap<&'a str, Vec<FunctionRef>>,
+    /// name → free functions of any file.
+    free_names: HashMap<&'a str, Vec<FunctionRef>>,
+}
+
+impl<'a> FunctionIndex<'a> {
+    fn build(files: &'a [ParsedFile]) -> Self {
+        let mut index = FunctionIndex {
+            free: HashMap::new(),
+            methods: HashMap::new(),
+            method_names: HashMap::new(),
+            free_names: HashMap::new(),
+        };
+        for pf in files {
+            for (i, func) in pf.functions.iter().enumerate() {
+                let r = FunctionRef { file: pf.path.clone(), index: i };
+                match &func.owner {
+                    Some(owner) => {
+                        index.methods.entry((owner, &func.name)).or_default().push(r.clone());
+                        index.method_names.entry(&func.name).or_default().push(r);
+                    }
+                    None => {
+                        index.free.entry((&pf.path, &func.name)).or_default().push(r.clone());
+                        index.free_names.entry(&func.name).or_default().push(r);
+                    }
+                }
+            }
+        }
+        index
+    }
+
+    fn free_in(&self, file: &str, name: &str) -> Option<FunctionRef> {
+        self.free.get(&(file, name)).and_then(|fs| fs.first().cloned())
+    }
+
+    /// A method of `owner`, preferring the impl in `file`.
+    fn method_of(&self, owner: &str, name: &str, file: &str) -> Option<FunctionRef> {
+        let candidates = self.methods.get(&(owner, name))?;
+        candidates
+            .iter()
+            .find(|r| r.file == file)
+            .or_else(|| (candidates.len() == 1).then(|| &candidates[0]))
+            .cloned()
+    }
+
+    /// Every function an unresolved `call` might name: the methods of that
+    /// name for a method call, the type's methods of that name for
+    /// `Type::name()`, the free functions of that name for a bare `name()`.
+    /// Other paths that do not resolve lead out of the project.
+    fn candidates(&self, call: &CallSite) -> Vec<FunctionRef> {
+        let Some((name, prefix)) = call.path.split_last() else {
+            return Vec::new();
+        };
+        let found = if call.is_method {
+            self.method_names.get(name.as_str())
+        } else {
+            match prefix {
+                [] => self.free_names.get(name.as_str()),
+                [.., ty] if ty.starts_with(char::is_uppercase) => self.methods.get(&(ty.as_str(), name.as_str())),
+                _ => None,
+            }
+        };
+        found.cloned().unwrap_or_default()
+    }
+}
+
+/// Sets `target` on every call site whose callee can be pinned down: functions
+/// of the same module, imported names, module paths, `Self::`/`Type::`
+/// associated functions and methods on `self`. Method calls on other
+/// receivers resolve only when the method name is unique in the project.
+/// Imports must already be resolved.
+pub fn resolve_calls(tree: &ModuleTree, files: &mut [ParsedFile]) {
+    type Targets = Vec<Option<FunctionRef>>;
+    let targets: Vec<(Vec<Targets>, Targets)> = {
+        let index = FunctionIndex::build(files);
+        files
+            .iter()
+            .map(|pf| {
+                let in_functions = pf
+                    .functions
+                    .iter()
+                    .map(|func| {
+                        let caller = Caller { scope: &func.scope, owner: func.owner.as_deref() };
+                        func.calls
+                            .iter()
+                            .map(|call| resolve_call(tree, &index, pf, &caller, call))
+                            .collect()
+                    })
+                    .collect();
+                let item_level = Caller { scope: &[], owner: None };
+                let in_items = pf
+                    .item_references
+                    .iter()
+                    .map(|call| resolve_call(tree, &index, pf, &item_level, call))
+                    .collect();
+                (in_functions, in_items)
+            })
+            .collect()
+    };
+
+    for (pf, (fn_targets, item_targets)) in files.iter_mut().zip(targets) {
+        for (func, call_targets) in pf.functions.iter_mut().zip(fn_targets) {
+            for (call, target) in func.calls.iter_mut().zip(call_targets) {
+                call.target = target;
+            }
+        }
+        for (call, target) in pf.item_references.iter_mut().zip(item_targets) {
+            call.target = target;
+        }
+    }
+}
+
+/// Where a call site sits: the function's inline module scope and `impl` type.
+struct Caller<'a> {
+    scope: &'a [String],
+    owner: Option<&'a str>,
+}
+
+fn resolve_call(
+    tree: &ModuleTree,
+    index: &FunctionIndex,
+    pf: &ParsedFile,
+    caller: &Caller,
+    call: &CallSite,
+) -> Option<FunctionRef> {
+    if call.is_method {
+        let name = call.path.last()?;
+        if call.on_self {
+            return index.method_of(caller.owner?, name, &pf.path);
+        }
+        let candidates = index.method_names.get(name.as_str())?;
+        return (candidates.len() == 1).then(|| candidates[0].clone());
+    }
+
+    let (name, prefix) = call.path.split_last()?;
+    match prefix {
+        [] => index.free_in(&pf.path, name).or_else(|| imported(index, pf, caller, name)),
+        [owner] if owner == "Self" => index.method_of(caller.owner?, name, &pf.path),
+        [.., ty] if ty.starts_with(char::is_uppercase) => {
+            // `Alias::new()` where `use a::Type as Alias`.
+            let ty = local_import(pf, caller, ty)
+                .and_then(|i| i.path.last())
+                .unwrap_or(ty);
+            index.method_of(ty, name, &pf.path)
+        }
+        [first, rest @ ..] => {
+            // A leading imported module name stands for its full path.
+            let path: Vec<String> = match local_import(pf, caller, first) {
+                Some(import) => [import.path.as_slice(), rest].concat(),
+                None => prefix.to_vec(),
+            };
+            match tree.resolve(&pf.path, caller.scope, &path)? {
+                ImportTarget::File(file) => index.free_in(&file, name),
+                _ => None,
+            }
+        }
+    }
+}
+
+/// A bare `name()` brought in by a `use`, by name or through a glob.
+fn imported(
+    index: &FunctionIndex,
+    pf: &ParsedFile,
+    caller: &Caller,
+    name: &str,
+) -> Option<FunctionRef> {
+    if let Some(import) = local_import(pf, caller, name) {
+        if let Some(ImportTarget::File(file)) = &import.target {
+            return index.free_in(file, import.path.last()?);
+        }
+    }
+    pf.imports
+        .iter()
+        .filter(|i| i.is_glob && caller.scope.starts_with(&i.scope))
+        .find_map(|i| match &i.target {
+            Some(ImportTarget::File(file)) => index.free_in(file, name),
+            _ => None,
+        })
+}
+
+/// The non-glob import visible from `caller` that binds `name`.
+fn local_import<'a>(pf: &'a ParsedFile, caller: &Caller, name: &str) -> Option<&'a ParsedImport> {
+    pf.imports
+        .iter()
+        .filter(|i| !i.is_glob && caller.scope.starts_with(&i.scope))
+        .find(|i| bound_name(i) == Some(name))
+}
+
+/// The name an import brings into scope; `a::b::{self}` binds `b`.
+fn bound_name(import: &ParsedImport) -> Option<&str> {
+    if let Some(alias) = &import.alias {
+        return Some(alias);
+    }
+    match import.path.as_slice() {
+        [.., module, last] if last == "self" => Some(module),
+        [.., last] => Some(last),
+        [] => None,
+    }
+}
+
+/// Attributes that make a function an entry point: test and benchmark
+/// harnesses, async runtime `main`s and symbols exported to foreign code.
+/// Matched on the last path segment, so `tokio::test` counts as `test`.
+const ROOT_ATTRIBUTES: &[&str] = &["test", "bench", "main", "no_mangle", "export_name"];
+
+/// Names of unreachable callers listed in a reason before eliding the rest.
+const REASON_CALLERS: usize = 3;
+
+/// Sets `unreachable` on every function that no entry point reaches through
+/// calls or references. Entry points are `main` of each crate root, `pub`
+/// functions of library crates, trait methods (dispatched dynamically or by
+/// generic code), functions with a root attribute such as `#[test]` or
+/// `#[tokio::main]`, and functions named by item-level macros. Calls must
+/// already be resolved; a call that could not be counts as a call to every
+/// function it might name, so an ambiguous name never makes dead code.
+pub fn mark_unreachable(tree: &ModuleTree, files: &mut [ParsedFile]) {
+    let reasons: HashMap<FunctionRef, String> = {
+        let index = FunctionIndex::build(files);
+        let targets = |call: &CallSite| -> Vec<FunctionRef> {
+            match &call.target {
+                Some(target) => vec![target.clone()],
+                // Most unresolved references are local variables.
+                None if call.is_reference => Vec::new(),
+                None => index.candidates(call),
+            }
+        };
+
+        let mut callers: HashMap<FunctionRef, Vec<FunctionRef>> = HashMap::new();
+        let mut roots: Vec<FunctionRef> = Vec::new();
+
+        for pf in files.iter() {
+            let krate = tree.crate_root(&pf.path);
+            let is_crate_root = krate == Some(pf.path.as_str());
+            let is_library = krate.is_some_and(|k| tree.is_library(k));
+
+            roots.extend(pf.item_references.iter().flat_map(targets));
+            for (index, func) in pf.functions.iter().enumerate() {
+                let this = FunctionRef { file: pf.path.clone(), index };
+                for target in func.calls.iter().flat_map(targets) {
+                    callers.entry(target).or_default().push(this.clone());
+                }
+
+                let is_main = is_crate_root
+                    && func.name == "main"
+                    && func.owner.is_none()
+                    && func.scope.is_empty();
+                let is_api = is_library && func.visibility.as_deref() == Some("pub");
+                let has_root_attribute = func.attributes.iter().any(|attr| {
+                    ROOT_ATTRIBUTES.contains(&attr.rsplit("::").next().unwrap_or(attr))
+                });
+                if is_main || is_api || func.trait_name.is_some() || has_root_attribute {
+                    roots.push(this);
+                }
+            }
+        }
+
+        let by_path: HashMap<&str, &ParsedFile> = files.iter().map(|f| (f.path.as_str(), f)).collect();
+        let function = |r: &FunctionRef| &by_path[r.file.as_str()].functions[r.index];
+
+        let mut reached: HashSet<FunctionRef> = HashSet::new();
+        let mut queue: VecDeque<FunctionRef> = roots.into_iter().collect();
+        while let Some(r) = queue.pop_front() {
+            if !reached.insert(r.clone()) {
+                continue;
+            }
+            queue.extend(function(&r).calls.iter().flat_map(targets));
+        }
+
+        let name_of = |r: &FunctionRef| {
+            let func = function(r);
+            match &func.owner {
+                Some(owner) => format!("{owner}::{}", func.name),
+                None => func.name.clone(),
+            }
+        };
+
+        let mut reasons = HashMap::new();
+        for pf in files.iter() {
+            for (index, func) in pf.functions.iter().enumerate() {
+                let this = FunctionRef { file: pf.path.clone(), index };
+                if reached.contains(&this) {
+                    continue;
+                }
+                let mut from: Vec<String> = callers
+                    .get(&this)
+                    .into_iter()
+                    .flatten()
+                    .filter(|c| **c != this)
+                    .map(name_of)
+                    .collect();
+                from.sort();
+                from.dedup();
+
+                let reason = if from.is_empty() {
+                    if func.visibility.as_deref() == Some("pub") {
+                        "public but not exported by a library crate, and never called".to_owned()
+                    } else if callers.contains_key(&this) {
+                        "only called by itself".to_owned()
+                    } else {
+                        "never called or referenced".to_owned()
+                    }
+                } else {
+                    let mut listed = from
+                        .iter()
+                        .take(REASON_CALLERS)
+                        .map(|n| format!("`{n}`"))
+                        .collect::<Vec<_>>()
+                        .join(", ");
+                    if from.len() > REASON_CALLERS {
+                        listed.push_str(&format!(" and {} more", from.len() - REASON_CALLERS));
+                    }
+                    format!("only called from unreachable code: {listed}")
+                };
+                reasons.insert(this, reason);
+            }
+        }
+        reasons
+    };
+
+    for pf in files.iter_mut() {
+        for (index, func) in pf.functions.iter_mut().enumerate() {
+            func.unreachable = reasons.get(&FunctionRef { file: pf.path.clone(), index }).cloned();
+        }
+    }
+}
+
+/// A function of a stored run, as loaded for the call graph view.
+pub struct CallGraphFunction {
+    pub id: Uuid,
+    pub name: String,
+    pub self_type: Option<String>,
+    pub qualified_name: String,
+}
+
+impl CallGraphFunction {
+    /// `Config::new` for methods, otherwise just the name.
+    fn short_name(&self) -> String {
+        match &self.self_type {
+            Some(owner) => format!("{owner}::{}", self.name),
+            None => self.name.clone(),
+        }
+    }
+
+    /// Whether `root` names this function, by name, `Type::name` or its
+    /// qualified name.
+    fn is_named(&self, root: &str) -> bool {
+        self.name == root || self.qualified_name == root || self.short_name() == root
+    }
+}
+
+/// Builds the function-level graph. With a `root`, keeps only the functions
+/// reachable from functions of that name within `depth` calls. Nodes are
+/// labelled `Type::name` and identified by function id.
+pub fn call_graph_data(
+    functions: &[CallGraphFunction],
+    calls: &[(Uuid, Uuid)],
+    root: Option<&str>,
+    depth: usize,
+) -> GraphData {
+    let mut callees: HashMap<Uuid, Vec<Uuid>> = HashMap::new();
+    for &(caller, callee) in calls {
+        callees.entry(caller).or_default().push(callee);
+    }
+
+    let roots: HashSet<Uuid> = match root {
+        Some(root) => functions.iter().filter(|f| f.is_named(root)).map(|f| f.id).collect(),
+        None => HashSet::new(),
+    };
+
+    let keep: HashSet<Uuid> = match root {
+        Some(_) => {
+            let mut seen: HashSet<Uuid> = roots.clone();
+            let mut queue: VecDeque<(Uuid, usize)> = roots.iter().map(|&id| (id, 0)).collect();
+            while let Some((id, d)) = queue.pop_front() {
+                if d == depth {
+                    continue;
+                }
+                for &callee in callees.get(&id).into_iter().flatten() {
+                    if seen.insert(callee) {
+                        queue.push_back((callee, d + 1));
+                    }
+                }
+            }
+            seen
+        }
+        // Without a root, show every function that takes part in a call.
+        None => calls.iter().flat_map(|&(a, b)| [a, b]).collect(),
+    };
+
+    let nodes = functions
+        .iter()
+        .filter(|f| keep.contains(&f.id))
+        .map(|f| GraphNode {
+            id: f.id.to_string(),
+            label: f.short_name(),
+            kind: if roots.contains(&f.id) { "root" } else { "function" }.into(),
+        })
+        .collect();
+
+    let mut seen_edges = HashSet::new();
+    let edges = calls
+        .iter()
+        .filter(|(a, b)| keep.contains(a) && keep.contains(b) && seen_edges.insert((*a, *b)))
+        .map(|(a, b)| GraphEdge {
+            from: a.to_string(),
+            to: b.to_string(),
+            label: Some("calls".into()),
+        })
+        .collect();
+
+    GraphData { nodes, edges }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{parser, resolve};
+
+    /// Why each function of `files` is unreachable, by qualified name.
+    fn unreachable(files: &[(&str, &str)]) -> HashMap<String, Option<String>> {
+        let mut parsed = parser::parse_sources(files);
+        resolve::link(&mut parsed, &[]);
+        parsed
+            .iter()
+            .flat_map(|pf| pf.functions.iter())
+            .map(|f| (f.qualified_name.clone(), f.unreachable.clone()))
+            .collect()
+    }
+
+    #[test]
+    fn ambiguous_method_calls_reach_every_candidate() {
+        let dead = unreachable(&[(
+            "src/main.rs",
+            r#"
+            struct A;
+            struct B;
+            impl A { fn name(&self) -> &str { "a" } }
+            impl B { fn name(&self) -> &str { "b" } }
+            impl B { fn unused(&self) {} }
+            fn main() {
+                let a = A;
+                let _ = a.name();
+            }
+            "#,
+        )]);
+        assert_eq!(dead["crate::A::name"], None);
+        assert_eq!(dead["crate::B::name"], None);
+        assert!(dead["crate::B::unused"].is_some());
+    }
+
+    #[test]
+    fn main_of_a_binary_is_a_root() {
+        let dead = unreachable(&[(
+            "src/main.rs",
+            r#"
+            fn main() { helper(); }
+            fn helper() {}
+            pub fn exported() {}
+            fn unused() { also_unused(); }
+            fn also_unused() {}
+            "#,
+        )]);
+        assert_eq!(dead["crate::main"], None);
+        assert_eq!(dead["crate::helper"], None);
+        assert_eq!(
+            dead["crate::exported"].as_deref(),
+            Some("public but not exported by a library crate, and never called")
+        );
+        assert_eq!(dead["crate::unused"].as_deref(), Some("never called or referenced"));
+        assert_eq!(
+            dead["crate::also_unused"].as_deref(),
+            Some("only called from unreachable code: `unused`")
+        );
+    }
+
+    #[test]
+    fn pub_functions_of_a_library_are_roots() {
+        let dead = unreachable(&[(
+            "src/lib.rs",
+            r#"
+            pub fn api() { internal(); }
+            fn internal() {}
+            pub(crate) fn crate_only() {}
+            fn recursive() { recursive(); }
+            "#,
+        )]);
+        assert_eq!(dead["crate::api"], None);
+        assert_eq!(dead["crate::internal"], None);
+        assert!(dead["crate::crate_only"].is_some());
+        assert_eq!(dead["crate::recursive"].as_deref(), Some("only called by itself"));
+    }
+
+    #[test]
+    fn trait_methods_are_roots() {
+        let dead = unreachable(&[(
+            "src/main.rs",
+            r#"
+            use std::fmt;
+            struct Point;
+            trait Shape { fn area(&self) -> f64 { 0.0 } }
+            impl fmt::Display for Point {
+                fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result { write_point(f) }
+            }
+            fn write_point(f: &mut fmt::Formatter) -> fmt::Result { Ok(()) }
+            fn main() {}
+            "#,
+        )]);
+        assert_eq!(dead["<crate::Point as Display>::fmt"], None);
+        assert_eq!(dead["crate::write_point"], None);
+        assert_eq!(dead["crate::Shape::area"], None);
+    }
+
+    #[test]
+    fn root_attributes_make_roots() {
+        let dead = unreachable(&[(
+            "src/main.rs",
+            r#"
+            fn main() {}
+            #[test]
+            fn checks() { fixture(); }
+            fn fixture() {}
+            #[tokio::test]
+            async fn checks_async() {}
+            #[no_mangle]
+            extern "C" fn called_from_c() {}
+            #[export_name = "renamed"]
+            extern "C" fn exported_under_another_name() {}
+            #[inline]
+            fn inlined() {}
+            "#,
+        )]);
+        for name in [
+            "crate::checks",
+            "crate::fixture",
+            "crate::checks_async",
+            "crate::called_from_c",
+            "crate::exported_under_another_name",
+        ] {
+            assert_eq!(dead[name], None, "{name}");
+        }
+        assert!(dead["crate::inlined"].is_some());
+    }
+
+    #[test]
+    fn item_level_macro_references_are_roots() {
+        let dead = unreachable(&[(
+            "src/main.rs",
+            r#"
+            fn main() {}
+            fn bench_parse() {}
+            fn bench_score() {}
+            criterion_group!(benches, bench_parse, bench_score);
+            "#,
+        )]);
+        assert_eq!(dead["crate::bench_parse"], None);
+        assert_eq!(dead["crate::bench_score"], None);
+    }
+
+    #[test]
+    fn functions_reached_through_unresolved_calls_are_not_dead() {
+        let dead = unreachable(&[
+            (
+                "src/main.rs",
+                r#"
+                mod util;
+                fn main() {
+                    // Not imported, so the call cannot be pinned to `util`.
+                    helper();
+                }
+                "#,
+            ),
+            (
+                "src/util.rs",
+                r#"
+                pub fn helper() { inner(); }
+                fn inner() {}
+                pub fn unused() {}
+                "#,
+            ),
+        ]);
+        assert_eq!(dead["crate::util::helper"], None);
+        assert_eq!(dead["crate::util::inner"], None);
+        assert!(dead["crate::util::unused"].is_some());
+    }
+}
@@ -0,0 +1,439 @@
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context, Result};
+use clap::{Args, Parser, Subcommand, ValueEnum};
+
+use crate::{
+    complexity,
+    config::GravityConfig,
+    crates,
+    gates,
+    graph::DependencyGraph,
+    layers,
+    models::{DependencyKind, GateReport, GateViolation},
+    parser,
+    report::Report,
+    resolve,
+    sarif,
+    walk,
+};
+
+#[derive(Debug, Parser)]
+#[command(name = "gravity", version, about = "Code intelligence for Rust projects")]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Start the HTTP API (the default). Needs DATABASE_URL.
+    Serve(ServeArgs),
+    /// Analyze a directory in memory and print the results. No database needed.
+    Analyze(AnalyzeArgs),
+    /// Analyze a directory and fail when a quality gate from gravity.toml is not met.
+    Check(CheckArgs),
+}
+
+#[derive(Debug, Default, Args)]
+pub struct ServeArgs {
+    /// Analyze this directory now and again whenever a Rust source in it
+    /// changes, pushing each run to open dashboards.
+    #[arg(long, value_name = "PATH")]
+    pub watch: Option<String>,
+}
+
+#[derive(Debug, Args)]
+pub struct AnalyzeArgs {
+    /// Root of the Rust project to analyze.
+    pub path: String,
+
+    #[arg(long, value_enum, default_value_t = OutputFormat::Table)]
+    pub format: OutputFormat,
+}
+
+#[derive(Debug, Args)]
+pub struct CheckArgs {
+    /// Root of the Rust project to check.
+    pub path: String,
+
+    /// Gate configuration; defaults to gravity.toml in the project root.
+    #[arg(long)]
+    pub config: Option<PathBuf>,
+
+    /// JSON report from an earlier `analyze --format json`, used to tell which
+    /// dead code candidates are new.
+    #[arg(long)]
+    pub baseline: Option<PathBuf>,
+
+    #[arg(long, value_enum, default_value_t = OutputFormat::Table)]
+    pub format: OutputFormat,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    Json,
+    Table,
+    Markdown,
+    /// SARIF 2.1.0, for code-scanning tools.
+    Sarif,
+}
+
+/// Runs the parse → score → graph pipeline on `path` without touching a database.
+pub fn analyze(path: &str, config: &GravityConfig) -> Result<Report> {
+    if !Path::new(path).is_dir() {
+        bail!("{path} is not a directory");
+    }
+
+    let files = walk::rust_files(Path::new(path), &config.files);
+    let crates = crates::load(Path::new(path), &files.manifests);
+    let mut parsed_files = parser::parse_files(Path::new(path), &files.paths, |_, _| {})?;
+    resolve::link(&mut parsed_files, &crates);
+    let complexity_scores = complexity::compute_all(&parsed_files);
+    let dep_graph = DependencyGraph::from_parsed(&parsed_files);
+    let layer_violations = layers::violations(&config.layers, &dep_graph, &parsed_files);
+
+    Ok(Report::build(
+        path,
+        &parsed_files,
+        files.skipped,
+        &crates,
+        &dep_graph,
+        &complexity_scores,
+        layer_violations,
+    ))
+}
+
+pub fn run_analyze(args: &AnalyzeArgs) -> Result<()> {
+    let config = GravityConfig::load(Path::new(&args.path))?;
+    let report = analyze(&args.path, &config)?;
+    print!("{}", render(&report, args.format, &config)?);
+    Ok(())
+}
+
+/// Returns whether every gate passed.
+pub fn run_check(args: &CheckArgs) -> Result<bool> {
+    let config = match &args.config {
+        Some(path) => GravityConfig::from_file(path)?,
+        None => GravityConfig::load(Path::new(&args.path))?,
+    };
+    let baseline = match &args.baseline {
+        Some(path) => {
+            let text = std::fs::read_to_string(path)
+                .with_context(|| format!("Failed to read {}", path.display()))?;
+            let report: Report = serde_json::from_str(&text)
+                .with_context(|| format!("{} is not a gravity JSON report", path.display()))?;
+            Some(report.snapshot())
+        }
+        None => None,
+    };
+
+    let report = analyze(&args.path, &config)?;
+    let gate_report = gates::evaluate(&config.gates, &report.snapshot(), baseline.as_ref());
+
+    // SARIF carries the findings themselves; the exit code still reflects the gates.
+    let output = match args.format {
+        OutputFormat::Sarif => render(&report, OutputFormat::Sarif, &config)?,
+        format => render_gates(&gate_report, format)?,
+    };
+    print!("{output}");
+    Ok(gate_report.passed)
+}
+
+pub fn render(report: &Report, format: OutputFormat, config: &GravityConfig) -> Result<String> {
+    Ok(match format {
+        OutputFormat::Json => serde_json::to_string_pretty(report)? + "\n",
+        OutputFormat::Table => render_table(report),
+        OutputFormat::Markdown => render_markdown(report),
+        OutputFormat::Sarif => {
+            serde_json::to_string_pretty(&sarif::export(&report.snapshot(), &config.gates))? + "\n"
+        }
+    })
+}
+
+fn render_table(report: &Report) -> String {
+    let s = &report.summary;
+    let mut out = String::new();
+
+    let _ = writeln!(out, "Gravity analysis of {}", report.root);
+    let _ = writeln!(
+        out,
+        "Files: {}  Functions: {}  Structs: {}  Imports: {}  Avg complexity: {:.1}",
+        s.total_files, s.total_functions, s.total_structs, s.total_imports, s.avg_complexity
+    );
+    if !s.symbol_counts.is_empty() {
+        let _ = writeln!(out, "Symbols: {}", symbol_counts(&s.symbol_counts));
+    }
+    let _ = writeln!(
+        out,
+        "Comment ratio: {:.0}%  Doc coverage: {:.0}%",
+        s.comment_ratio * 100.0,
+        s.doc_coverage * 100.0
+    );
+    if let Some(skipped) = walk::describe(&report.skipped) {
+        let _ = writeln!(out, "Skipped: {skipped}");
+    }
+    let _ = writeln!(
+        out,
+        "Graph: {} nodes, {} edges",
+        report.graph.nodes.len(),
+        report.graph.edges.len()
+    );
+
+    if !s.crates.is_empty() {
+        let _ = writeln!(out, "\nCrates ({}):", s.crates.len());
+        for c in &s.crates {
+            let _ = writeln!(
+                out,
+                "  - {}{} ({}): {} files, {} functions, {} lines, avg complexity {:.1}, docs {:.0}%, {} unreachable [{}]",
+                c.name,
+                c.version.as_deref().map(|v| format!(" {v}")).unwrap_or_default(),
+                if c.path.is_empty() { "." } else { &c.path },
+                c.files,
+                c.functions,
+                c.lines,
+                c.avg_complexity,
+                c.doc_coverage * 100.0,
+                c.dead_code,
+                c.targets.join(", ")
+            );
+            let uses = local_dependencies(report, &c.name);
+            if !uses.is_empty() {
+                let _ = writeln!(out, "      uses {}", uses.join(", "));
+            }
+        }
+    }
+
+    let _ = writeln!(out, "\nArchitecture notes:");
+    for note in &s.architecture_notes {
+        let _ = writeln!(out, "  - {note}");
+    }
+
+    if !report.layer_violations.is_empty() {
+        let _ = writeln!(out, "\nLayer violations ({}):", report.layer_violations.len());
+        for v in &report.layer_violations {
+            let _ = writeln!(out, "  - {}", layers::describe(v));
+        }
+    }
+
+    if !report.dead_code.is_empty() {
+        let _ = writeln!(out, "\nUnreachable functions ({}):", s.dead_code_count);
+        for item in &report.dead_code {
+            let _ = writeln!(
+                out,
+                "  - {}:{}: {} ({})",
+                item.file_path, item.line_start, item.qualified_name, item.reason
+            );
+        }
+    }
+
+    let name_width = report
+        .complexity
+        .iter()
+        .map(|c| c.qualified_name.len())
+        .chain(["FUNCTION".len()])
+        .max()
+        .unwrap_or_default();
+
+    let _ = writeln!(
+        out,
+        "\n{:>5}  {:>9}  {:>5}  {:>4}  {:>6}  {:>4}  {:<11}  {:<name_width$}  FILE",
+        "SCORE", "COGNITIVE", "MI", "LOC", "PARAMS", "NEST", "LINES", "FUNCTION"
+    );
+    for item in &report.complexity {
+        let lines = format!("{}-{}", item.line_start, item.line_end);
+        let _ = writeln!(
+            out,
+            "{:>5}  {:>9}  {:>5.1}  {:>4}  {:>6}  {:>4}  {:<11}  {:<name_width$}  {}",
+            item.score,
+            item.cognitive,
+            item.maintainability_index,
+            item.logical_lines,
+            item.params,
+            item.max_nesting,
+            lines,
+            item.qualified_name,
+            item.file_path
+        );
+    }
+
+    out
+}
+
+fn render_markdown(report: &Report) -> String {
+    let s = &report.summary;
+    let mut out = String::new();
+
+    let _ = writeln!(out, "# Gravity analysis of `{}`\n", report.root);
+    let _ = writeln!(out, "| Files | Functions | Structs | Imports | Avg complexity | Comment ratio | Doc coverage |");
+    let _ = writeln!(out, "|------:|----------:|--------:|--------:|---------------:|--------------:|-------------:|");
+    let _ = writeln!(
+        out,
+        "| {} | {} | {} | {} | {:.1} | {:.0}% | {:.0}% |\n",
+        s.total_files,
+        s.total_functions,
+        s.total_structs,
+        s.total_imports,
+        s.avg_complexity,
+        s.comment_ratio * 100.0,
+        s.doc_coverage * 100.0
+    );
+
+    if !s.symbol_counts.is_empty() {
+        let _ = writeln!(out, "Symbols: {}\n", symbol_counts(&s.symbol_counts));
+    }
+    if let Some(skipped) = walk::describe(&report.skipped) {
+        let _ = writeln!(out, "Skipped: {skipped}\n");
+    }
+
+    if !s.crates.is_empty() {
+        let _ = writeln!(out, "## Crates\n");
+        let _ = writeln!(out, "| Crate | Path | Targets | Files | Functions | Lines | Avg complexity | Doc coverage | Unreachable | Uses |");
+        let _ = writeln!(out, "|-------|------|---------|------:|----------:|------:|---------------:|-------------:|------------:|------|");
+        for c in &s.crates {
+            let _ = writeln!(
+                out,
+                "| `{}` | `{}` | {} | {} | {} | {} | {:.1} | {:.0}% | {} | {} |",
+                c.name,
+                if c.path.is_empty() { "." } else { &c.path },
+                c.targets.join(", "),
+                c.files,
+                c.functions,
+                c.lines,
+                c.avg_complexity,
+                c.doc_coverage * 100.0,
+                c.dead_code,
+                local_dependencies(report, &c.name).join(", ")
+            );
+        }
+        let _ = writeln!(out);
+    }
+
+    let _ = writeln!(out, "## Architecture notes\n");
+    for note in &s.architecture_notes {
+        let _ = writeln!(out, "- {note}");
+    }
+
+    if !report.layer_violations.is_empty() {
+        let _ = writeln!(out, "\n## Layer violations\n");
+        let _ = writeln!(out, "| From | To | Use | Location |");
+        let _ = writeln!(out, "|------|----|-----|----------|");
+        for v in &report.layer_violations {
+            let _ = writeln!(
+                out,
+                "| {} | {} | `{}` | `{}:{}` |",
+                v.from_layer, v.to_layer, v.use_path, v.file_path, v.line
+            );
+        }
+    }
+
+    if !report.dead_code.is_empty() {
+        let _ = writeln!(out, "\n## Unreachable functions\n");
+        let _ = writeln!(out, "| Function | File | Lines | Reason |");
+        let _ = writeln!(out, "|----------|------|-------|--------|");
+        for item in &report.dead_code {
+            let _ = writeln!(
+                out,
+                "| `{}` | `{}` | {}-{} | {} |",
+                item.qualified_name, item.file_path, item.line_start, item.line_end, item.reason
+            );
+        }
+    }
+
+    let _ = writeln!(out, "\n## Complexity\n");
+    let _ = writeln!(out, "| Score | Cognitive | MI | LOC | Params | Nesting | Function | File | Lines |");
+    let _ = writeln!(out, "|------:|----------:|---:|----:|-------:|--------:|----------|------|-------|");
+    for item in &report.complexity {
+        let _ = writeln!(
+            out,
+            "| {} | {} | {:.1} | {} | {} | {} | `{}` | `{}` | {}-{} |",
+            item.score,
+            item.cognitive,
+            item.maintainability_index,
+            item.logical_lines,
+            item.params,
+            item.max_nesting,
+            item.qualified_name,
+            item.file_path,
+            item.line_start,
+            item.line_end
+        );
+    }
+
+    out
+}
+
+/// Analyzed packages `krate` depends on, with the kind for dev and build ones.
+fn local_dependencies(report: &Report, krate: &str) -> Vec<String> {
+    report
+        .crate_dependencies
+        .iter()
+        .filter(|d| d.local && d.from == krate)
+        .map(|d| match d.kind {
+            DependencyKind::Normal => d.to.clone(),
+            kind => format!("{} ({})", d.to, kind.as_str()),
+        })
+        .collect()
+}
+
+/// `enum 2, struct 5, trait 1`
+fn symbol_counts(counts: &BTreeMap<String, i64>) -> String {
+    counts
+        .iter()
+        .map(|(kind, count)| format!("{kind} {count}"))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+pub fn render_gates(report: &GateReport, format: OutputFormat) -> Result<String> {
+    if format == OutputFormat::Json {
+        return Ok(serde_json::to_string_pretty(report)? + "\n");
+    }
+
+    let markdown = format == OutputFormat::Markdown;
+    let mut out = String::new();
+    if markdown {
+        let _ = writeln!(out, "# Gravity quality gates\n");
+    }
+    if report.gates.is_empty() {
+        let _ = writeln!(out, "No quality gates configured in gravity.toml.");
+        return Ok(out);
+    }
+
+    for gate in &report.gates {
+        let verdict = if gate.passed { "PASS" } else { "FAIL" };
+        let heading = format!("{verdict} {} (threshold {}, actual {})", gate.gate, gate.threshold, gate.actual);
+        if markdown {
+            let _ = writeln!(out, "- **{heading}**");
+        } else {
+            let _ = writeln!(out, "{heading}");
+        }
+        for violation in &gate.violations {
+            let indent = if markdown { "  - " } else { "  " };
+            let _ = writeln!(out, "{indent}{}", format_violation(violation));
+        }
+    }
+
+    let failed = report.gates.iter().filter(|g| !g.passed).count();
+    let _ = writeln!(
+        out,
+        "\n{}",
+        if failed == 0 {
+            format!("All {} quality gates passed.", report.gates.len())
+        } else {
+            format!("{failed} of {} quality gates failed.", report.gates.len())
+        }
+    );
+    Ok(out)
+}
+
+/// `file:line: message`, dropping whichever location parts are unknown.
+fn format_violation(violation: &GateViolation) -> String {
+    match (&violation.file_path, violation.line) {
+        (Some(path), Some(line)) => format!("{path}:{line}: {}", violation.message),
+        (Some(path), None) => format!("{path}: {}", violation.message),
+        _ => violation.message.clone(),
+    }
+}
@@ -1,58 +1,259 @@
-use tree_sitter::{Language, Node, Parser};
-use anyhow::{Context, Result};
+use tree_sitter::Node;
+use rayon::prelude::*;
 
-use crate::models::ParsedFunction;
+use std::collections::{HashMap, HashSet};
 
-extern "C" {
-    fn tree_sitter_rust() -> Language;
+use crate::models::{FunctionRef, Halstead, ParsedFile, ParsedFunction};
+
+/// Scores of one function.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FunctionScores {
+    pub cyclomatic: usize,
+    pub cognitive: usize,
+    pub halstead: Halstead,
+    pub maintainability_index: f64,
 }
 
-const BRANCH_KINDS: &[&str] = &[
-    "if_expression",
-    "else_clause",
-    "match_expression",
-    "match_arm",
-    "for_expression",
-    "while_expression",
-    "loop_expression",
-    "binary_expression", 
-    "try_expression",   
-    "closure_expression",
+impl Default for FunctionScores {
+    fn default() -> Self {
+        FunctionScores {
+            cyclomatic: 1,
+            cognitive: 0,
+            halstead: Halstead::default(),
+            maintainability_index: 100.0,
+        }
+    }
+}
+
+/// Leaves whose text is an operand; every other token is an operator.
+const OPERAND_KINDS: &[&str] = &[
+    "identifier",
+    "field_identifier",
+    "type_identifier",
+    "shorthand_field_identifier",
+    "primitive_type",
+    "integer_literal",
+    "float_literal",
+    "boolean_literal",
+    "string_literal",
+    "raw_string_literal",
+    "char_literal",
+    "self",
+    "metavariable",
 ];
 
-pub fn compute_complexity(func: &ParsedFunction) -> Result<usize> {
-    let mut parser = Parser::new();
-    let lang = unsafe { tree_sitter_rust() };
-    parser
-        .set_language(lang)
-        .context("Failed to set language for complexity parser")?;
+/// Closing halves of a pair, counted once with the opening token.
+const CLOSING_TOKENS: &[&str] = &[")", "]", "}"];
+
+/// Halstead counts over the tokens under `node`. Literals are single
+/// operands; comments are skipped.
+pub fn halstead(node: &Node, source: &str) -> Halstead {
+    let mut operators: HashSet<&str> = HashSet::new();
+    let mut operands: HashSet<&str> = HashSet::new();
+    let mut counts = Halstead::default();
+    count_tokens(node, source, &mut operators, &mut operands, &mut counts);
+    counts.distinct_operators = operators.len();
+    counts.distinct_operands = operands.len();
+    counts
+}
 
-    let tree = parser
-        .parse(&func.body_source, None)
-        .context("Failed to parse function body")?;
+fn count_tokens<'s>(
+    node: &Node,
+    source: &'s str,
+    operators: &mut HashSet<&'s str>,
+    operands: &mut HashSet<&'s str>,
+    counts: &mut Halstead,
+) {
+    let kind = node.kind();
+    if node.is_extra() || kind.ends_with("comment") {
+        return;
+    }
+    if OPERAND_KINDS.contains(&kind) {
+        operands.insert(node.utf8_text(source.as_bytes()).unwrap_or_default());
+        counts.operands += 1;
+        return;
+    }
+    if node.child_count() == 0 {
+        if !CLOSING_TOKENS.contains(&kind) {
+            operators.insert(kind);
+            counts.operators += 1;
+        }
+        return;
+    }
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        count_tokens(&child, source, operators, operands, counts);
+    }
+}
+
+/// The Maintainability Index as Visual Studio reports it, scaled to 0–100:
+/// `(171 − 5.2 ln V − 0.23 CC − 16.2 ln LOC) · 100 / 171`, floored at 0.
+/// 20 and above is maintainable, 10–19 needs attention, below 10 is poor.
+pub fn maintainability_index(volume: f64, cyclomatic: usize, lines: usize) -> f64 {
+    scaled_index(volume, cyclomatic as f64, lines as f64)
+}
+
+fn scaled_index(volume: f64, cyclomatic: f64, lines: f64) -> f64 {
+    let ln = |x: f64| if x > 1.0 { x.ln() } else { 0.0 };
+    let raw = 171.0 - 5.2 * ln(volume) - 0.23 * cyclomatic - 16.2 * ln(lines);
+    (raw * 100.0 / 171.0).clamp(0.0, 100.0)
+}
+
+/// Maintainability Index of a whole file. As in the original per-module
+/// formula this takes the average volume, complexity and length of its
+/// functions; totals would push every large file to 0. A file without
+/// functions is scored as a whole.
+pub fn file_maintainability_index(file: &ParsedFile, scores: &ScoreMap) -> f64 {
+    let functions: Vec<(&ParsedFunction, &FunctionScores)> = file
+        .functions
+        .iter()
+        .filter_map(|func| {
+            let s = scores.get(&(file.path.clone(), func.qualified_name.clone()))?;
+            Some((func, s))
+        })
+        .collect();
+    if functions.is_empty() {
+        return maintainability_index(file.halstead.volume(), 1, file.line_count);
+    }
+
+    let n = functions.len() as f64;
+    let volume = functions.iter().map(|(_, s)| s.halstead.volume()).sum::<f64>() / n;
+    let cyclomatic = functions.iter().map(|(_, s)| s.cyclomatic).sum::<usize>() as f64 / n;
+    let lines = functions.iter().map(|(f, _)| f.line_end + 1 - f.line_start).sum::<usize>() as f64 / n;
+    scaled_index(volume, cyclomatic, lines)
+}
+
+/// McCabe's cyclomatic complexity of the function at `node`.
+pub fn cyclomatic(node: &Node) -> usize {
+    let mut count = 0usize;
+    count_branches(node, &mut count);
+    count + 1 // baseline complexity = 1
+}
 
+/// SonarSource-style cognitive complexity of the function at `node`:
+/// structures that break the linear flow cost 1, plus their nesting depth for
+/// `if`, `match` and loops; `?` costs nothing. Recursive calls add 1 each on
+/// top, which `compute_all` counts once calls are resolved.
+pub fn cognitive(node: &Node) -> usize {
     let mut count = 0usize;
-    count_branches(&tree.root_node(), &func.body_source, &mut count);
+    if let Some(body) = node.child_by_field_name("body") {
+        count_cognitive(&body, 0, &mut count);
+    }
+    count
+}
+
+/// Scores by (file_path, qualified_name), as returned by `compute_all`.
+pub type ScoreMap = HashMap<(String, String), FunctionScores>;
 
-    Ok(count + 1) // baseline complexity = 1
+/// The scores of `func`, or the defaults when `compute_all` did not see it.
+pub fn scores_of(scores: &ScoreMap, file: &ParsedFile, func: &ParsedFunction) -> FunctionScores {
+    scores
+        .get(&(file.path.clone(), func.qualified_name.clone()))
+        .copied()
+        .unwrap_or_default()
 }
 
-pub fn compute_all(
-    files: &[crate::models::ParsedFile],
-) -> Vec<(String, String, usize)> {
-    let mut results = Vec::new();
+/// Completes the scores the parser read off each function of `files`, after
+/// `resolve::link`.
+pub fn compute_all<'a>(files: impl IntoParallelIterator<Item = &'a ParsedFile>) -> ScoreMap {
+    files
+        .into_par_iter()
+        .flat_map_iter(|pf| {
+            pf.functions.iter().enumerate().map(move |(index, func)| {
+                let this = FunctionRef { file: pf.path.clone(), index };
+                let recursive_calls = func
+                    .calls
+                    .iter()
+                    .filter(|c| !c.is_reference && c.target.as_ref() == Some(&this))
+                    .count();
+                let lines = func.line_end + 1 - func.line_start;
+                let scores = FunctionScores {
+                    cyclomatic: func.cyclomatic,
+                    cognitive: func.cognitive + recursive_calls,
+                    halstead: func.halstead,
+                    maintainability_index: maintainability_index(func.halstead.volume(), func.cyclomatic, lines),
+                };
+                ((pf.path.clone(), func.qualified_name.clone()), scores)
+            })
+        })
+        .collect()
+}
 
-    for pf in files {
-        for func in &pf.functions {
-            let score = compute_complexity(func).unwrap_or(1);
-            results.push((pf.path.clone(), func.name.clone(), score));
+fn count_cognitive(node: &Node, nesting: usize, count: &mut usize) {
+    let mut cursor = node.walk();
+    match node.kind() {
+        // Scored on their own.
+        "function_item" => {}
+        "if_expression" => {
+            // `else if` continues the chain at the level of the first `if`.
+            let else_if = node.parent().is_some_and(|p| p.kind() == "else_clause");
+            *count += if else_if { 1 } else { 1 + nesting };
+            for child in node.children(&mut cursor) {
+                let depth = if Some(child) == node.child_by_field_name("consequence") {
+                    nesting + 1
+                } else {
+                    nesting
+                };
+                count_cognitive(&child, depth, count);
+            }
+        }
+        "else_clause" => {
+            for child in node.children(&mut cursor) {
+                if child.kind() == "block" {
+                    *count += 1;
+                    count_cognitive(&child, nesting + 1, count);
+                } else {
+                    count_cognitive(&child, nesting, count);
+                }
+            }
+        }
+        "match_expression" | "for_expression" | "while_expression" | "loop_expression" => {
+            *count += 1 + nesting;
+            let body = node.child_by_field_name("body");
+            for child in node.children(&mut cursor) {
+                let depth = if Some(child) == body { nesting + 1 } else { nesting };
+                count_cognitive(&child, depth, count);
+            }
+        }
+        "closure_expression" => {
+            for child in node.children(&mut cursor) {
+                count_cognitive(&child, nesting + 1, count);
+            }
+        }
+        "break_expression" | "continue_expression" => {
+            if node.children(&mut cursor).any(|c| c.kind() == "label") {
+                *count += 1;
+            }
+        }
+        _ => {
+            // Each run of the same logical operator counts once: `a && b && c`
+            // is 1, `a && b || c` is 2.
+            if let Some(op) = logical_operator(node) {
+                let continues_run = node.parent().and_then(|p| logical_operator(&p)) == Some(op);
+                if !continues_run {
+                    *count += 1;
+                }
+            }
+            for child in node.children(&mut cursor) {
+                count_cognitive(&child, nesting, count);
+            }
         }
     }
+}
 
-    results
+/// `&&` or `||` for a logical binary expression.
+fn logical_operator(node: &Node) -> Option<&'static str> {
+    if node.kind() != "binary_expression" {
+        return None;
+    }
+    match node.child_by_field_name("operator")?.kind() {
+        "&&" => Some("&&"),
+        "||" => Some("||"),
+        _ => None,
+    }
 }
 
-fn count_branches(node: &Node, source: &str, count: &mut usize) {
+fn count_branches(node: &Node, count: &mut usize) {
     match node.kind() {
         "if_expression" => *count += 1,
         "else_clause" => {
@@ -84,6 +285,194 @@ fn count_branches(node: &Node, source: &str, count: &mut usize) {
 
     let mut cursor = node.walk();
     for child in node.children(&mut cursor) {
-        count_branches(&child, source, count);
+        count_branches(&child, count);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser;
+
+    /// Cognitive complexity of each top-level function in `source`, by name.
+    /// Bare calls are pointed at the function of that name, standing in for
+    /// `resolve::link`, which the parse bench does not build.
+    fn cognitive_scores(source: &str) -> HashMap<String, usize> {
+        let mut files = parser::parse_sources(&[("src/main.rs", source)]);
+        let file = &mut files[0];
+        let names: HashMap<String, usize> =
+            file.functions.iter().enumerate().map(|(i, f)| (f.name.clone(), i)).collect();
+        for func in &mut file.functions {
+            func.qualified_name = func.name.clone();
+            for call in &mut func.calls {
+                if let [name] = call.path.as_slice() {
+                    call.target = names
+                        .get(name)
+                        .map(|&index| FunctionRef { file: file.path.clone(), index });
+                }
+            }
+        }
+        let scores = compute_all(&files);
+        files[0]
+            .functions
+            .iter()
+            .map(|f| (f.name.clone(), scores_of(&scores, &files[0], f).cognitive))
+            .collect()
+    }
+
+    #[test]
+    fn sum_of_primes_from_the_spec_scores_7() {
+        let scores = cognitive_scores(
+            r#"
+            fn sum_of_primes(max: u32) -> u32 {
+                let mut total = 0;
+                'out: for i in 1..=max {      // +1
+                    for j in 2..i {           // +2 (nesting 1)
+                        if i % j == 0 {       // +3 (nesting 2)
+                            continue 'out;    // +1
+                        }
+                    }
+                    total += i;
+                }
+                total
+            }
+            "#,
+        );
+        assert_eq!(scores["sum_of_primes"], 7);
+    }
+
+    #[test]
+    fn get_words_from_the_spec_scores_1() {
+        let scores = cognitive_scores(
+            r#"
+            fn get_words(number: u32) -> &'static str {
+                match number {                // +1
+                    1 => "one",
+                    2 => "a couple",
+                    3 => "a few",
+                    _ => "lots",
+                }
+            }
+            "#,
+        );
+        assert_eq!(scores["get_words"], 1);
+    }
+
+    #[test]
+    fn else_if_and_else_cost_one_without_nesting() {
+        let scores = cognitive_scores(
+            r#"
+            fn chain(a: bool, b: bool) -> u8 {
+                if a { 1 }                    // +1
+                else if b { 2 }               // +1
+                else { 3 }                    // +1
+            }
+            fn nested_chain(xs: &[bool]) {
+                for x in xs {                 // +1
+                    if *x {                   // +2 (nesting 1)
+                    } else if !*x {           // +1
+                    } else {                  // +1
+                    }
+                }
+            }
+            "#,
+        );
+        assert_eq!(scores["chain"], 3);
+        assert_eq!(scores["nested_chain"], 5);
+    }
+
+    #[test]
+    fn nesting_increments_through_structures_and_closures() {
+        let scores = cognitive_scores(
+            r#"
+            fn nested(a: Option<u8>, b: bool) {
+                if b {                        // +1
+                    match a {                 // +2 (nesting 1)
+                        Some(_) => {
+                            while b {}        // +3 (nesting 2)
+                        }
+                        None => {}
+                    }
+                }
+            }
+            fn in_closure(xs: Vec<u8>) -> Vec<u8> {
+                xs.into_iter()
+                    .map(|x| if x > 1 { x } else { 0 })  // +2 (nesting 1), +1
+                    .collect()
+            }
+            fn with_try(s: &str) -> Result<u8, std::num::ParseIntError> {
+                let n = s.parse::<u8>()?;     // `?` is free
+                Ok(n)
+            }
+            "#,
+        );
+        assert_eq!(scores["nested"], 6);
+        assert_eq!(scores["in_closure"], 3);
+        assert_eq!(scores["with_try"], 0);
+    }
+
+    #[test]
+    fn only_labelled_jumps_cost_one() {
+        let scores = cognitive_scores(
+            r#"
+            fn jumps() {
+                'outer: loop {                // +1
+                    loop {                    // +2 (nesting 1)
+                        break 'outer;         // +1
+                    }
+                    continue;
+                }
+            }
+            fn plain_jumps() {
+                loop {                        // +1
+                    if true { break; }        // +2 (nesting 1)
+                    continue;
+                }
+            }
+            "#,
+        );
+        assert_eq!(scores["jumps"], 4);
+        assert_eq!(scores["plain_jumps"], 3);
+    }
+
+    #[test]
+    fn each_run_of_a_logical_operator_costs_one() {
+        let scores = cognitive_scores(
+            r#"
+            fn same(a: bool, b: bool, c: bool) -> bool {
+                a && b && c                   // +1
+            }
+            fn mixed(a: bool, b: bool, c: bool, d: bool, e: bool, f: bool) -> bool {
+                if a && b && c || d || e && f { // +1 if, +3 for `&&`, `||`, `&&`
+                    return true;
+                }
+                false
+            }
+            fn alternating(a: bool, b: bool, c: bool, d: bool) -> bool {
+                a && b || c && d              // +3
+            }
+            "#,
+        );
+        assert_eq!(scores["same"], 1);
+        assert_eq!(scores["mixed"], 4);
+        assert_eq!(scores["alternating"], 3);
+    }
+
+    #[test]
+    fn recursive_calls_cost_one_each() {
+        let scores = cognitive_scores(
+            r#"
+            fn factorial(n: u64) -> u64 {
+                if n == 0 { 1 }               // +1
+                else { n * factorial(n - 1) } // +1, +1 for recursion
+            }
+            fn fib(n: u64) -> u64 {
+                if n < 2 { return n; }        // +1
+                fib(n - 1) + fib(n - 2)       // +1 per recursive call
+            }
+            "#,
+        );
+        assert_eq!(scores["factorial"], 3);
+        assert_eq!(scores["fib"], 3);
     }
 }
@@ -0,0 +1,90 @@
+use std::path::Path;
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+
+/// Name of the per-project configuration file, looked up at the analysis root.
+pub const CONFIG_FILE: &str = "gravity.toml";
+
+/// Contents of `gravity.toml`. Every section is optional.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct GravityConfig {
+    pub files: FilesConfig,
+    pub gates: GateConfig,
+    pub layers: Vec<LayerConfig>,
+}
+
+/// Which `.rs` files under the root are analyzed, on top of `.gitignore`,
+/// `.ignore` and `.gravityignore`. Globs are over `/`-separated paths relative
+/// to the root, such as `src/**` or `**/generated/*.rs`. With `include` set,
+/// only matching files are analyzed; `exclude` wins over `include`. Cargo
+/// `target/` directories are skipped unless `include_target` is set.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct FilesConfig {
+    pub include: Vec<String>,
+    pub exclude: Vec<String>,
+    pub include_target: bool,
+}
+
+/// Quality gate thresholds. A gate that is left unset is not evaluated.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct GateConfig {
+    pub max_function_complexity: Option<i32>,
+    pub max_avg_complexity: Option<f64>,
+    pub max_file_lines: Option<i32>,
+    pub forbid_cycles: bool,
+    pub max_new_dead_code: Option<usize>,
+}
+
+/// One `[[layers]]` entry. `modules` are glob patterns over module paths
+/// such as `crate::api::**`, where `*` matches within one segment and `**`
+/// any number of segments. A file belongs to the first layer that matches.
+/// With `allow` set, the layer may only use itself and the listed layers;
+/// `forbid` names layers it must never use. Files outside every layer are
+/// not checked.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LayerConfig {
+    pub name: String,
+    pub modules: Vec<String>,
+    pub allow: Option<Vec<String>>,
+    pub forbid: Vec<String>,
+}
+
+impl GravityConfig {
+    /// Reads `gravity.toml` from `root`, falling back to defaults when absent.
+    pub fn load(root: &Path) -> Result<Self> {
+        let path = root.join(CONFIG_FILE);
+        if !path.is_file() {
+            return Ok(Self::default());
+        }
+        Self::from_file(&path)
+    }
+
+    pub fn from_file(path: &Path) -> Result<Self> {
+        let text = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        let config: Self =
+            toml::from_str(&text).with_context(|| format!("Invalid {}", path.display()))?;
+        config.check_layers().with_context(|| format!("Invalid {}", path.display()))?;
+        Ok(config)
+    }
+
+    fn check_layers(&self) -> Result<()> {
+        for (i, layer) in self.layers.iter().enumerate() {
+            if self.layers[..i].iter().any(|l| l.name == layer.name) {
+                bail!("layer `{}` is declared twice", layer.name);
+            }
+            let referenced = layer.allow.iter().flatten().chain(&layer.forbid);
+            for name in referenced {
+                if !self.layers.iter().any(|l| &l.name == name) {
+                    bail!("layer `{}` refers to unknown layer `{name}`", layer.name);
+                }
+            }
+        }
+        Ok(())
+    }
+}
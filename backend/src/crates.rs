@@ -0,0 +1,356 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use toml::{Table, Value};
+
+use crate::layers::glob_matches;
+use crate::models::{
+    CrateDependency, CrateManifest, CrateTarget, DependencyKind, GraphData, GraphEdge, GraphNode,
+    ParsedFile, TargetKind,
+};
+use crate::resolve::{self, ModuleTree};
+use crate::walk;
+
+/// A `Cargo.toml` as read from disk.
+struct Manifest {
+    /// Directory relative to the root, `/`-separated; empty for the root.
+    dir: String,
+    table: Table,
+}
+
+/// A dependency as declared, before it is matched against the packages found.
+struct Declared {
+    name: String,
+    kind: DependencyKind,
+    /// Directory of a `path` dependency, relative to the root.
+    path: Option<String>,
+}
+
+/// Dependency tables and the kind of dependency each declares.
+const DEPENDENCY_TABLES: &[(&str, DependencyKind)] = &[
+    ("dependencies", DependencyKind::Normal),
+    ("dev-dependencies", DependencyKind::Dev),
+    ("build-dependencies", DependencyKind::Build),
+];
+
+/// Target tables other than `[lib]`: the kind they declare, the `auto*` key
+/// that turns off discovery and the directory targets are discovered in.
+const TARGET_TABLES: &[(&str, TargetKind, &str, &str)] = &[
+    ("bin", TargetKind::Bin, "autobins", "src/bin"),
+    ("test", TargetKind::Test, "autotests", "tests"),
+    ("example", TargetKind::Example, "autoexamples", "examples"),
+    ("bench", TargetKind::Bench, "autobenches", "benches"),
+];
+
+/// Reads the packages among `manifests`, which lie under `root`, ordered by
+/// path. Targets are the declared ones plus those Cargo discovers from the
+/// layout. Manifests that cannot be read are logged and left out; virtual
+/// manifests only contribute workspace settings.
+pub fn load(root: &Path, manifests: &[PathBuf]) -> Vec<CrateManifest> {
+    let manifests: Vec<Manifest> = manifests
+        .iter()
+        .filter_map(|path| match read(root, path) {
+            Ok(manifest) => Some(manifest),
+            Err(e) => {
+                tracing::warn!("Skipping {}: {e:#}", path.display());
+                None
+            }
+        })
+        .collect();
+
+    let mut packages: Vec<(CrateManifest, Vec<Declared>)> =
+        manifests.iter().filter_map(|m| package(root, m, &manifests)).collect();
+    packages.sort_by(|(a, _), (b, _)| a.path.cmp(&b.path));
+
+    // Local dependencies are recognised by their directory, whatever they are called.
+    let by_dir: HashMap<String, String> =
+        packages.iter().map(|(c, _)| (c.path.clone(), c.name.clone())).collect();
+    packages
+        .into_iter()
+        .map(|(mut krate, declared)| {
+            let mut dependencies: Vec<CrateDependency> = declared
+                .into_iter()
+                .map(|d| {
+                    let local = d.path.as_ref().and_then(|p| by_dir.get(p));
+                    CrateDependency {
+                        from: krate.name.clone(),
+                        to: local.cloned().unwrap_or(d.name),
+                        kind: d.kind,
+                        local: local.is_some(),
+                    }
+                })
+                .collect();
+            dependencies.sort_by(|a, b| (&a.to, a.kind).cmp(&(&b.to, b.kind)));
+            // Platform-specific tables often repeat a dependency.
+            dependencies.dedup();
+            krate.dependencies = dependencies;
+            krate
+        })
+        .collect()
+}
+
+fn read(root: &Path, path: &Path) -> Result<Manifest> {
+    let text = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    let table: Table = toml::from_str(&text).with_context(|| format!("Invalid {}", path.display()))?;
+    let dir = path.parent().map(|d| walk::relative_path(root, d)).unwrap_or_default();
+    Ok(Manifest { dir, table })
+}
+
+fn package(root: &Path, m: &Manifest, all: &[Manifest]) -> Option<(CrateManifest, Vec<Declared>)> {
+    let package = m.table.get("package")?.as_table()?;
+    let name = package.get("name")?.as_str()?.to_owned();
+    let workspace = workspace_of(m, package, all);
+
+    let version = match package.get("version") {
+        Some(Value::String(version)) => Some(version.clone()),
+        Some(inherited) if inherits(inherited) => workspace
+            .and_then(|w| w.table.get("workspace")?.get("package")?.get("version")?.as_str())
+            .map(str::to_owned),
+        _ => None,
+    };
+
+    let krate = CrateManifest {
+        targets: targets(root, m, package, &name),
+        name,
+        path: m.dir.clone(),
+        version,
+        workspace: workspace.map(|w| w.dir.clone()),
+        dependencies: Vec::new(),
+    };
+    Some((krate, declared_dependencies(m, workspace)))
+}
+
+/// `{ workspace = true }`: the value is taken from the workspace root.
+fn inherits(value: &Value) -> bool {
+    value.get("workspace").and_then(Value::as_bool) == Some(true)
+}
+
+/// The workspace root `m` is a member of: the one `package.workspace` points
+/// at, or else the nearest manifest above it with a `[workspace]` table,
+/// provided that lists it as a member.
+fn workspace_of<'a>(m: &Manifest, package: &Table, all: &'a [Manifest]) -> Option<&'a Manifest> {
+    let is_workspace = |w: &&Manifest| w.table.get("workspace").is_some_and(Value::is_table);
+    if let Some(dir) = package.get("workspace").and_then(Value::as_str) {
+        let dir = resolve::join(&m.dir, dir);
+        return all.iter().filter(is_workspace).find(|w| w.dir == dir);
+    }
+
+    let nearest = all
+        .iter()
+        .filter(is_workspace)
+        .filter(|w| contains(&w.dir, &m.dir))
+        .max_by_key(|w| w.dir.len())?;
+    if nearest.dir == m.dir {
+        return Some(nearest);
+    }
+    let relative = if nearest.dir.is_empty() { &m.dir[..] } else { &m.dir[nearest.dir.len() + 1..] };
+    let list = |key: &str| -> Vec<&str> {
+        nearest.table["workspace"]
+            .get(key)
+            .and_then(Value::as_array)
+            .map(|items| items.iter().filter_map(Value::as_str).collect())
+            .unwrap_or_default()
+    };
+    let member = list("members").iter().any(|glob| glob_matches(glob.trim_end_matches('/'), relative, "/"));
+    let excluded = list("exclude").iter().any(|dir| contains(&resolve::join("", dir), relative));
+    (member && !excluded).then_some(nearest)
+}
+
+/// Whether `path` is `dir` or lies under it; every path lies under the root.
+fn contains(dir: &str, path: &str) -> bool {
+    dir.is_empty() || path == dir || path.strip_prefix(dir).is_some_and(|rest| rest.starts_with('/'))
+}
+
+fn targets(root: &Path, m: &Manifest, package: &Table, name: &str) -> Vec<CrateTarget> {
+    let base = root.join(&m.dir);
+    let exists = |rel: &str| base.join(rel).is_file();
+    let auto = |key: &str| package.get(key).and_then(Value::as_bool).unwrap_or(true);
+    let target = |kind, name: &str, rel: &str| CrateTarget {
+        kind,
+        name: name.to_owned(),
+        root: resolve::join(&m.dir, rel),
+    };
+    let mut targets = Vec::new();
+
+    let lib = m.table.get("lib").and_then(Value::as_table);
+    let lib_path = match lib.and_then(|l| l.get("path")).and_then(Value::as_str) {
+        Some(path) => Some(path),
+        None if lib.is_some() || (auto("autolib") && exists("src/lib.rs")) => Some("src/lib.rs"),
+        None => None,
+    };
+    if let Some(path) = lib_path {
+        let lib_name = lib
+            .and_then(|l| l.get("name"))
+            .and_then(Value::as_str)
+            .map_or_else(|| name.replace('-', "_"), str::to_owned);
+        targets.push(target(TargetKind::Lib, &lib_name, path));
+    }
+
+    for &(key, kind, auto_key, dir) in TARGET_TABLES {
+        let declared: Vec<&Table> = m
+            .table
+            .get(key)
+            .and_then(Value::as_array)
+            .map(|items| items.iter().filter_map(Value::as_table).collect())
+            .unwrap_or_default();
+        let mut found: Vec<CrateTarget> = Vec::new();
+        for table in declared {
+            let Some(target_name) = table.get("name").and_then(Value::as_str) else { continue };
+            let path = match table.get("path").and_then(Value::as_str) {
+                Some(path) => path.to_owned(),
+                None if kind == TargetKind::Bin && target_name == name && exists("src/main.rs") => {
+                    "src/main.rs".to_owned()
+                }
+                None => {
+                    let flat = format!("{dir}/{target_name}.rs");
+                    if exists(&flat) { flat } else { format!("{dir}/{target_name}/main.rs") }
+                }
+            };
+            found.push(target(kind, target_name, &path));
+        }
+
+        if auto(auto_key) {
+            let mut discovered: Vec<(String, String)> = discover(&base.join(dir))
+                .into_iter()
+                .map(|(target_name, rel)| (target_name, format!("{dir}/{rel}")))
+                .collect();
+            if kind == TargetKind::Bin && exists("src/main.rs") {
+                discovered.insert(0, (name.to_owned(), "src/main.rs".to_owned()));
+            }
+            // A declared target overrides a discovered one of the same name or file.
+            for (target_name, rel) in discovered {
+                let t = target(kind, &target_name, &rel);
+                if !found.iter().any(|f| f.name == t.name || f.root == t.root) {
+                    found.push(t);
+                }
+            }
+        }
+        targets.extend(found);
+    }
+
+    let build = match package.get("build") {
+        Some(Value::String(path)) => Some(path.as_str()),
+        Some(Value::Boolean(false)) => None,
+        _ => exists("build.rs").then_some("build.rs"),
+    };
+    if let Some(path) = build {
+        targets.push(target(TargetKind::Build, "build-script-build", path));
+    }
+    targets
+}
+
+/// Targets Cargo finds in `dir`: `name.rs` and `name/main.rs`, by name.
+fn discover(dir: &Path) -> Vec<(String, String)> {
+    let Ok(entries) = std::fs::read_dir(dir) else { return Vec::new() };
+    let mut found: Vec<(String, String)> = entries
+        .filter_map(|e| e.ok())
+        .filter_map(|e| {
+            let file_name = e.file_name().to_str()?.to_owned();
+            let path = e.path();
+            if path.is_dir() {
+                path.join("main.rs").is_file().then(|| (file_name.clone(), format!("{file_name}/main.rs")))
+            } else {
+                let stem = file_name.strip_suffix(".rs")?;
+                Some((stem.to_owned(), file_name.clone()))
+            }
+        })
+        .collect();
+    found.sort();
+    found
+}
+
+/// Dependencies from the plain and the `[target.'cfg(..)'.*]` tables.
+fn declared_dependencies(m: &Manifest, workspace: Option<&Manifest>) -> Vec<Declared> {
+    let platforms = m
+        .table
+        .get("target")
+        .and_then(Value::as_table)
+        .into_iter()
+        .flat_map(|t| t.values().filter_map(Value::as_table));
+    let mut declared = Vec::new();
+    for table in std::iter::once(&m.table).chain(platforms) {
+        for &(key, kind) in DEPENDENCY_TABLES {
+            let Some(dependencies) = table.get(key).and_then(Value::as_table) else { continue };
+            for (key, spec) in dependencies {
+                declared.push(declare(m, workspace, key, spec, kind));
+            }
+        }
+    }
+    declared
+}
+
+fn declare(m: &Manifest, workspace: Option<&Manifest>, key: &str, spec: &Value, kind: DependencyKind) -> Declared {
+    // `foo = { workspace = true }` takes the rest of its spec from `[workspace.dependencies]`.
+    let (spec, dir) = match workspace {
+        Some(w) if inherits(spec) => {
+            match w.table.get("workspace").and_then(|t| t.get("dependencies")?.get(key)) {
+                Some(inherited) => (inherited, &w.dir),
+                None => (spec, &m.dir),
+            }
+        }
+        _ => (spec, &m.dir),
+    };
+    Declared {
+        name: spec.get("package").and_then(Value::as_str).unwrap_or(key).to_owned(),
+        kind,
+        path: spec.get("path").and_then(Value::as_str).map(|p| resolve::join(dir, p)),
+    }
+}
+
+/// Sets `crate_name` and `target` on every file: from the target whose root
+/// `tree` hangs it under, or else from the package whose directory holds it.
+pub fn attribute(crates: &[CrateManifest], tree: &ModuleTree, files: &mut [ParsedFile]) {
+    let by_root: HashMap<&str, (&CrateManifest, &CrateTarget)> = crates
+        .iter()
+        .flat_map(|c| c.targets.iter().map(move |t| (t.root.as_str(), (c, t))))
+        .collect();
+    for pf in files.iter_mut() {
+        match tree.crate_root(&pf.path).and_then(|root| by_root.get(root)) {
+            Some((krate, target)) => {
+                pf.crate_name = Some(krate.name.clone());
+                pf.target = Some(target.label());
+            }
+            None => {
+                pf.crate_name = crates
+                    .iter()
+                    .filter(|c| contains(&c.path, &pf.path))
+                    .max_by_key(|c| c.path.len())
+                    .map(|c| c.name.clone());
+                pf.target = None;
+            }
+        }
+    }
+}
+
+/// The crate graph: a node per package and per external crate they use, an
+/// edge per dependency labelled with its kind.
+pub fn graph_data(packages: &[String], dependencies: &[CrateDependency]) -> GraphData {
+    let mut nodes: Vec<GraphNode> = packages
+        .iter()
+        .map(|name| GraphNode {
+            id: name.clone(),
+            label: name.clone(),
+            kind: "crate".into(),
+        })
+        .collect();
+    for dep in dependencies.iter().filter(|d| !d.local) {
+        if !nodes.iter().any(|n| n.id == dep.to) {
+            nodes.push(GraphNode {
+                id: dep.to.clone(),
+                label: dep.to.clone(),
+                kind: "extern".into(),
+            });
+        }
+    }
+    let edges = dependencies
+        .iter()
+        .map(|dep| GraphEdge {
+            from: dep.from.clone(),
+            to: dep.to.clone(),
+            label: Some(dep.kind.as_str().into()),
+        })
+        .collect();
+    GraphData { nodes, edges }
+}
@@ -1,12 +1,27 @@
+use std::collections::{BTreeMap, HashMap};
+
 use anyhow::{Context, Result};
-use sqlx::{PgPool, postgres::PgPoolOptions};
+use sqlx::{PgPool, postgres::PgPoolOptions, types::Json};
 use uuid::Uuid;
 
 use crate::models::{
-    AnalysisSummary, ComplexityItem, Dependency, FileEntry,
-    FunctionEntry, GraphData, GraphEdge, GraphNode, ParsedFile,
-    ParsedFunction, Project,
+    AnalysisRun, AnalysisSummary, AnalyzeRequest, AnalyzeResponse, ComplexityItem, CrateDependency,
+    CrateManifest, CrateSummary, DeadCodeItem, DependencyCycle, DependencyKind, FileEntry,
+    FunctionRef, GraphData, GraphEdge, GraphNode, ImportEdge, ImportTarget, Job, JobStatus,
+    LayerViolation, LineCounts, ModuleMetrics, ParsedFile, Project, RunSnapshot, StoredParse,
+    SymbolEntry, SymbolKind,
 };
+use crate::callgraph::{self, CallGraphFunction};
+use crate::complexity::{self, ScoreMap};
+use crate::config::GravityConfig;
+use crate::crates;
+use crate::graph::DependencyGraph;
+use crate::metrics;
+use crate::report;
+
+/// Recorded on every run so results can be traced to the analyzer that made them.
+pub const ANALYZER_VERSION: &str = env!("CARGO_PKG_VERSION");
+
 pub async fn init_pool(database_url: &str) -> Result<PgPool> {
     let pool = PgPoolOptions::new()
         .max_connections(10)
@@ -64,187 +79,866 @@ pub async fn upsert_project(pool: &PgPool, name: &str, path: &str) -> Result<Pro
     Ok(project)
 }
 
+/// Stores a completed analysis as a new run. Earlier runs are left untouched.
+/// Files in `copied` parsed and linked as they did in the given run, whose rows
+/// are copied over; only the other files are written out from `parsed_files`.
+#[allow(clippy::too_many_arguments)]
 pub async fn save_analysis(
     pool: &PgPool,
     project_id: Uuid,
+    commit_sha: Option<&str>,
+    config: &GravityConfig,
     parsed_files: &[ParsedFile],
-    complexity_map: &[(String, String, usize)], // (file_path, fn_name, score)
-) -> Result<()> {
+    copied: &HashMap<String, Uuid>,
+    complexity_map: &ScoreMap,
+    layer_violations: &[LayerViolation],
+) -> Result<AnalysisRun> {
     let mut tx = pool.begin().await?;
+    // Call sites reference functions of any file, so they go in last.
+    let function_ids: HashMap<FunctionRef, Uuid> = parsed_files
+        .iter()
+        .flat_map(|pf| (0..pf.functions.len()).map(|index| FunctionRef { file: pf.path.clone(), index }))
+        .map(|func| (func, Uuid::new_v4()))
+        .collect();
 
-    sqlx::query!("DELETE FROM complexities WHERE project_id = $1", project_id)
-        .execute(&mut *tx)
-        .await?;
-    sqlx::query!("DELETE FROM functions WHERE project_id = $1", project_id)
-        .execute(&mut *tx)
-        .await?;
-    sqlx::query!("DELETE FROM dependencies WHERE project_id = $1", project_id)
-        .execute(&mut *tx)
-        .await?;
-    sqlx::query!("DELETE FROM files WHERE project_id = $1", project_id)
-        .execute(&mut *tx)
-        .await?;
+    let run = sqlx::query_as!(
+        AnalysisRun,
+        r#"INSERT INTO analysis_runs (id, project_id, commit_sha, analyzer_version, config, created_at)
+           VALUES ($1, $2, $3, $4, $5, NOW())
+           RETURNING id, project_id, commit_sha, analyzer_version, created_at"#,
+        Uuid::new_v4(),
+        project_id,
+        commit_sha,
+        ANALYZER_VERSION,
+        Json(config) as _,
+    )
+    .fetch_one(&mut *tx)
+    .await
+    .context("Failed to insert analysis run")?;
 
-    for parsed_file in parsed_files {
+    for parsed_file in parsed_files.iter().filter(|pf| !copied.contains_key(&pf.path)) {
         let file_id = Uuid::new_v4();
+        let (public_items, documented_items) = parsed_file.doc_items();
 
         sqlx::query!(
-            r#"INSERT INTO files (id, project_id, path, module_name, line_count, created_at)
-               VALUES ($1, $2, $3, $4, $5, NOW())"#,
+            r#"INSERT INTO files
+                 (id, project_id, run_id, path, module_name, line_count, abstract_types, concrete_types,
+                  halstead_volume, halstead_difficulty, halstead_effort, maintainability_index,
+                  code_lines, comment_lines, doc_lines, blank_lines, public_items, documented_items,
+                  crate, target, content_hash, created_at)
+               VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18,
+                       $19, $20, $21, NOW())"#,
             file_id,
             project_id,
+            run.id,
             parsed_file.path,
             parsed_file.module_name,
             parsed_file.line_count as i32,
+            parsed_file.count(SymbolKind::Trait) as i32,
+            (parsed_file.count(SymbolKind::Struct) + parsed_file.count(SymbolKind::Enum)) as i32,
+            parsed_file.halstead.volume(),
+            parsed_file.halstead.difficulty(),
+            parsed_file.halstead.effort(),
+            complexity::file_maintainability_index(parsed_file, complexity_map),
+            parsed_file.lines.code as i32,
+            parsed_file.lines.comment as i32,
+            parsed_file.lines.doc as i32,
+            parsed_file.lines.blank as i32,
+            public_items as i32,
+            documented_items as i32,
+            parsed_file.crate_name,
+            parsed_file.target,
+            parsed_file.content_hash,
+        )
+        .execute(&mut *tx)
+        .await?;
+        // Kept once per file for `fetch_reusable_files`, along with the run
+        // that now holds its rows.
+        sqlx::query!(
+            r#"INSERT INTO parsed_files (project_id, path, content_hash, analyzer_version, parsed, run_id, updated_at)
+               VALUES ($1, $2, $3, $4, $5, $6, NOW())
+               ON CONFLICT (project_id, path) DO UPDATE
+               SET content_hash = EXCLUDED.content_hash, analyzer_version = EXCLUDED.analyzer_version,
+                   parsed = EXCLUDED.parsed, run_id = EXCLUDED.run_id, updated_at = NOW()"#,
+            project_id,
+            parsed_file.path,
+            parsed_file.content_hash,
+            ANALYZER_VERSION,
+            Json(parsed_file) as _,
+            run.id,
         )
         .execute(&mut *tx)
         .await?;
-        for func in &parsed_file.functions {
-            let func_id = Uuid::new_v4();
+        for symbol in &parsed_file.symbols {
+            sqlx::query!(
+                r#"INSERT INTO symbols
+                     (id, project_id, run_id, file_id, kind, name, line_start, line_end, visibility, self_type, trait_name, created_at)
+                   VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, NOW())"#,
+                Uuid::new_v4(),
+                project_id,
+                run.id,
+                file_id,
+                symbol.kind.as_str(),
+                symbol.name,
+                symbol.line_start as i32,
+                symbol.line_end as i32,
+                symbol.visibility,
+                symbol.self_type,
+                symbol.trait_name,
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+        for (index, func) in parsed_file.functions.iter().enumerate() {
+            let func_id = function_ids[&FunctionRef { file: parsed_file.path.clone(), index }];
             sqlx::query!(
-                r#"INSERT INTO functions (id, project_id, file_id, name, line_start, line_end, is_public, is_async, created_at)
-                   VALUES ($1, $2, $3, $4, $5, $6, $7, $8, NOW())"#,
+                r#"INSERT INTO functions
+                     (id, project_id, run_id, file_id, name, qualified_name, module_path, self_type, trait_name,
+                      line_start, line_end, is_public, is_async, unreachable_reason,
+                      logical_lines, params, generic_params, max_nesting, returns, unsafe_blocks, unwraps,
+                      ordinal, created_at)
+                   VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14,
+                           $15, $16, $17, $18, $19, $20, $21, $22, NOW())"#,
                 func_id,
                 project_id,
+                run.id,
                 file_id,
                 func.name,
+                func.qualified_name,
+                func.module_path,
+                func.owner,
+                func.trait_name,
                 func.line_start as i32,
                 func.line_end as i32,
                 func.is_public,
                 func.is_async,
+                func.unreachable,
+                func.shape.logical_lines as i32,
+                func.shape.params as i32,
+                func.shape.generic_params as i32,
+                func.shape.max_nesting as i32,
+                func.shape.returns as i32,
+                func.shape.unsafe_blocks as i32,
+                func.shape.unwraps as i32,
+                index as i32,
             )
             .execute(&mut *tx)
             .await?;
 
-            let score = complexity_map
-                .iter()
-                .find(|(fp, fn_name, _)| *fp == parsed_file.path && fn_name == &func.name)
-                .map(|(_, _, s)| *s as i32)
-                .unwrap_or(1);
+            let scores = complexity::scores_of(complexity_map, parsed_file, func);
 
             sqlx::query!(
-                r#"INSERT INTO complexities (id, project_id, function_id, score, created_at)
-                   VALUES ($1, $2, $3, $4, NOW())"#,
+                r#"INSERT INTO complexities
+                     (id, project_id, run_id, function_id, score, cognitive,
+                      halstead_volume, halstead_difficulty, halstead_effort, maintainability_index, created_at)
+                   VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, NOW())"#,
                 Uuid::new_v4(),
                 project_id,
+                run.id,
                 func_id,
-                score,
+                scores.cyclomatic as i32,
+                scores.cognitive as i32,
+                scores.halstead.volume(),
+                scores.halstead.difficulty(),
+                scores.halstead.effort(),
+                scores.maintainability_index,
             )
             .execute(&mut *tx)
             .await?;
         }
-        for import_target in &parsed_file.imports {
+        for import in &parsed_file.imports {
+            let path = import.path.join("::");
+            let target = match &import.target {
+                Some(ImportTarget::File(target) | ImportTarget::Extern(target)) => target,
+                Some(ImportTarget::Local) => &parsed_file.path,
+                None => &path,
+            };
             sqlx::query!(
-                r#"INSERT INTO dependencies (id, project_id, file_id, source, target, kind, created_at)
-                   VALUES ($1, $2, $3, $4, $5, 'use', NOW())"#,
+                r#"INSERT INTO dependencies
+                     (id, project_id, run_id, file_id, source, target, kind, path, alias, is_glob, visibility, line, created_at)
+                   VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, NOW())"#,
                 Uuid::new_v4(),
                 project_id,
+                run.id,
                 file_id,
                 parsed_file.path,
-                import_target,
+                target,
+                ImportTarget::kind(import.target.as_ref()),
+                path,
+                import.alias,
+                import.is_glob,
+                import.visibility,
+                import.line as i32,
             )
             .execute(&mut *tx)
             .await?;
         }
     }
 
+    copy_unchanged_files(&mut tx, run.id, parsed_files, copied, &function_ids).await?;
+
+    for parsed_file in parsed_files.iter().filter(|pf| !copied.contains_key(&pf.path)) {
+        for (index, func) in parsed_file.functions.iter().enumerate() {
+            let caller_id = function_ids[&FunctionRef { file: parsed_file.path.clone(), index }];
+            for call in &func.calls {
+                let callee_id = call.target.as_ref().and_then(|t| function_ids.get(t)).copied();
+                // Most unresolved references are just local variables.
+                if call.is_reference && callee_id.is_none() {
+                    continue;
+                }
+                sqlx::query!(
+                    r#"INSERT INTO calls
+                         (id, project_id, run_id, caller_id, callee_id, callee_name, is_method, is_reference, line, created_at)
+                       VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, NOW())"#,
+                    Uuid::new_v4(),
+                    project_id,
+                    run.id,
+                    caller_id,
+                    callee_id,
+                    call.path.join("::"),
+                    call.is_method,
+                    call.is_reference,
+                    call.line as i32,
+                )
+                .execute(&mut *tx)
+                .await?;
+            }
+        }
+    }
+
+    for v in layer_violations {
+        sqlx::query!(
+            r#"INSERT INTO layer_violations
+                 (id, project_id, run_id, from_layer, to_layer, file_path, line, use_path, target_path, created_at)
+               VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, NOW())"#,
+            Uuid::new_v4(),
+            project_id,
+            run.id,
+            v.from_layer,
+            v.to_layer,
+            v.file_path,
+            v.line,
+            v.use_path,
+            v.target_path,
+        )
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    // Files gone from the tree have nothing left to reuse.
+    let paths: Vec<String> = parsed_files.iter().map(|pf| pf.path.clone()).collect();
+    sqlx::query!(
+        "DELETE FROM parsed_files WHERE project_id = $1 AND NOT (path = ANY($2))",
+        project_id,
+        &paths,
+    )
+    .execute(&mut *tx)
+    .await?;
+
     tx.commit().await?;
+    Ok(run)
+}
+
+/// Copies the rows of the `copied` files from the runs that hold them into
+/// `run_id`, functions under their ids in `function_ids`. Copied calls find
+/// their callee in `run_id` by file and position, where every function of
+/// the analysis is stored by now.
+async fn copy_unchanged_files(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    run_id: Uuid,
+    parsed_files: &[ParsedFile],
+    copied: &HashMap<String, Uuid>,
+    function_ids: &HashMap<FunctionRef, Uuid>,
+) -> Result<()> {
+    if copied.is_empty() {
+        return Ok(());
+    }
+    let (paths, from_runs): (Vec<String>, Vec<Uuid>) = copied.iter().map(|(path, run)| (path.clone(), *run)).unzip();
+
+    let (mut fn_paths, mut fn_runs, mut ordinals, mut fn_ids) = (Vec::new(), Vec::new(), Vec::new(), Vec::new());
+    for pf in parsed_files {
+        let Some(&from_run) = copied.get(&pf.path) else { continue };
+        for index in 0..pf.functions.len() {
+            fn_paths.push(pf.path.clone());
+            fn_runs.push(from_run);
+            ordinals.push(index as i32);
+            fn_ids.push(function_ids[&FunctionRef { file: pf.path.clone(), index }]);
+        }
+    }
+
+    sqlx::query!(
+        r#"INSERT INTO files
+             (project_id, run_id, path, module_name, line_count, abstract_types, concrete_types,
+              halstead_volume, halstead_difficulty, halstead_effort, maintainability_index,
+              code_lines, comment_lines, doc_lines, blank_lines, public_items, documented_items,
+              crate, target, content_hash, created_at)
+           SELECT f.project_id, $1, f.path, f.module_name, f.line_count, f.abstract_types, f.concrete_types,
+                  f.halstead_volume, f.halstead_difficulty, f.halstead_effort, f.maintainability_index,
+                  f.code_lines, f.comment_lines, f.doc_lines, f.blank_lines, f.public_items, f.documented_items,
+                  f.crate, f.target, f.content_hash, NOW()
+           FROM UNNEST($2::text[], $3::uuid[]) AS c(path, from_run)
+           JOIN files f ON f.run_id = c.from_run AND f.path = c.path"#,
+        run_id,
+        &paths,
+        &from_runs,
+    )
+    .execute(&mut **tx)
+    .await?;
+
+    sqlx::query!(
+        r#"INSERT INTO symbols
+             (project_id, run_id, file_id, kind, name, line_start, line_end, visibility, self_type, trait_name, created_at)
+           SELECT s.project_id, $1, nf.id, s.kind, s.name, s.line_start, s.line_end, s.visibility, s.self_type,
+                  s.trait_name, NOW()
+           FROM UNNEST($2::text[], $3::uuid[]) AS c(path, from_run)
+           JOIN files f ON f.run_id = c.from_run AND f.path = c.path
+           JOIN symbols s ON s.file_id = f.id
+           JOIN files nf ON nf.run_id = $1 AND nf.path = c.path"#,
+        run_id,
+        &paths,
+        &from_runs,
+    )
+    .execute(&mut **tx)
+    .await?;
+
+    sqlx::query!(
+        r#"INSERT INTO dependencies
+             (project_id, run_id, file_id, source, target, kind, path, alias, is_glob, visibility, line, created_at)
+           SELECT d.project_id, $1, nf.id, d.source, d.target, d.kind, d.path, d.alias, d.is_glob, d.visibility,
+                  d.line, NOW()
+           FROM UNNEST($2::text[], $3::uuid[]) AS c(path, from_run)
+           JOIN files f ON f.run_id = c.from_run AND f.path = c.path
+           JOIN dependencies d ON d.file_id = f.id
+           JOIN files nf ON nf.run_id = $1 AND nf.path = c.path"#,
+        run_id,
+        &paths,
+        &from_runs,
+    )
+    .execute(&mut **tx)
+    .await?;
+
+    sqlx::query!(
+        r#"INSERT INTO functions
+             (id, project_id, run_id, file_id, name, qualified_name, module_path, self_type, trait_name,
+              line_start, line_end, is_public, is_async, unreachable_reason,
+              logical_lines, params, generic_params, max_nesting, returns, unsafe_blocks, unwraps,
+              ordinal, created_at)
+           SELECT c.id, fn.project_id, $1, nf.id, fn.name, fn.qualified_name, fn.module_path, fn.self_type,
+                  fn.trait_name, fn.line_start, fn.line_end, fn.is_public, fn.is_async, fn.unreachable_reason,
+                  fn.logical_lines, fn.params, fn.generic_params, fn.max_nesting, fn.returns, fn.unsafe_blocks,
+                  fn.unwraps, fn.ordinal, NOW()
+           FROM UNNEST($2::text[], $3::uuid[], $4::int[], $5::uuid[]) AS c(path, from_run, ordinal, id)
+           JOIN files f ON f.run_id = c.from_run AND f.path = c.path
+           JOIN functions fn ON fn.file_id = f.id AND fn.ordinal = c.ordinal
+           JOIN files nf ON nf.run_id = $1 AND nf.path = c.path"#,
+        run_id,
+        &fn_paths,
+        &fn_runs,
+        &ordinals,
+        &fn_ids,
+    )
+    .execute(&mut **tx)
+    .await?;
+
+    sqlx::query!(
+        r#"INSERT INTO complexities
+             (project_id, run_id, function_id, score, cognitive,
+              halstead_volume, halstead_difficulty, halstead_effort, maintainability_index, created_at)
+           SELECT cx.project_id, $1, c.id, cx.score, cx.cognitive,
+                  cx.halstead_volume, cx.halstead_difficulty, cx.halstead_effort, cx.maintainability_index, NOW()
+           FROM UNNEST($2::text[], $3::uuid[], $4::int[], $5::uuid[]) AS c(path, from_run, ordinal, id)
+           JOIN files f ON f.run_id = c.from_run AND f.path = c.path
+           JOIN functions fn ON fn.file_id = f.id AND fn.ordinal = c.ordinal
+           JOIN complexities cx ON cx.function_id = fn.id"#,
+        run_id,
+        &fn_paths,
+        &fn_runs,
+        &ordinals,
+        &fn_ids,
+    )
+    .execute(&mut **tx)
+    .await?;
+
+    sqlx::query!(
+        r#"INSERT INTO calls
+             (project_id, run_id, caller_id, callee_id, callee_name, is_method, is_reference, line, created_at)
+           SELECT ca.project_id, $1, c.id, callee.id, ca.callee_name, ca.is_method, ca.is_reference, ca.line, NOW()
+           FROM UNNEST($2::text[], $3::uuid[], $4::int[], $5::uuid[]) AS c(path, from_run, ordinal, id)
+           JOIN files f ON f.run_id = c.from_run AND f.path = c.path
+           JOIN functions fn ON fn.file_id = f.id AND fn.ordinal = c.ordinal
+           JOIN calls ca ON ca.caller_id = fn.id
+           LEFT JOIN functions old_callee ON old_callee.id = ca.callee_id
+           LEFT JOIN files old_callee_file ON old_callee_file.id = old_callee.file_id
+           LEFT JOIN files callee_file ON callee_file.run_id = $1 AND callee_file.path = old_callee_file.path
+           LEFT JOIN functions callee ON callee.file_id = callee_file.id AND callee.ordinal = old_callee.ordinal"#,
+        run_id,
+        &fn_paths,
+        &fn_runs,
+        &ordinals,
+        &fn_ids,
+    )
+    .execute(&mut **tx)
+    .await?;
+
     Ok(())
 }
 
-pub async fn fetch_summary(pool: &PgPool, project_id: Uuid) -> Result<AnalysisSummary> {
-    struct Row {
-        name: String,
-        path: String,
+/// Stores the packages of `run` and the dependencies declared between them.
+pub async fn save_crates(pool: &PgPool, run: &AnalysisRun, crates: &[CrateManifest]) -> Result<()> {
+    let mut tx = pool.begin().await?;
+    for krate in crates {
+        let targets: Vec<String> = krate.targets.iter().map(|t| t.label()).collect();
+        sqlx::query!(
+            r#"INSERT INTO crates (id, project_id, run_id, name, path, version, workspace, targets, created_at)
+               VALUES ($1, $2, $3, $4, $5, $6, $7, $8, NOW())"#,
+            Uuid::new_v4(),
+            run.project_id,
+            run.id,
+            krate.name,
+            krate.path,
+            krate.version,
+            krate.workspace,
+            &targets,
+        )
+        .execute(&mut *tx)
+        .await?;
+        for dep in &krate.dependencies {
+            sqlx::query!(
+                r#"INSERT INTO crate_dependencies (id, project_id, run_id, from_crate, to_crate, kind, is_local, created_at)
+                   VALUES ($1, $2, $3, $4, $5, $6, $7, NOW())"#,
+                Uuid::new_v4(),
+                run.project_id,
+                run.id,
+                dep.from,
+                dep.to,
+                dep.kind as DependencyKind,
+                dep.local,
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
     }
+    tx.commit().await?;
+    Ok(())
+}
 
-    let project = sqlx::query_as!(
-        Row,
-        "SELECT name, path FROM projects WHERE id = $1",
+/// The last parse of each file of the project, by path, for
+/// `parser::reuse_unchanged`. Empty for a new project; entries that no longer
+/// deserialize are left out and parsed again.
+pub async fn fetch_reusable_files(pool: &PgPool, name: &str, path: &str) -> Result<HashMap<String, StoredParse>> {
+    let rows = sqlx::query!(
+        r#"SELECT pf.analyzer_version, pf.run_id, pf.parsed
+           FROM parsed_files pf
+           JOIN projects p ON p.id = pf.project_id
+           WHERE p.name = $1 AND p.path = $2"#,
+        name,
+        path,
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .filter_map(|r| {
+            let file = serde_json::from_value::<ParsedFile>(r.parsed).ok()?;
+            Some((file.path.clone(), StoredParse { analyzer_version: r.analyzer_version, run_id: r.run_id, file }))
+        })
+        .collect())
+}
+
+pub async fn fetch_run(pool: &PgPool, run_id: Uuid) -> Result<Option<AnalysisRun>> {
+    let run = sqlx::query_as!(
+        AnalysisRun,
+        r#"SELECT id, project_id, commit_sha, analyzer_version, created_at
+           FROM analysis_runs WHERE id = $1"#,
+        run_id
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(run)
+}
+
+pub async fn fetch_latest_run(pool: &PgPool, project_id: Uuid) -> Result<Option<AnalysisRun>> {
+    let run = sqlx::query_as!(
+        AnalysisRun,
+        r#"SELECT id, project_id, commit_sha, analyzer_version, created_at
+           FROM analysis_runs WHERE project_id = $1
+           ORDER BY created_at DESC
+           LIMIT 1"#,
         project_id
     )
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(run)
+}
+
+pub async fn fetch_runs(pool: &PgPool, project_id: Uuid) -> Result<Vec<AnalysisRun>> {
+    let runs = sqlx::query_as!(
+        AnalysisRun,
+        r#"SELECT id, project_id, commit_sha, analyzer_version, created_at
+           FROM analysis_runs WHERE project_id = $1
+           ORDER BY created_at DESC"#,
+        project_id
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(runs)
+}
+
+/// The run analyzed just before `run`, if any.
+pub async fn fetch_previous_run(pool: &PgPool, run: &AnalysisRun) -> Result<Option<AnalysisRun>> {
+    let previous = sqlx::query_as!(
+        AnalysisRun,
+        r#"SELECT id, project_id, commit_sha, analyzer_version, created_at
+           FROM analysis_runs
+           WHERE project_id = $1 AND created_at < $2
+           ORDER BY created_at DESC
+           LIMIT 1"#,
+        run.project_id,
+        run.created_at
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(previous)
+}
+
+/// The `gravity.toml` the run was analyzed with.
+pub async fn fetch_run_config(pool: &PgPool, run_id: Uuid) -> Result<GravityConfig> {
+    let config = sqlx::query_scalar!(
+        r#"SELECT config AS "config: Json<GravityConfig>" FROM analysis_runs WHERE id = $1"#,
+        run_id
+    )
     .fetch_one(pool)
     .await
-    .context("Project not found")?;
+    .context("Failed to load run config")?;
+
+    Ok(config.0)
+}
+
+pub async fn fetch_summary(pool: &PgPool, run: &AnalysisRun) -> Result<AnalysisSummary> {
+    let project_id = run.project_id;
+    let run_id = run.id;
+
+    let project_name = sqlx::query_scalar!("SELECT name FROM projects WHERE id = $1", project_id)
+        .fetch_one(pool)
+        .await
+        .context("Project not found")?;
 
     let total_files: i64 = sqlx::query_scalar!(
-        "SELECT COUNT(*) FROM files WHERE project_id = $1",
-        project_id
+        "SELECT COUNT(*) FROM files WHERE run_id = $1",
+        run_id
     )
     .fetch_one(pool)
     .await?
     .unwrap_or(0);
 
     let total_functions: i64 = sqlx::query_scalar!(
-        "SELECT COUNT(*) FROM functions WHERE project_id = $1",
-        project_id
+        "SELECT COUNT(*) FROM functions WHERE run_id = $1",
+        run_id
     )
     .fetch_one(pool)
     .await?
     .unwrap_or(0);
 
     let total_imports: i64 = sqlx::query_scalar!(
-        "SELECT COUNT(*) FROM dependencies WHERE project_id = $1",
-        project_id
+        "SELECT COUNT(*) FROM dependencies WHERE run_id = $1",
+        run_id
     )
     .fetch_one(pool)
     .await?
     .unwrap_or(0);
 
     let avg_complexity: f64 = sqlx::query_scalar!(
-        "SELECT AVG(score::FLOAT8) FROM complexities WHERE project_id = $1",
-        project_id
+        "SELECT AVG(score::FLOAT8) FROM complexities WHERE run_id = $1",
+        run_id
     )
     .fetch_one(pool)
     .await?
     .unwrap_or(1.0);
-    let dead_code_candidates: Vec<String> = sqlx::query_scalar!(
-        r#"SELECT f.name
-           FROM functions f
-           WHERE f.project_id = $1
-             AND f.name NOT IN (
-               SELECT DISTINCT target FROM dependencies WHERE project_id = $1
-             )
-             AND f.is_public = FALSE
-           ORDER BY f.name
-           LIMIT 20"#,
-        project_id
+
+    let totals = sqlx::query!(
+        r#"SELECT COALESCE(SUM(code_lines), 0)       AS "code!",
+                  COALESCE(SUM(comment_lines), 0)    AS "comment!",
+                  COALESCE(SUM(doc_lines), 0)        AS "doc!",
+                  COALESCE(SUM(blank_lines), 0)      AS "blank!",
+                  COALESCE(SUM(public_items), 0)     AS "public_items!",
+                  COALESCE(SUM(documented_items), 0) AS "documented_items!"
+           FROM files WHERE run_id = $1"#,
+        run_id
     )
-    .fetch_all(pool)
+    .fetch_one(pool)
     .await?;
-    let mut notes = Vec::new();
-    if total_files > 20 {
-        notes.push("Large project: consider splitting into smaller crates.".into());
-    }
-    if avg_complexity > 10.0 {
-        notes.push("High average cyclomatic complexity: refactoring recommended.".into());
-    }
-    if total_imports > total_files * 10 {
-        notes.push("High import density: possible tight coupling detected.".into());
-    }
-    if notes.is_empty() {
-        notes.push("Project structure looks healthy.".into());
-    }
+    let lines = LineCounts {
+        code: totals.code as usize,
+        comment: totals.comment as usize,
+        doc: totals.doc as usize,
+        blank: totals.blank as usize,
+    };
+
+    let symbol_counts = fetch_symbol_counts(pool, run_id).await?;
+
+    let dead_code_count = count_dead_code(pool, run_id).await?;
+    let cycles = fetch_cycles(pool, run_id).await?;
+    let modules = fetch_module_metrics(pool, run_id).await?;
+    let layer_violations = fetch_layer_violations(pool, run_id).await?;
+    let functions = fetch_complexities(pool, run_id).await?;
+    let notes = report::architecture_notes(total_files, avg_complexity, &functions, &modules, &cycles);
+    let crates = fetch_crate_summaries(pool, run_id).await?;
 
     Ok(AnalysisSummary {
         project_id,
-        project_name: project.name,
+        project_name,
+        run_id,
+        commit_sha: run.commit_sha.clone(),
+        analyzed_at: run.created_at,
         total_files,
         total_functions,
-        total_structs: 0, 
+        total_structs: symbol_counts.get(SymbolKind::Struct.as_str()).copied().unwrap_or(0),
         total_imports,
+        symbol_counts,
         avg_complexity,
-        dead_code_candidates,
+        comment_ratio: lines.comment_ratio(),
+        doc_coverage: report::doc_coverage(totals.public_items as usize, totals.documented_items as usize),
+        dead_code_count,
+        crates,
+        layer_violations,
         architecture_notes: notes,
     })
 }
 
-pub async fn fetch_files(pool: &PgPool, project_id: Uuid) -> Result<Vec<FileEntry>> {
+/// Per-package numbers of a run, ordered by path; see `report::crate_summaries`.
+pub async fn fetch_crate_summaries(pool: &PgPool, run_id: Uuid) -> Result<Vec<CrateSummary>> {
+    let crates = sqlx::query!(
+        r#"SELECT c.name, c.path, c.version, c.targets,
+                  COUNT(f.id)                          AS "files!",
+                  COALESCE(SUM(f.line_count), 0)       AS "lines!",
+                  COALESCE(SUM(f.public_items), 0)     AS "public_items!",
+                  COALESCE(SUM(f.documented_items), 0) AS "documented_items!"
+           FROM crates c
+           LEFT JOIN files f ON f.run_id = c.run_id AND f.crate = c.name
+           WHERE c.run_id = $1
+           GROUP BY c.id
+           ORDER BY c.path"#,
+        run_id
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let functions: HashMap<String, (i64, Option<f64>, i64)> = sqlx::query!(
+        r#"SELECT f.crate AS "krate!",
+                  COUNT(*)                      AS "functions!",
+                  AVG(cx.score::FLOAT8)         AS avg_complexity,
+                  COUNT(fn.unreachable_reason)  AS "dead_code!"
+           FROM functions fn
+           JOIN files f ON f.id = fn.file_id
+           LEFT JOIN complexities cx ON cx.function_id = fn.id
+           WHERE fn.run_id = $1 AND f.crate IS NOT NULL
+           GROUP BY f.crate"#,
+        run_id
+    )
+    .fetch_all(pool)
+    .await?
+    .into_iter()
+    .map(|r| (r.krate, (r.functions, r.avg_complexity, r.dead_code)))
+    .collect();
+
+    Ok(crates
+        .into_iter()
+        .map(|c| {
+            let (functions, avg_complexity, dead_code) = functions.get(&c.name).copied().unwrap_or_default();
+            CrateSummary {
+                doc_coverage: report::doc_coverage(c.public_items as usize, c.documented_items as usize),
+                name: c.name,
+                path: c.path,
+                version: c.version,
+                targets: c.targets,
+                files: c.files,
+                functions,
+                lines: c.lines,
+                avg_complexity: avg_complexity.unwrap_or(1.0),
+                dead_code,
+            }
+        })
+        .collect())
+}
+
+/// The crate graph of a run; see `crates::graph_data`.
+pub async fn fetch_crate_graph(pool: &PgPool, run_id: Uuid) -> Result<GraphData> {
+    let names = sqlx::query_scalar!(
+        "SELECT name FROM crates WHERE run_id = $1 ORDER BY path",
+        run_id
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let dependencies = sqlx::query_as!(
+        CrateDependency,
+        r#"SELECT from_crate AS "from", to_crate AS "to", kind AS "kind: DependencyKind", is_local AS local
+           FROM crate_dependencies
+           WHERE run_id = $1
+           ORDER BY from_crate, to_crate, kind"#,
+        run_id
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(crates::graph_data(&names, &dependencies))
+}
+
+/// Dependency cycles between the files of a run.
+pub async fn fetch_cycles(pool: &PgPool, run_id: Uuid) -> Result<Vec<DependencyCycle>> {
+    let imports = sqlx::query_as!(
+        ImportEdge,
+        r#"SELECT source AS "from", target AS "to", path, line FROM dependencies
+           WHERE run_id = $1 AND kind = 'use'"#,
+        run_id
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(DependencyGraph::from_imports(&imports).cycles())
+}
+
+/// Number of symbols of each kind in a run.
+pub async fn fetch_symbol_counts(pool: &PgPool, run_id: Uuid) -> Result<BTreeMap<String, i64>> {
+    let rows = sqlx::query!(
+        r#"SELECT kind, COUNT(*) AS "count!" FROM symbols WHERE run_id = $1 GROUP BY kind"#,
+        run_id
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows.into_iter().map(|r| (r.kind, r.count)).collect())
+}
+
+/// Symbols of a run whose name contains `query`, case-insensitively,
+/// optionally of one kind. Ordered by name, then location.
+pub async fn fetch_symbols(
+    pool: &PgPool,
+    run_id: Uuid,
+    query: Option<&str>,
+    kind: Option<&str>,
+    limit: i64,
+) -> Result<Vec<SymbolEntry>> {
+    // LIKE wildcards in the query match literally.
+    let pattern = query.map(|q| {
+        let escaped = q.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_");
+        format!("%{escaped}%")
+    });
+    let symbols = sqlx::query_as!(
+        SymbolEntry,
+        r#"SELECT s.kind, s.name, f.path AS file_path, s.line_start, s.line_end,
+                  s.visibility, s.self_type, s.trait_name
+           FROM symbols s
+           JOIN files f ON f.id = s.file_id
+           WHERE s.run_id = $1
+             AND ($2::TEXT IS NULL OR s.name ILIKE $2)
+             AND ($3::TEXT IS NULL OR s.kind = $3)
+           ORDER BY s.name, f.path, s.line_start
+           LIMIT $4"#,
+        run_id,
+        pattern,
+        kind,
+        limit,
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(symbols)
+}
+
+/// Uses that break the layering declared in gravity.toml, by file and line.
+pub async fn fetch_layer_violations(pool: &PgPool, run_id: Uuid) -> Result<Vec<LayerViolation>> {
+    let violations = sqlx::query_as!(
+        LayerViolation,
+        r#"SELECT from_layer, to_layer, file_path, line, use_path, target_path
+           FROM layer_violations
+           WHERE run_id = $1
+           ORDER BY file_path, line"#,
+        run_id
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(violations)
+}
+
+/// Coupling and abstractness of every file of a run, ordered by path.
+pub async fn fetch_module_metrics(pool: &PgPool, run_id: Uuid) -> Result<Vec<ModuleMetrics>> {
+    let imports = sqlx::query_as!(
+        ImportEdge,
+        r#"SELECT source AS "from", target AS "to", path, line FROM dependencies
+           WHERE run_id = $1 AND kind = 'use'"#,
+        run_id
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let types = sqlx::query!(
+        "SELECT path, abstract_types, concrete_types FROM files WHERE run_id = $1",
+        run_id
+    )
+    .fetch_all(pool)
+    .await?
+    .into_iter()
+    .map(|r| (r.path, r.abstract_types, r.concrete_types))
+    .collect::<Vec<_>>();
+
+    Ok(metrics::module_metrics(&DependencyGraph::from_imports(&imports), &types))
+}
+
+pub async fn count_dead_code(pool: &PgPool, run_id: Uuid) -> Result<i64> {
+    let count = sqlx::query_scalar!(
+        "SELECT COUNT(*) FROM functions WHERE run_id = $1 AND unreachable_reason IS NOT NULL",
+        run_id
+    )
+    .fetch_one(pool)
+    .await?
+    .unwrap_or(0);
+
+    Ok(count)
+}
+
+/// Unreachable functions of a run by file and line; all of them when `limit`
+/// is `None`.
+pub async fn fetch_dead_code(
+    pool: &PgPool,
+    run_id: Uuid,
+    limit: Option<i64>,
+    offset: i64,
+) -> Result<Vec<DeadCodeItem>> {
+    let items = sqlx::query_as!(
+        DeadCodeItem,
+        r#"SELECT fn.name AS function_name, fn.qualified_name, fi.path AS file_path,
+                  fn.line_start, fn.line_end, fn.unreachable_reason AS "reason!"
+           FROM functions fn
+           JOIN files fi ON fi.id = fn.file_id
+           WHERE fn.run_id = $1 AND fn.unreachable_reason IS NOT NULL
+           ORDER BY fi.path, fn.line_start
+           LIMIT $2 OFFSET $3"#,
+        run_id,
+        limit,
+        offset,
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(items)
+}
+
+pub async fn fetch_files(pool: &PgPool, run_id: Uuid) -> Result<Vec<FileEntry>> {
     let files = sqlx::query_as!(
         FileEntry,
-        r#"SELECT id, project_id, path, module_name, line_count, created_at
-           FROM files WHERE project_id = $1 ORDER BY path"#,
-        project_id
+        r#"SELECT id, project_id, run_id, path, module_name, line_count,
+                  code_lines, comment_lines, doc_lines, blank_lines, public_items, documented_items,
+                  halstead_volume, halstead_difficulty, halstead_effort, maintainability_index,
+                  crate AS crate_name, target, created_at
+           FROM files WHERE run_id = $1 ORDER BY path"#,
+        run_id
     )
     .fetch_all(pool)
     .await?;
@@ -252,23 +946,25 @@ pub async fn fetch_files(pool: &PgPool, project_id: Uuid) -> Result<Vec<FileEntr
     Ok(files)
 }
 
-pub async fn fetch_graph(pool: &PgPool, project_id: Uuid) -> Result<GraphData> {
+pub async fn fetch_graph(pool: &PgPool, run_id: Uuid) -> Result<GraphData> {
     struct DepRow {
         source: String,
         target: String,
+        kind: String,
     }
 
     let deps = sqlx::query_as!(
         DepRow,
-        "SELECT source, target FROM dependencies WHERE project_id = $1",
-        project_id
+        r#"SELECT DISTINCT source, target, kind FROM dependencies
+           WHERE run_id = $1 AND kind IN ('use', 'extern')"#,
+        run_id
     )
     .fetch_all(pool)
     .await?;
 
     let files = sqlx::query_scalar!(
-        "SELECT path FROM files WHERE project_id = $1",
-        project_id
+        "SELECT path FROM files WHERE run_id = $1",
+        run_id
     )
     .fetch_all(pool)
     .await?;
@@ -292,11 +988,11 @@ pub async fn fetch_graph(pool: &PgPool, project_id: Uuid) -> Result<GraphData> {
     let mut edges: Vec<GraphEdge> = Vec::new();
 
     for dep in &deps {
-        if !nodes.iter().any(|n| n.id == dep.target) {
-            let label = dep.target.split("::").last().unwrap_or(&dep.target).to_string();
+        // Targets of `use` edges are files of the run; only crates get their own node.
+        if dep.kind == "extern" && !nodes.iter().any(|n| n.id == dep.target) {
             nodes.push(GraphNode {
                 id: dep.target.clone(),
-                label,
+                label: dep.target.clone(),
                 kind: "extern".into(),
             });
         }
@@ -310,11 +1006,53 @@ pub async fn fetch_graph(pool: &PgPool, project_id: Uuid) -> Result<GraphData> {
     Ok(GraphData { nodes, edges })
 }
 
-pub async fn fetch_complexities(pool: &PgPool, project_id: Uuid) -> Result<Vec<ComplexityItem>> {
+/// The function call graph of a run; see `callgraph::call_graph_data`.
+pub async fn fetch_call_graph(
+    pool: &PgPool,
+    run_id: Uuid,
+    root: Option<&str>,
+    depth: usize,
+) -> Result<GraphData> {
+    let functions = sqlx::query_as!(
+        CallGraphFunction,
+        "SELECT id, name, self_type, qualified_name FROM functions WHERE run_id = $1",
+        run_id
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let calls = sqlx::query!(
+        r#"SELECT caller_id, callee_id AS "callee_id!" FROM calls
+           WHERE run_id = $1 AND callee_id IS NOT NULL"#,
+        run_id
+    )
+    .fetch_all(pool)
+    .await?
+    .into_iter()
+    .map(|r| (r.caller_id, r.callee_id))
+    .collect::<Vec<_>>();
+
+    Ok(callgraph::call_graph_data(&functions, &calls, root, depth))
+}
+
+pub async fn fetch_complexities(pool: &PgPool, run_id: Uuid) -> Result<Vec<ComplexityItem>> {
     struct Row {
         function_name: String,
+        qualified_name: String,
         file_path: String,
         score: i32,
+        cognitive: i32,
+        halstead_volume: f64,
+        halstead_difficulty: f64,
+        halstead_effort: f64,
+        maintainability_index: f64,
+        logical_lines: i32,
+        params: i32,
+        generic_params: i32,
+        max_nesting: i32,
+        returns: i32,
+        unsafe_blocks: i32,
+        unwraps: i32,
         line_start: i32,
         line_end: i32,
     }
@@ -323,16 +1061,29 @@ pub async fn fetch_complexities(pool: &PgPool, project_id: Uuid) -> Result<Vec<C
         Row,
         r#"SELECT
                fn.name      AS function_name,
+               fn.qualified_name,
                fi.path      AS file_path,
                cx.score,
+               cx.cognitive,
+               cx.halstead_volume,
+               cx.halstead_difficulty,
+               cx.halstead_effort,
+               cx.maintainability_index,
+               fn.logical_lines,
+               fn.params,
+               fn.generic_params,
+               fn.max_nesting,
+               fn.returns,
+               fn.unsafe_blocks,
+               fn.unwraps,
                fn.line_start,
                fn.line_end
            FROM complexities cx
            JOIN functions fn ON cx.function_id = fn.id
            JOIN files     fi ON fn.file_id      = fi.id
-           WHERE cx.project_id = $1
+           WHERE cx.run_id = $1
            ORDER BY cx.score DESC"#,
-        project_id
+        run_id
     )
     .fetch_all(pool)
     .await?;
@@ -341,10 +1092,192 @@ pub async fn fetch_complexities(pool: &PgPool, project_id: Uuid) -> Result<Vec<C
         .into_iter()
         .map(|r| ComplexityItem {
             function_name: r.function_name,
+            qualified_name: r.qualified_name,
             file_path: r.file_path,
             score: r.score,
+            cognitive: r.cognitive,
+            halstead_volume: r.halstead_volume,
+            halstead_difficulty: r.halstead_difficulty,
+            halstead_effort: r.halstead_effort,
+            maintainability_index: r.maintainability_index,
+            logical_lines: r.logical_lines,
+            params: r.params,
+            generic_params: r.generic_params,
+            max_nesting: r.max_nesting,
+            returns: r.returns,
+            unsafe_blocks: r.unsafe_blocks,
+            unwraps: r.unwraps,
             line_start: r.line_start,
             line_end: r.line_end,
         })
         .collect())
 }
+
+// ─── Analysis jobs ─────────────────────────────────────────────────────────
+
+pub async fn create_job(pool: &PgPool, req: &AnalyzeRequest) -> Result<Job> {
+    let job = sqlx::query_as!(
+        Job,
+        r#"INSERT INTO jobs (id, status, request, created_at, updated_at)
+           VALUES ($1, $2, $3, NOW(), NOW())
+           RETURNING id, project_id, status AS "status: JobStatus",
+                     files_total, files_parsed, functions_found,
+                     result AS "result: Json<AnalyzeResponse>", error,
+                     created_at, updated_at"#,
+        Uuid::new_v4(),
+        JobStatus::Queued as JobStatus,
+        Json(req) as _,
+    )
+    .fetch_one(pool)
+    .await
+    .context("Failed to insert job")?;
+
+    Ok(job)
+}
+
+pub async fn fetch_job(pool: &PgPool, job_id: Uuid) -> Result<Option<Job>> {
+    let job = sqlx::query_as!(
+        Job,
+        r#"SELECT id, project_id, status AS "status: JobStatus",
+                  files_total, files_parsed, functions_found,
+                  result AS "result: Json<AnalyzeResponse>", error,
+                  created_at, updated_at
+           FROM jobs WHERE id = $1"#,
+        job_id
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(job)
+}
+
+/// Jobs that were queued or mid-flight when the backend last stopped.
+pub async fn fetch_unfinished_jobs(pool: &PgPool) -> Result<Vec<(Uuid, AnalyzeRequest)>> {
+    struct Row {
+        id: Uuid,
+        request: Json<AnalyzeRequest>,
+    }
+
+    let rows = sqlx::query_as!(
+        Row,
+        r#"SELECT id, request AS "request: Json<AnalyzeRequest>"
+           FROM jobs
+           WHERE status NOT IN ('done', 'failed')
+           ORDER BY created_at"#
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows.into_iter().map(|r| (r.id, r.request.0)).collect())
+}
+
+pub async fn set_job_status(pool: &PgPool, job_id: Uuid, status: JobStatus) -> Result<()> {
+    sqlx::query!(
+        "UPDATE jobs SET status = $2, updated_at = NOW() WHERE id = $1",
+        job_id,
+        status as JobStatus,
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn set_job_progress(
+    pool: &PgPool,
+    job_id: Uuid,
+    files_total: usize,
+    files_parsed: usize,
+    functions_found: usize,
+) -> Result<()> {
+    sqlx::query!(
+        r#"UPDATE jobs
+           SET files_total = $2, files_parsed = $3, functions_found = $4, updated_at = NOW()
+           WHERE id = $1"#,
+        job_id,
+        files_total as i32,
+        files_parsed as i32,
+        functions_found as i32,
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn complete_job(pool: &PgPool, job_id: Uuid, result: &AnalyzeResponse) -> Result<()> {
+    sqlx::query!(
+        r#"UPDATE jobs
+           SET status = $2, project_id = $3, run_id = $4, result = $5, error = NULL, updated_at = NOW()
+           WHERE id = $1"#,
+        job_id,
+        JobStatus::Done as JobStatus,
+        result.project_id,
+        result.run_id,
+        Json(result) as _,
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn fail_job(pool: &PgPool, job_id: Uuid, error: &str) -> Result<()> {
+    sqlx::query!(
+        "UPDATE jobs SET status = $2, error = $3, updated_at = NOW() WHERE id = $1",
+        job_id,
+        JobStatus::Failed as JobStatus,
+        error,
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+// ─── Run comparison ────────────────────────────────────────────────────────
+
+pub async fn fetch_run_snapshot(pool: &PgPool, run_id: Uuid) -> Result<RunSnapshot> {
+    struct FileRow {
+        path: String,
+        line_count: i32,
+        content_hash: Option<String>,
+    }
+
+    let files = sqlx::query_as!(
+        FileRow,
+        "SELECT path, line_count, content_hash FROM files WHERE run_id = $1",
+        run_id
+    )
+    .fetch_all(pool)
+    .await?;
+
+    struct DepRow {
+        source: String,
+        target: String,
+    }
+
+    let deps = sqlx::query_as!(
+        DepRow,
+        r#"SELECT DISTINCT source, target FROM dependencies
+           WHERE run_id = $1 AND kind IN ('use', 'extern')"#,
+        run_id
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(RunSnapshot {
+        files: files.into_iter().map(|f| (f.path, f.line_count, f.content_hash)).collect(),
+        functions: fetch_complexities(pool, run_id).await?,
+        edges: deps
+            .into_iter()
+            .map(|d| GraphEdge {
+                from: d.source,
+                to: d.target,
+                label: Some("uses".into()),
+            })
+            .collect(),
+        cycles: fetch_cycles(pool, run_id).await?,
+        dead_code: fetch_dead_code(pool, run_id, None, 0).await?,
+    })
+}
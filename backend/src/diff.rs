@@ -0,0 +1,156 @@
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+
+use uuid::Uuid;
+
+use crate::models::{
+    ComplexityDelta, ComplexityItem, DeadCodeItem, FileChange, GraphEdge, RunDiff, RunSnapshot,
+};
+
+/// Compares two runs of the same project. Files are matched by their path
+/// relative to the analysis root, functions by file and qualified name.
+pub fn diff_runs(from_run: Uuid, from: &RunSnapshot, to_run: Uuid, to: &RunSnapshot) -> RunDiff {
+    let from_files = keyed_files(&from.files);
+    let to_files = keyed_files(&to.files);
+
+    let files_added: Vec<String> = to_files
+        .keys()
+        .filter(|p| !from_files.contains_key(*p))
+        .map(|p| p.to_string())
+        .collect();
+    let files_removed: Vec<String> = from_files
+        .keys()
+        .filter(|p| !to_files.contains_key(*p))
+        .map(|p| p.to_string())
+        .collect();
+
+    // ── Functions ────────────────────────────────────────────────────────────
+    let from_fns = keyed_functions(&from.functions);
+    let to_fns = keyed_functions(&to.functions);
+
+    let mut functions_added = Vec::new();
+    let mut complexity_deltas = Vec::new();
+    for (key, item) in &to_fns {
+        match from_fns.get(key) {
+            None => functions_added.push((*item).clone()),
+            Some(old) if old.score != item.score => complexity_deltas.push(ComplexityDelta {
+                function_name: item.function_name.clone(),
+                qualified_name: item.qualified_name.clone(),
+                file_path: item.file_path.clone(),
+                score_from: old.score,
+                score_to: item.score,
+                delta: item.score - old.score,
+            }),
+            Some(_) => {}
+        }
+    }
+    let functions_removed: Vec<ComplexityItem> = from_fns
+        .iter()
+        .filter(|(key, _)| !to_fns.contains_key(*key))
+        .map(|(_, item)| (*item).clone())
+        .collect();
+
+    // Biggest regressions first.
+    complexity_deltas.sort_by_key(|d| Reverse(d.delta));
+
+    // A file present in both runs changed if its contents did. Runs saved
+    // before files were hashed fall back to its size and functions.
+    let touched: HashSet<&str> = functions_added
+        .iter()
+        .chain(&functions_removed)
+        .map(|f| f.file_path.as_str())
+        .chain(complexity_deltas.iter().map(|d| d.file_path.as_str()))
+        .collect();
+    let files_changed: Vec<FileChange> = to_files
+        .iter()
+        .filter_map(|(path, &(to_lines, to_hash))| {
+            let &(from_lines, from_hash) = from_files.get(path)?;
+            let changed = match (from_hash, to_hash) {
+                (Some(from_hash), Some(to_hash)) => from_hash != to_hash,
+                _ => from_lines != to_lines || touched.contains(path),
+            };
+            changed.then(|| FileChange {
+                path: path.to_string(),
+                line_count_from: from_lines,
+                line_count_to: to_lines,
+            })
+        })
+        .collect();
+
+    // ── Dependencies ─────────────────────────────────────────────────────────
+    let from_edges = edge_set(&from.edges);
+    let to_edges = edge_set(&to.edges);
+    let edges_added = to_edges.difference(&from_edges).map(to_edge).collect();
+    let edges_removed = from_edges.difference(&to_edges).map(to_edge).collect();
+
+    // ── Dead code ────────────────────────────────────────────────────────────
+    let new_dead_code = new_dead_code(from, to).into_iter().cloned().collect();
+
+    RunDiff {
+        from_run,
+        to_run,
+        files_added,
+        files_removed,
+        files_changed,
+        functions_added,
+        functions_removed,
+        complexity_deltas,
+        edges_added,
+        edges_removed,
+        new_dead_code,
+    }
+}
+
+fn keyed_files(files: &[(String, i32, Option<String>)]) -> BTreeMap<&str, (i32, Option<&str>)> {
+    files
+        .iter()
+        .map(|(path, lines, hash)| (path.as_str(), (*lines, hash.as_deref())))
+        .collect()
+}
+
+/// Keys functions by (file, qualified name, n) where `n` counts earlier
+/// functions with the same identity in that file, so duplicates such as
+/// `#[cfg]` variants pair up in order.
+fn keyed_functions(items: &[ComplexityItem]) -> BTreeMap<(&str, &str, usize), &ComplexityItem> {
+    let mut sorted: Vec<&ComplexityItem> = items.iter().collect();
+    sorted.sort_by_key(|f| (f.file_path.as_str(), identity(f), f.line_start));
+
+    let mut seen: HashMap<(&str, &str), usize> = HashMap::new();
+    sorted
+        .into_iter()
+        .map(|f| {
+            let n = seen.entry((&f.file_path, identity(f))).or_default();
+            let key = (f.file_path.as_str(), identity(f), *n);
+            *n += 1;
+            (key, f)
+        })
+        .collect()
+}
+
+/// Reports saved before functions had a qualified name only carry the name.
+fn identity(f: &ComplexityItem) -> &str {
+    if f.qualified_name.is_empty() { &f.function_name } else { &f.qualified_name }
+}
+
+fn edge_set(edges: &[GraphEdge]) -> BTreeSet<(&str, &str)> {
+    edges.iter().map(|e| (e.from.as_str(), e.to.as_str())).collect()
+}
+
+fn to_edge(&(from, to): &(&str, &str)) -> GraphEdge {
+    GraphEdge {
+        from: from.to_owned(),
+        to: to.to_owned(),
+        label: Some("uses".into()),
+    }
+}
+
+/// Functions unreachable in `to` that were not unreachable in `from`, matched
+/// by file and qualified name.
+pub fn new_dead_code<'a>(from: &RunSnapshot, to: &'a RunSnapshot) -> Vec<&'a DeadCodeItem> {
+    let key = |d: &DeadCodeItem| -> (String, String) {
+        let name = if d.qualified_name.is_empty() { &d.function_name } else { &d.qualified_name };
+        (d.file_path.clone(), name.clone())
+    };
+    let known: HashSet<(String, String)> = from.dead_code.iter().map(key).collect();
+    to.dead_code.iter().filter(|d| !known.contains(&key(d))).collect()
+}
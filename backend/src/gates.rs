@@ -0,0 +1,141 @@
+use crate::config::GateConfig;
+use crate::diff;
+use crate::graph;
+use crate::models::{DeadCodeItem, GateReport, GateResult, GateViolation, RunSnapshot};
+
+/// Checks a run against the configured thresholds. `baseline` is the earlier
+/// run that "new" dead code is measured against; without one that gate passes.
+pub fn evaluate(config: &GateConfig, run: &RunSnapshot, baseline: Option<&RunSnapshot>) -> GateReport {
+    let mut gates = Vec::new();
+
+    if let Some(max) = config.max_function_complexity {
+        let mut over: Vec<_> = run.functions.iter().filter(|f| f.score > max).collect();
+        over.sort_by_key(|f| (&f.file_path, f.line_start));
+        let worst = run.functions.iter().map(|f| f.score).max().unwrap_or(0);
+        gates.push(GateResult {
+            gate: "max_function_complexity".into(),
+            threshold: max.to_string(),
+            actual: worst.to_string(),
+            passed: over.is_empty(),
+            violations: over
+                .into_iter()
+                .map(|f| GateViolation {
+                    file_path: Some(f.file_path.clone()),
+                    line: Some(f.line_start),
+                    message: format!("`{}` has complexity {} (max {max})", f.qualified_name, f.score),
+                })
+                .collect(),
+        });
+    }
+
+    if let Some(max) = config.max_avg_complexity {
+        let avg = average_complexity(run);
+        let passed = avg <= max;
+        gates.push(GateResult {
+            gate: "max_avg_complexity".into(),
+            threshold: format!("{max:.1}"),
+            actual: format!("{avg:.1}"),
+            passed,
+            violations: if passed {
+                Vec::new()
+            } else {
+                vec![GateViolation {
+                    file_path: None,
+                    line: None,
+                    message: format!("Average complexity is {avg:.1} (max {max:.1})"),
+                }]
+            },
+        });
+    }
+
+    if let Some(max) = config.max_file_lines {
+        let mut over: Vec<_> = run.files.iter().filter(|(_, lines, _)| *lines > max).collect();
+        over.sort();
+        let longest = run.files.iter().map(|(_, lines, _)| *lines).max().unwrap_or(0);
+        gates.push(GateResult {
+            gate: "max_file_lines".into(),
+            threshold: max.to_string(),
+            actual: longest.to_string(),
+            passed: over.is_empty(),
+            violations: over
+                .into_iter()
+                .map(|(path, lines, _)| GateViolation {
+                    file_path: Some(path.clone()),
+                    // Point at the first line past the limit.
+                    line: Some(max + 1),
+                    message: format!("File has {lines} lines (max {max})"),
+                })
+                .collect(),
+        });
+    }
+
+    if config.forbid_cycles {
+        gates.push(GateResult {
+            gate: "forbid_cycles".into(),
+            threshold: "0".into(),
+            actual: run.cycles.len().to_string(),
+            passed: run.cycles.is_empty(),
+            violations: run
+                .cycles
+                .iter()
+                .map(|cycle| {
+                    // Point at the `use` that leads back to the first file.
+                    let closing = cycle.imports.last();
+                    GateViolation {
+                        file_path: closing.map(|i| i.from.clone()),
+                        line: closing.map(|i| i.line),
+                        message: format!("Dependency cycle: {}", graph::describe_cycle(cycle)),
+                    }
+                })
+                .collect(),
+        });
+    }
+
+    if let Some(max) = config.max_new_dead_code {
+        gates.push(match baseline {
+            Some(baseline) => {
+                let new = diff::new_dead_code(baseline, run);
+                GateResult {
+                    gate: "max_new_dead_code".into(),
+                    threshold: max.to_string(),
+                    actual: new.len().to_string(),
+                    passed: new.len() <= max,
+                    violations: if new.len() <= max {
+                        Vec::new()
+                    } else {
+                        new.into_iter().map(dead_code_violation).collect()
+                    },
+                }
+            }
+            None => GateResult {
+                gate: "max_new_dead_code".into(),
+                threshold: max.to_string(),
+                actual: "no baseline".into(),
+                passed: true,
+                violations: Vec::new(),
+            },
+        });
+    }
+
+    GateReport {
+        run_id: None,
+        baseline_run_id: None,
+        passed: gates.iter().all(|g| g.passed),
+        gates,
+    }
+}
+
+fn average_complexity(run: &RunSnapshot) -> f64 {
+    if run.functions.is_empty() {
+        return 1.0;
+    }
+    run.functions.iter().map(|f| f.score as f64).sum::<f64>() / run.functions.len() as f64
+}
+
+fn dead_code_violation(item: &DeadCodeItem) -> GateViolation {
+    GateViolation {
+        file_path: Some(item.file_path.clone()),
+        line: Some(item.line_start),
+        message: format!("New unreachable function `{}`: {}", item.qualified_name, item.reason),
+    }
+}
@@ -1,49 +1,157 @@
-use std::collections::HashMap;
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, HashSet, VecDeque};
+use petgraph::algo::tarjan_scc;
 use petgraph::graph::{DiGraph, NodeIndex};
-use serde_json;
 
-use crate::models::{GraphData, GraphEdge, GraphNode, ParsedFile};
+use crate::models::{
+    DependencyCycle, GraphData, GraphEdge, GraphNode, ImportEdge, ImportTarget, ParsedFile,
+};
 
 pub struct DependencyGraph {
-    pub graph: DiGraph<String, ()>,
+    /// Edge weights are the `use` statements behind each edge.
+    pub graph: DiGraph<String, Vec<ImportEdge>>,
     pub index_map: HashMap<String, NodeIndex>,
+    /// Nodes standing for external crates rather than project files.
+    pub externs: HashSet<NodeIndex>,
 }
 
 impl DependencyGraph {
+    /// Expects import targets to be filled in by `resolve::link`.
     pub fn from_parsed(files: &[ParsedFile]) -> Self {
-        let mut graph: DiGraph<String, ()> = DiGraph::new();
-        let mut index_map: HashMap<String, NodeIndex> = HashMap::new();
+        let mut dep_graph = DependencyGraph {
+            graph: DiGraph::new(),
+            index_map: HashMap::new(),
+            externs: HashSet::new(),
+        };
 
         for pf in files {
-            let idx = graph.add_node(pf.path.clone());
-            index_map.insert(pf.path.clone(), idx);
+            dep_graph.node(&pf.path);
         }
 
         for pf in files {
-            let src_idx = match index_map.get(&pf.path) {
-                Some(&i) => i,
-                None => continue,
-            };
-
             for import in &pf.imports {
-                let target_id = normalise_import(import);
-
-                let dst_idx = if let Some(&i) = index_map.get(&target_id) {
-                    i
-                } else {
-                    let i = graph.add_node(target_id.clone());
-                    index_map.insert(target_id.clone(), i);
-                    i
+                let (target, external) = match &import.target {
+                    Some(ImportTarget::File(path)) => (path, false),
+                    Some(ImportTarget::Extern(name)) => (name, true),
+                    _ => continue,
                 };
-
-                // Avoid duplicate edges
-                if !graph.contains_edge(src_idx, dst_idx) {
-                    graph.add_edge(src_idx, dst_idx, ());
+                let dst = dep_graph.node(target);
+                if external {
+                    dep_graph.externs.insert(dst);
                 }
+                dep_graph.add_import(ImportEdge {
+                    from: pf.path.clone(),
+                    to: target.clone(),
+                    path: import.path.join("::"),
+                    line: import.line as i32,
+                });
             }
         }
 
-        DependencyGraph { graph, index_map }
+        dep_graph
+    }
+
+    /// The file graph of a stored run, from its `use` dependencies.
+    pub fn from_imports(imports: &[ImportEdge]) -> Self {
+        let mut dep_graph = DependencyGraph {
+            graph: DiGraph::new(),
+            index_map: HashMap::new(),
+            externs: HashSet::new(),
+        };
+        for import in imports {
+            dep_graph.add_import(import.clone());
+        }
+        dep_graph
+    }
+
+    fn node(&mut self, id: &str) -> NodeIndex {
+        if let Some(&i) = self.index_map.get(id) {
+            return i;
+        }
+        let i = self.graph.add_node(id.to_owned());
+        self.index_map.insert(id.to_owned(), i);
+        i
+    }
+
+    /// Adds the edge for `import`, or records it on the existing one.
+    fn add_import(&mut self, import: ImportEdge) {
+        let a = self.node(&import.from);
+        let b = self.node(&import.to);
+        match self.graph.find_edge(a, b) {
+            Some(e) => self.graph[e].push(import),
+            None => {
+                self.graph.add_edge(a, b, vec![import]);
+            }
+        }
+    }
+
+    /// Strongly connected components of the file graph (Tarjan), each
+    /// reported as the shortest cycle through its first file along with the
+    /// `use` statements that close it. Self-imports count as a cycle of one.
+    pub fn cycles(&self) -> Vec<DependencyCycle> {
+        let mut cycles: Vec<DependencyCycle> = tarjan_scc(&self.graph)
+            .into_iter()
+            .filter(|scc| scc.len() > 1 || self.graph.contains_edge(scc[0], scc[0]))
+            .filter_map(|scc| {
+                let component: HashSet<NodeIndex> = scc.iter().copied().collect();
+                let start = *scc.iter().min_by_key(|&&i| &self.graph[i])?;
+                let order = self.shortest_cycle(start, &component)?;
+
+                let mut imports = Vec::new();
+                for (k, &a) in order.iter().enumerate() {
+                    let b = order[(k + 1) % order.len()];
+                    let e = self.graph.find_edge(a, b)?;
+                    let mut closing = self.graph[e].clone();
+                    closing.sort_by_key(|i| i.line);
+                    imports.extend(closing);
+                }
+
+                let mut members: Vec<String> = scc.iter().map(|&i| self.graph[i].clone()).collect();
+                members.sort();
+                Some(DependencyCycle {
+                    files: order.iter().map(|&i| self.graph[i].clone()).collect(),
+                    members,
+                    imports,
+                })
+            })
+            .collect();
+        cycles.sort_by(|a, b| a.members.cmp(&b.members));
+        cycles
+    }
+
+    /// Nodes of the shortest path from `start` back to itself inside
+    /// `component`, starting with `start`.
+    fn shortest_cycle(&self, start: NodeIndex, component: &HashSet<NodeIndex>) -> Option<Vec<NodeIndex>> {
+        let successors = |n: NodeIndex| {
+            let mut next: Vec<NodeIndex> = self
+                .graph
+                .neighbors(n)
+                .filter(|m| component.contains(m))
+                .collect();
+            next.sort_by_key(|&m| &self.graph[m]);
+            next.dedup();
+            next
+        };
+
+        let mut parent: HashMap<NodeIndex, NodeIndex> = HashMap::new();
+        let mut queue = VecDeque::from([start]);
+        while let Some(n) = queue.pop_front() {
+            for m in successors(n) {
+                if m == start {
+                    let mut path = vec![n];
+                    while let Some(&p) = parent.get(path.last()?) {
+                        path.push(p);
+                    }
+                    path.reverse();
+                    return Some(path);
+                }
+                if let Entry::Vacant(entry) = parent.entry(m) {
+                    entry.insert(n);
+                    queue.push_back(m);
+                }
+            }
+        }
+        None
     }
 
     pub fn to_graph_data(&self) -> GraphData {
@@ -52,9 +160,8 @@ impl DependencyGraph {
             .node_indices()
             .map(|idx| {
                 let id = &self.graph[idx];
-                let label = id.split("::").last().unwrap_or(id).to_string();
-                // Heuristic: if the id contains "/" it's a file node, otherwise external
-                let kind = if id.contains('/') { "file" } else { "module" };
+                let label = id.rsplit('/').next().unwrap_or(id).to_string();
+                let kind = if self.externs.contains(&idx) { "extern" } else { "file" };
                 GraphNode {
                     id: id.clone(),
                     label,
@@ -80,18 +187,106 @@ impl DependencyGraph {
     }
 }
 
+/// `a.rs → b.rs → a.rs`.
+pub fn describe_cycle(cycle: &DependencyCycle) -> String {
+    let mut files: Vec<&str> = cycle.files.iter().map(String::as_str).collect();
+    files.extend(cycle.files.first().map(String::as_str));
+    files.join(" → ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{parser, resolve};
+
+    fn import(from: &str, to: &str, line: i32) -> ImportEdge {
+        let module = to.trim_start_matches("src/").trim_end_matches(".rs");
+        ImportEdge {
+            from: from.to_owned(),
+            to: to.to_owned(),
+            path: format!("crate::{module}"),
+            line,
+        }
+    }
+
+    fn lines(imports: &[ImportEdge]) -> Vec<(&str, &str, i32)> {
+        imports.iter().map(|i| (i.from.as_str(), i.to.as_str(), i.line)).collect()
+    }
+
+    #[test]
+    fn each_component_reports_its_shortest_cycle_and_closing_imports() {
+        let graph = DependencyGraph::from_imports(&[
+            // a → b → c → d → a, with c → a closing a shorter loop.
+            import("src/a.rs", "src/b.rs", 1),
+            import("src/b.rs", "src/c.rs", 1),
+            import("src/c.rs", "src/d.rs", 1),
+            import("src/d.rs", "src/a.rs", 1),
+            import("src/c.rs", "src/a.rs", 7),
+            import("src/c.rs", "src/a.rs", 3),
+            // x ⇄ y, reached from a but not part of its cycle.
+            import("src/a.rs", "src/x.rs", 2),
+            import("src/x.rs", "src/y.rs", 1),
+            import("src/y.rs", "src/x.rs", 4),
+            // Acyclic.
+            import("src/main.rs", "src/a.rs", 1),
+        ]);
+
+        let cycles = graph.cycles();
+        assert_eq!(cycles.len(), 2);
 
-fn normalise_import(raw: &str) -> String {
-    let trimmed = raw
-        .trim()
-        .trim_start_matches("use ")
-        .trim_end_matches(';')
-        .trim();
+        let abcd = &cycles[0];
+        assert_eq!(abcd.members, ["src/a.rs", "src/b.rs", "src/c.rs", "src/d.rs"]);
+        assert_eq!(abcd.files, ["src/a.rs", "src/b.rs", "src/c.rs"]);
+        assert_eq!(
+            lines(&abcd.imports),
+            [
+                ("src/a.rs", "src/b.rs", 1),
+                ("src/b.rs", "src/c.rs", 1),
+                ("src/c.rs", "src/a.rs", 3),
+                ("src/c.rs", "src/a.rs", 7),
+            ]
+        );
+        assert_eq!(describe_cycle(abcd), "src/a.rs → src/b.rs → src/c.rs → src/a.rs");
 
-    let top = trimmed.split("::").next().unwrap_or(trimmed);
+        let xy = &cycles[1];
+        assert_eq!(xy.members, ["src/x.rs", "src/y.rs"]);
+        assert_eq!(xy.files, ["src/x.rs", "src/y.rs"]);
+        assert_eq!(lines(&xy.imports), [("src/x.rs", "src/y.rs", 1), ("src/y.rs", "src/x.rs", 4)]);
+    }
+
+    #[test]
+    fn a_stored_self_import_is_a_cycle_of_one() {
+        let cycles = DependencyGraph::from_imports(&[import("src/a.rs", "src/a.rs", 2)]).cycles();
+        assert_eq!(cycles.len(), 1);
+        assert_eq!(cycles[0].files, ["src/a.rs"]);
+        assert_eq!(lines(&cycles[0].imports), [("src/a.rs", "src/a.rs", 2)]);
+    }
+
+    #[test]
+    fn imports_within_a_file_make_no_self_loops() {
+        let mut files = parser::parse_sources(&[
+            (
+                "src/lib.rs",
+                r#"
+                pub mod db;
+                pub fn run() {}
+                #[cfg(test)]
+                mod tests {
+                    use super::*;
+                    use crate::run;
+                }
+                "#,
+            ),
+            ("src/db.rs", "use crate::db::helper;\npub fn helper() {}\n"),
+        ]);
+        resolve::link(&mut files, &[]);
+        assert!(files
+            .iter()
+            .flat_map(|f| &f.imports)
+            .all(|i| i.target == Some(ImportTarget::Local)));
 
-    match top {
-        "std" | "core" | "alloc" => trimmed.to_owned(),
-        _ => trimmed.to_owned(),
+        let graph = DependencyGraph::from_parsed(&files);
+        assert_eq!(graph.graph.edge_count(), 0);
+        assert!(graph.cycles().is_empty());
     }
 }
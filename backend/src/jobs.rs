@@ -0,0 +1,401 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use anyhow::{bail, Context, Result};
+use rayon::prelude::*;
+use tokio::sync::{broadcast, mpsc};
+use tokio::task::JoinHandle;
+use tracing::{error, info, warn};
+use uuid::Uuid;
+
+use crate::{
+    api::AppState,
+    complexity,
+    config::GravityConfig,
+    crates,
+    db,
+    graph::DependencyGraph,
+    layers,
+    models::{AnalyzeRequest, AnalyzeResponse, JobEvent, JobStatus, ParsedFile, UpdateEvent},
+    parser,
+    resolve,
+    walk,
+    watch,
+};
+
+/// Minimum gap between progress writes to the `jobs` row while parsing.
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Events buffered per job before slow subscribers start missing some.
+const EVENT_BUFFER: usize = 1024;
+
+/// Live event channels for the jobs running in this process, and one for
+/// every run they store.
+#[derive(Clone)]
+pub struct JobEvents {
+    channels: Arc<Mutex<HashMap<Uuid, broadcast::Sender<JobEvent>>>>,
+    updates: broadcast::Sender<UpdateEvent>,
+}
+
+impl Default for JobEvents {
+    fn default() -> Self {
+        JobEvents {
+            channels: Arc::default(),
+            updates: broadcast::channel(EVENT_BUFFER).0,
+        }
+    }
+}
+
+impl JobEvents {
+    /// Subscribes to a running job, or returns `None` once it has finished.
+    pub fn subscribe(&self, job_id: Uuid) -> Option<broadcast::Receiver<JobEvent>> {
+        let channels = self.channels.lock().unwrap();
+        channels.get(&job_id).map(|tx| tx.subscribe())
+    }
+
+    /// Subscribes to the runs stored from now on, by any job.
+    pub fn subscribe_updates(&self) -> broadcast::Receiver<UpdateEvent> {
+        self.updates.subscribe()
+    }
+
+    fn open(&self, job_id: Uuid) {
+        let (tx, _) = broadcast::channel(EVENT_BUFFER);
+        self.channels.lock().unwrap().insert(job_id, tx);
+    }
+
+    fn publish(&self, job_id: Uuid, event: JobEvent) {
+        if let Some(tx) = self.channels.lock().unwrap().get(&job_id) {
+            // No subscribers is fine; the event is simply dropped.
+            let _ = tx.send(event);
+        }
+    }
+
+    fn announce(&self, event: UpdateEvent) {
+        let _ = self.updates.send(event);
+    }
+
+    /// Drops the sender, which ends every subscriber's stream.
+    fn close(&self, job_id: Uuid) {
+        self.channels.lock().unwrap().remove(&job_id);
+    }
+}
+
+/// Runs the job in the background; the outcome is recorded on its `jobs` row.
+/// The handle resolves once it is.
+pub fn spawn(state: Arc<AppState>, job_id: Uuid, req: AnalyzeRequest) -> JoinHandle<()> {
+    // Open the channel up front so a client subscribing straight after
+    // `POST /api/analyze` never misses the start of the run.
+    state.events.open(job_id);
+
+    tokio::spawn(async move {
+        match run(&state, job_id, &req).await {
+            Ok(result) => {
+                state.events.announce(UpdateEvent::Analyzed { result: result.clone() });
+                state.events.publish(job_id, JobEvent::Done { result });
+            }
+            Err(e) => {
+                error!("Analysis job {job_id} failed: {e:#}");
+                let message = format!("{e:#}");
+                if let Err(e) = db::fail_job(&state.pool, job_id, &message).await {
+                    error!("Failed to record failure of job {job_id}: {e}");
+                }
+                state.events.publish(job_id, JobEvent::Failed { error: message });
+            }
+        }
+        state.events.close(job_id);
+    })
+}
+
+/// Re-queues jobs that were interrupted by a backend restart. They start over
+/// from the beginning since no partial results are persisted, and those that
+/// asked for it watch their directory again. Watches whose job had already
+/// finished are not stored and end with the process.
+pub async fn resume_unfinished(state: &Arc<AppState>) -> Result<()> {
+    let jobs = db::fetch_unfinished_jobs(&state.pool).await?;
+    if !jobs.is_empty() {
+        info!("Resuming {} unfinished analysis job(s)", jobs.len());
+    }
+
+    for (job_id, req) in jobs {
+        if req.watch {
+            if let Err(e) = watch::start(state.clone(), req.clone()) {
+                warn!("Job {job_id} no longer watches its directory: {e:#}");
+            }
+        }
+        db::set_job_status(&state.pool, job_id, JobStatus::Queued).await?;
+        spawn(state.clone(), job_id, req);
+    }
+
+    Ok(())
+}
+
+/// Records the new phase on the `jobs` row and announces it to subscribers.
+async fn enter_phase(state: &AppState, job_id: Uuid, status: JobStatus) -> Result<()> {
+    db::set_job_status(&state.pool, job_id, status).await?;
+    state.events.publish(job_id, JobEvent::Phase { status });
+    Ok(())
+}
+
+async fn run(state: &AppState, job_id: Uuid, req: &AnalyzeRequest) -> Result<AnalyzeResponse> {
+    let mut analyze_path = req.path.clone().unwrap_or_else(|| state.analyze_path.clone());
+    // Clones land in a fresh temp dir each time, so they are keyed by URL.
+    let mut project_path = analyze_path.clone();
+    let mut project_name = req
+        .project_name
+        .as_deref()
+        .unwrap_or("default")
+        .to_owned();
+
+    // Removed again once the job finishes, whatever the outcome.
+    let mut clone_dir = None;
+
+    if let Some(url) = &req.github_url {
+        enter_phase(state, job_id, JobStatus::Cloning).await?;
+        let path = clone_repository(url).await?;
+
+        analyze_path = path.to_string_lossy().into_owned();
+        project_path = url.clone();
+        clone_dir = Some(path);
+
+        // If project name was default, use repo name from URL
+        if project_name == "default" {
+            project_name = url.trim_end_matches('/').rsplit('/').next().unwrap_or("default").to_owned();
+        }
+    }
+
+    let result = analyze(state, job_id, req, &project_name, &project_path, &analyze_path).await;
+
+    if let Some(path) = clone_dir {
+        info!("Cleaning up temp directory: {:?}", path);
+        let _ = tokio::fs::remove_dir_all(path).await;
+    }
+
+    let response = result?;
+    db::complete_job(&state.pool, job_id, &response).await?;
+    info!("Analysis job {job_id} done: {}", response.message);
+
+    Ok(response)
+}
+
+/// `gravity.toml` under `root` with the request's include and exclude globs
+/// added. Stored with the run, so its config shows the globs that applied.
+pub fn load_config(root: &Path, req: &AnalyzeRequest) -> Result<GravityConfig> {
+    let mut config = GravityConfig::load(root)?;
+    config.files.include.extend(req.include.iter().cloned());
+    config.files.exclude.extend(req.exclude.iter().cloned());
+    Ok(config)
+}
+
+async fn analyze(
+    state: &AppState,
+    job_id: Uuid,
+    req: &AnalyzeRequest,
+    project_name: &str,
+    project_path: &str,
+    analyze_path: &str,
+) -> Result<AnalyzeResponse> {
+    info!("Starting analysis of path: {analyze_path}");
+    let config = load_config(Path::new(analyze_path), req)?;
+
+    // ── Parsing ──────────────────────────────────────────────────────────────
+    enter_phase(state, job_id, JobStatus::Parsing).await?;
+
+    let walk_root = PathBuf::from(analyze_path);
+    let files = config.files.clone();
+    let (walk::RustFiles { paths, skipped, .. }, crates) = tokio::task::spawn_blocking(move || {
+        let found = walk::rust_files(&walk_root, &files);
+        let crates = crates::load(&walk_root, &found.manifests);
+        (found, crates)
+    })
+    .await?;
+    let files_total = paths.len();
+    // Reused files are put back in walk order, as a full parse returns them.
+    let order: HashMap<String, usize> = paths
+        .iter()
+        .enumerate()
+        .map(|(i, path)| (parser::stored_path(Path::new(analyze_path), path), i))
+        .collect();
+
+    // Files unchanged since the project's last run are not parsed again.
+    let previous = db::fetch_reusable_files(&state.pool, project_name, project_path).await?;
+    let root = PathBuf::from(analyze_path);
+    let (reused, paths) = tokio::task::spawn_blocking(move || {
+        parser::reuse_unchanged(&root, paths, previous, db::ANALYZER_VERSION)
+    })
+    .await?;
+    let files_reused = reused.len();
+    let mut files_parsed = files_reused;
+    let mut functions_found: usize = reused.iter().map(|s| s.file.functions.len()).sum();
+    // Reused files as the run that wrote their rows linked them.
+    let stored: HashMap<String, (Uuid, ParsedFile)> = reused
+        .iter()
+        .filter_map(|s| Some((s.file.path.clone(), (s.run_id?, s.file.clone()))))
+        .collect();
+    db::set_job_progress(&state.pool, job_id, files_total, files_parsed, functions_found).await?;
+
+    // The parser runs on a blocking thread and reports each file back here.
+    let (tx, mut rx) = mpsc::unbounded_channel::<ParsedFileReport>();
+    let root = PathBuf::from(analyze_path);
+    let parse_task = tokio::task::spawn_blocking(move || {
+        parser::parse_files(&root, &paths, |path, outcome| {
+            let _ = tx.send(ParsedFileReport::new(path, outcome));
+        })
+    });
+
+    let mut last_write = Instant::now();
+    while let Some(report) = rx.recv().await {
+        files_parsed += 1;
+        functions_found += report.functions_found;
+        state.events.publish(
+            job_id,
+            JobEvent::File {
+                path: report.path,
+                functions_found: report.functions_found,
+                syntax_errors: report.syntax_errors,
+                error: report.error,
+                files_parsed,
+                files_total,
+            },
+        );
+        if last_write.elapsed() >= PROGRESS_INTERVAL {
+            db::set_job_progress(&state.pool, job_id, files_total, files_parsed, functions_found).await?;
+            last_write = Instant::now();
+        }
+    }
+    db::set_job_progress(&state.pool, job_id, files_total, files_parsed, functions_found).await?;
+
+    let mut parsed_files = parse_task.await??;
+    let files_reparsed = parsed_files.len();
+    parsed_files.extend(reused.into_iter().map(|s| s.file));
+    parsed_files.sort_by_key(|pf| order.get(&pf.path).copied());
+
+    // ── Scoring ──────────────────────────────────────────────────────────────
+    enter_phase(state, job_id, JobStatus::Scoring).await?;
+
+    let layers = config.layers.clone();
+    let (parsed_files, crates, copied, complexity_scores, layer_violations) = tokio::task::spawn_blocking(move || {
+        let mut parsed_files = parsed_files;
+        resolve::link(&mut parsed_files, &crates);
+        let dep_graph = DependencyGraph::from_parsed(&parsed_files);
+        let violations = layers::violations(&layers, &dep_graph, &parsed_files);
+        // A reused file that links as it did before has the same rows and
+        // scores, which are copied from the run that stored them.
+        let copied: HashMap<String, Uuid> = parsed_files
+            .iter()
+            .filter_map(|pf| {
+                let (run_id, before) = stored.get(&pf.path)?;
+                (before == pf).then(|| (pf.path.clone(), *run_id))
+            })
+            .collect();
+        let scores = complexity::compute_all(parsed_files.par_iter().filter(|pf| !copied.contains_key(&pf.path)));
+        (parsed_files, crates, copied, scores, violations)
+    })
+    .await?;
+
+    // ── Saving ───────────────────────────────────────────────────────────────
+    enter_phase(state, job_id, JobStatus::Saving).await?;
+
+    let commit_sha = head_commit(analyze_path).await;
+    let project = db::upsert_project(&state.pool, project_name, project_path).await?;
+    let run = db::save_analysis(
+        &state.pool,
+        project.id,
+        commit_sha.as_deref(),
+        &config,
+        &parsed_files,
+        &copied,
+        &complexity_scores,
+        &layer_violations,
+    )
+    .await?;
+    db::save_crates(&state.pool, &run, &crates).await?;
+
+    let functions_found: usize = parsed_files.iter().map(|f| f.functions.len()).sum();
+    let mut message = format!("Analysis complete: {} files", parsed_files.len());
+    if files_reused > 0 {
+        message.push_str(&format!(" ({files_reused} unchanged)"));
+    }
+    message.push_str(&format!(", {functions_found} functions"));
+    if let Some(skipped) = walk::describe(&skipped) {
+        message.push_str(&format!(" (skipped {skipped})"));
+    }
+    Ok(AnalyzeResponse {
+        project_id: project.id,
+        run_id: run.id,
+        files_analyzed: parsed_files.len(),
+        files_reused,
+        files_reparsed,
+        files_skipped: skipped,
+        functions_found,
+        message,
+    })
+}
+
+/// What the parser thread sends back for each file it attempted.
+struct ParsedFileReport {
+    path: String,
+    functions_found: usize,
+    syntax_errors: usize,
+    error: Option<String>,
+}
+
+impl ParsedFileReport {
+    fn new(path: &Path, outcome: Result<&ParsedFile, &anyhow::Error>) -> Self {
+        match outcome {
+            Ok(pf) => ParsedFileReport {
+                path: pf.path.clone(),
+                functions_found: pf.functions.len(),
+                syntax_errors: pf.syntax_errors,
+                error: None,
+            },
+            Err(e) => ParsedFileReport {
+                path: path.to_string_lossy().replace('\\', "/"),
+                functions_found: 0,
+                syntax_errors: 0,
+                error: Some(format!("{e:#}")),
+            },
+        }
+    }
+}
+
+async fn clone_repository(url: &str) -> Result<PathBuf> {
+    info!("Cloning GitHub repository: {url}");
+
+    let path = std::env::temp_dir().join(format!("gravity-clone-{}", Uuid::new_v4()));
+    tokio::fs::create_dir_all(&path)
+        .await
+        .context("Failed to create temp dir")?;
+
+    let output = tokio::process::Command::new("git")
+        .args(["clone", "--depth", "1", url])
+        .arg(&path)
+        .output()
+        .await
+        .context("Git clone failed to start")?;
+
+    if !output.status.success() {
+        let err = String::from_utf8_lossy(&output.stderr);
+        warn!("Git clone failed: {err}");
+        let _ = tokio::fs::remove_dir_all(&path).await;
+        bail!("Git clone failed: {err}");
+    }
+
+    Ok(path)
+}
+
+/// The checked-out commit of `path`, when it is inside a git work tree.
+async fn head_commit(path: &str) -> Option<String> {
+    let output = tokio::process::Command::new("git")
+        .args(["-C", path, "rev-parse", "HEAD"])
+        .output()
+        .await
+        .ok()?;
+
+    if !output.status.success() {
+        return None;
+    }
+    let sha = String::from_utf8_lossy(&output.stdout).trim().to_owned();
+    (!sha.is_empty()).then_some(sha)
+}
@@ -0,0 +1,140 @@
+use std::collections::HashMap;
+
+use petgraph::visit::EdgeRef;
+
+use crate::config::LayerConfig;
+use crate::graph::DependencyGraph;
+use crate::models::{LayerViolation, ParsedFile};
+
+/// Checks every `use` between project files against the declared layers.
+/// Violations are ordered by file and line.
+pub fn violations(
+    layers: &[LayerConfig],
+    graph: &DependencyGraph,
+    files: &[ParsedFile],
+) -> Vec<LayerViolation> {
+    if layers.is_empty() {
+        return Vec::new();
+    }
+    let layer_of: HashMap<&str, &LayerConfig> = files
+        .iter()
+        .filter_map(|pf| {
+            let module = pf.module_path.as_deref()?;
+            let layer = layers.iter().find(|l| l.modules.iter().any(|p| glob_matches(p, module, "::")))?;
+            Some((pf.path.as_str(), layer))
+        })
+        .collect();
+
+    let mut found = Vec::new();
+    for edge in graph.graph.edge_references() {
+        if graph.externs.contains(&edge.target()) {
+            continue;
+        }
+        let source = graph.graph[edge.source()].as_str();
+        let target = graph.graph[edge.target()].as_str();
+        let (Some(from), Some(to)) = (layer_of.get(source), layer_of.get(target)) else {
+            continue;
+        };
+        if !forbidden(from, &to.name) {
+            continue;
+        }
+        found.extend(edge.weight().iter().map(|import| LayerViolation {
+            from_layer: from.name.clone(),
+            to_layer: to.name.clone(),
+            file_path: import.from.clone(),
+            line: import.line,
+            use_path: import.path.clone(),
+            target_path: import.to.clone(),
+        }));
+    }
+    found.sort_by(|a, b| (&a.file_path, a.line).cmp(&(&b.file_path, b.line)));
+    found
+}
+
+fn forbidden(from: &LayerConfig, to: &str) -> bool {
+    if from.name == to {
+        return false;
+    }
+    from.forbid.iter().any(|l| l == to)
+        || from.allow.as_ref().is_some_and(|allow| !allow.iter().any(|l| l == to))
+}
+
+/// Matches a module or file path against a pattern, both split into segments
+/// on `separator`: `*` matches within one segment and `**` any number of them.
+pub fn glob_matches(pattern: &str, path: &str, separator: &str) -> bool {
+    let pattern: Vec<&str> = pattern.split(separator).collect();
+    let path: Vec<&str> = path.split(separator).collect();
+    matches_segments(&pattern, &path)
+}
+
+fn matches_segments(pattern: &[&str], module: &[&str]) -> bool {
+    match pattern.split_first() {
+        None => module.is_empty(),
+        Some((&"**", rest)) => (0..=module.len()).any(|skip| matches_segments(rest, &module[skip..])),
+        Some((first, rest)) => module
+            .split_first()
+            .is_some_and(|(segment, tail)| wildcard(first, segment) && matches_segments(rest, tail)),
+    }
+}
+
+/// `*` within one segment matches any run of characters.
+fn wildcard(pattern: &str, text: &str) -> bool {
+    let Some((prefix, rest)) = pattern.split_once('*') else {
+        return pattern == text;
+    };
+    let Some(text) = text.strip_prefix(prefix) else {
+        return false;
+    };
+    (0..=text.len())
+        .filter(|&i| text.is_char_boundary(i))
+        .any(|i| wildcard(rest, &text[i..]))
+}
+
+/// One line per violation, for notes and the CLI.
+pub fn describe(v: &LayerViolation) -> String {
+    format!(
+        "`{}` must not use `{}`: `use {}` at {}:{}",
+        v.from_layer, v.to_layer, v.use_path, v.file_path, v.line
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn double_star_matches_zero_or_more_segments() {
+        assert!(glob_matches("crate::**::db", "crate::db", "::"));
+        assert!(glob_matches("crate::**::db", "crate::store::db", "::"));
+        assert!(glob_matches("crate::**::db", "crate::a::b::db", "::"));
+        assert!(!glob_matches("crate::**::db", "crate::a::dbx", "::"));
+    }
+
+    #[test]
+    fn trailing_double_star_matches_the_module_and_everything_below() {
+        assert!(glob_matches("crate::api::**", "crate::api", "::"));
+        assert!(glob_matches("crate::api::**", "crate::api::handlers::users", "::"));
+        assert!(!glob_matches("crate::api::**", "crate::apis", "::"));
+        assert!(glob_matches("src/**", "src/a/b.rs", "/"));
+    }
+
+    #[test]
+    fn single_star_stays_within_a_segment() {
+        assert!(glob_matches("crate::*", "crate::db", "::"));
+        assert!(!glob_matches("crate::*", "crate::db::pool", "::"));
+        assert!(glob_matches("crate::*_store", "crate::user_store", "::"));
+        assert!(glob_matches("src/*.rs", "src/main.rs", "/"));
+        assert!(!glob_matches("src/*.rs", "src/bin/tool.rs", "/"));
+        assert!(glob_matches("src/*_test.rs", "src/parser_test.rs", "/"));
+        assert!(glob_matches("**/gen_*.rs", "src/out/gen_types.rs", "/"));
+    }
+
+    #[test]
+    fn pattern_without_wildcards_matches_only_the_whole_path() {
+        assert!(glob_matches("crate::db", "crate::db", "::"));
+        assert!(!glob_matches("crate::db", "crate::db::pool", "::"));
+        assert!(!glob_matches("crate::db", "crate", "::"));
+        assert!(glob_matches("src/main.rs", "src/main.rs", "/"));
+        assert!(!glob_matches("main.rs", "src/main.rs", "/"));
+    }
+}
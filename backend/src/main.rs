@@ -1,21 +1,62 @@
 mod api;
+mod callgraph;
+mod cli;
 mod complexity;
+mod config;
+mod crates;
 mod db;
+mod diff;
+mod gates;
 mod graph;
+mod jobs;
+mod layers;
+mod metrics;
 mod models;
 mod parser;
+mod report;
+mod resolve;
+mod sarif;
+mod walk;
+mod watch;
 
 use std::net::SocketAddr;
+use std::process::ExitCode;
+use std::sync::Arc;
 use anyhow::{Context, Result};
+use clap::Parser;
 use tower_http::cors::{Any, CorsLayer};
 use tower_http::trace::TraceLayer;
 use tracing::info;
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt, EnvFilter};
 
 use api::{AppState, build_router};
+use cli::{Cli, Command, ServeArgs};
 
 #[tokio::main]
-async fn main() -> Result<()> {
+async fn main() -> Result<ExitCode> {
+    match Cli::parse().command.unwrap_or(Command::Serve(ServeArgs::default())) {
+        Command::Serve(args) => serve(&args).await.map(|_| ExitCode::SUCCESS),
+        Command::Analyze(args) => {
+            init_cli_tracing();
+            cli::run_analyze(&args).map(|_| ExitCode::SUCCESS)
+        }
+        Command::Check(args) => {
+            init_cli_tracing();
+            let passed = cli::run_check(&args)?;
+            Ok(if passed { ExitCode::SUCCESS } else { ExitCode::FAILURE })
+        }
+    }
+}
+
+/// Keep stdout for the report; only warnings go to stderr.
+fn init_cli_tracing() {
+    tracing_subscriber::registry()
+        .with(EnvFilter::try_from_default_env().unwrap_or_else(|_| "warn".into()))
+        .with(tracing_subscriber::fmt::layer().with_writer(std::io::stderr))
+        .init();
+}
+
+async fn serve(args: &ServeArgs) -> Result<()> {
     // ── 1. Tracing / logging ─────────────────────────────────────────────────
     tracing_subscriber::registry()
         .with(EnvFilter::try_from_default_env().unwrap_or_else(|_| "info".into()))
@@ -45,10 +86,23 @@ async fn main() -> Result<()> {
     db::run_migrations(&pool).await?;
 
     // ── 4. Application state ─────────────────────────────────────────────────
-    let state = AppState {
+    let state = Arc::new(AppState {
         pool,
         analyze_path,
-    };
+        events: jobs::JobEvents::default(),
+        watched: watch::Watched::default(),
+    });
+
+    jobs::resume_unfinished(&state).await?;
+
+    if let Some(path) = &args.watch {
+        let req = watch::request_for(path);
+        // A resumed job that watches the same directory runs its first analysis.
+        if watch::start(state.clone(), req.clone())? {
+            let job = db::create_job(&state.pool, &req).await?;
+            jobs::spawn(state.clone(), job.id, req);
+        }
+    }
 
     // ── 5. CORS ──────────────────────────────────────────────────────────────
     let cors = CorsLayer::new()
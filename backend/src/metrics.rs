@@ -0,0 +1,167 @@
+use std::collections::HashSet;
+
+use petgraph::Direction;
+
+use crate::graph::DependencyGraph;
+use crate::models::{ComplexityItem, ModuleMetrics};
+
+/// Files depended on by at least this many others count as load-bearing.
+const LOAD_BEARING_AFFERENT: i64 = 3;
+
+/// Fan-out above which a file is noted as depending on too much.
+const HIGH_EFFERENT: i64 = 10;
+
+/// How far into a corner of the A/I plane a file must sit to be noted.
+const ZONE_MARGIN: f64 = 0.3;
+
+/// Per-function limits above which a function is noted.
+const LONG_FUNCTION_LINES: i32 = 100;
+const MANY_PARAMS: i32 = 7;
+const MANY_GENERIC_PARAMS: i32 = 4;
+const DEEP_NESTING: i32 = 4;
+const MANY_RETURNS: i32 = 5;
+const MANY_UNWRAPS: i32 = 5;
+
+/// Files or functions named per architecture note before the rest are counted.
+const NOTED_ITEMS: usize = 3;
+
+/// Per-file coupling metrics. `types` gives (path, abstract types, concrete
+/// types) for every file of the run; files missing from `graph` have no
+/// coupling. Only dependencies between project files count: external
+/// crates are treated as stable and leave instability unchanged.
+pub fn module_metrics(graph: &DependencyGraph, types: &[(String, i32, i32)]) -> Vec<ModuleMetrics> {
+    let files: HashSet<&str> = types.iter().map(|(path, _, _)| path.as_str()).collect();
+
+    let coupling = |path: &str, direction: Direction| -> i64 {
+        let Some(&idx) = graph.index_map.get(path) else { return 0 };
+        graph
+            .graph
+            .neighbors_directed(idx, direction)
+            .filter(|&n| n != idx && files.contains(graph.graph[n].as_str()))
+            .collect::<HashSet<_>>()
+            .len() as i64
+    };
+
+    let mut metrics: Vec<ModuleMetrics> = types
+        .iter()
+        .map(|(path, abstract_types, concrete_types)| {
+            let afferent = coupling(path, Direction::Incoming);
+            let efferent = coupling(path, Direction::Outgoing);
+            let instability = ratio(efferent as f64, (afferent + efferent) as f64);
+            let abstractness =
+                ratio(*abstract_types as f64, (*abstract_types + *concrete_types) as f64);
+            ModuleMetrics {
+                path: path.clone(),
+                afferent,
+                efferent,
+                instability,
+                abstractness,
+                distance: (abstractness + instability - 1.0).abs(),
+                abstract_types: *abstract_types,
+                concrete_types: *concrete_types,
+            }
+        })
+        .collect();
+    metrics.sort_by(|a, b| a.path.cmp(&b.path));
+    metrics
+}
+
+fn ratio(part: f64, whole: f64) -> f64 {
+    if whole == 0.0 { 0.0 } else { part / whole }
+}
+
+/// Notes on files in the zone of pain (stable, concrete and depended on),
+/// the zone of uselessness (abstract, yet nothing depends on them) and with
+/// high fan-out.
+pub fn coupling_notes(modules: &[ModuleMetrics]) -> Vec<String> {
+    let mut notes = Vec::new();
+
+    let mut pain: Vec<&ModuleMetrics> = modules
+        .iter()
+        .filter(|m| {
+            m.afferent >= LOAD_BEARING_AFFERENT
+                && m.instability <= ZONE_MARGIN
+                && m.abstractness <= ZONE_MARGIN
+        })
+        .collect();
+    pain.sort_by_key(|m| std::cmp::Reverse(m.afferent));
+    if !pain.is_empty() {
+        notes.push(format!(
+            "Zone of pain: {} {} stable and concrete; changes there ripple into many dependents.",
+            list(&pain, |m| format!("{} (Ca={})", m.path, m.afferent)),
+            if pain.len() == 1 { "is" } else { "are" },
+        ));
+    }
+
+    let useless: Vec<&ModuleMetrics> = modules
+        .iter()
+        .filter(|m| {
+            m.abstract_types > 0
+                && m.abstractness >= 1.0 - ZONE_MARGIN
+                && m.instability >= 1.0 - ZONE_MARGIN
+        })
+        .collect();
+    if !useless.is_empty() {
+        notes.push(format!(
+            "Zone of uselessness: {} {} abstract but little depends on them.",
+            list(&useless, |m| m.path.clone()),
+            if useless.len() == 1 { "is" } else { "are" },
+        ));
+    }
+
+    let mut fan_out: Vec<&ModuleMetrics> =
+        modules.iter().filter(|m| m.efferent > HIGH_EFFERENT).collect();
+    fan_out.sort_by_key(|m| std::cmp::Reverse(m.efferent));
+    if !fan_out.is_empty() {
+        notes.push(format!(
+            "High fan-out: {} depend{} on more than {HIGH_EFFERENT} other files; possible tight coupling.",
+            list(&fan_out, |m| format!("{} (Ce={})", m.path, m.efferent)),
+            if fan_out.len() == 1 { "s" } else { "" },
+        ));
+    }
+
+    notes
+}
+
+/// (note label, limit, measure) of a per-function check.
+type FunctionCheck = (String, i32, fn(&ComplexityItem) -> i32);
+
+/// Notes on functions past the size and shape limits, worst first.
+pub fn function_notes(functions: &[ComplexityItem]) -> Vec<String> {
+    let checks: [FunctionCheck; 7] = [
+        (format!("Long functions (over {LONG_FUNCTION_LINES} lines of code)"), LONG_FUNCTION_LINES, |f| f.logical_lines),
+        (format!("Many parameters (over {MANY_PARAMS})"), MANY_PARAMS, |f| f.params),
+        (format!("Many generic parameters (over {MANY_GENERIC_PARAMS})"), MANY_GENERIC_PARAMS, |f| f.generic_params),
+        (format!("Deep nesting (over {DEEP_NESTING} levels)"), DEEP_NESTING, |f| f.max_nesting),
+        (format!("Many returns (over {MANY_RETURNS})"), MANY_RETURNS, |f| f.returns),
+        (format!("Many unwrap/expect calls (over {MANY_UNWRAPS})"), MANY_UNWRAPS, |f| f.unwraps),
+        ("Unsafe blocks".into(), 0, |f| f.unsafe_blocks),
+    ];
+
+    let mut notes = Vec::new();
+    for (label, limit, metric) in checks {
+        let mut over: Vec<&ComplexityItem> = functions.iter().filter(|f| metric(f) > limit).collect();
+        over.sort_by_key(|f| std::cmp::Reverse(metric(f)));
+        if !over.is_empty() {
+            notes.push(format!(
+                "{label}: {}.",
+                list(&over, |f| format!("{} ({})", f.qualified_name, metric(f))),
+            ));
+        }
+    }
+    notes
+}
+
+/// The first few items, then a count of the rest.
+fn list<T>(items: &[&T], describe: impl Fn(&T) -> String) -> String {
+    let mut text = items
+        .iter()
+        .take(NOTED_ITEMS)
+        .map(|item| describe(item))
+        .collect::<Vec<_>>()
+        .join(", ");
+    if items.len() > NOTED_ITEMS {
+        text.push_str(&format!(" and {} more", items.len() - NOTED_ITEMS));
+    }
+    text
+}
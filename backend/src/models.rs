@@ -1,6 +1,9 @@
+use std::collections::BTreeMap;
+
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 use chrono::{DateTime, Utc};
+use sqlx::types::Json;
 
 
 #[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
@@ -13,71 +16,381 @@ pub struct Project {
 }
 
 
+/// One immutable snapshot of a project; every entity row belongs to a run.
 #[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
-pub struct FileEntry {
-    pub id: Uuid,
-    pub project_id: Uuid,
-    pub path: String,
-    pub module_name: Option<String>,
-    pub line_count: i32,
-    pub created_at: DateTime<Utc>,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
-pub struct FunctionEntry {
+pub struct AnalysisRun {
     pub id: Uuid,
     pub project_id: Uuid,
-    pub file_id: Uuid,
-    pub name: String,
-    pub line_start: i32,
-    pub line_end: i32,
-    pub is_public: bool,
-    pub is_async: bool,
-    pub created_at: DateTime<Utc>,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
-pub struct Dependency {
-    pub id: Uuid,
-    pub project_id: Uuid,
-    pub file_id: Uuid,
-    pub source: String,
-    pub target: String,
-    pub kind: String,
+    pub commit_sha: Option<String>,
+    pub analyzer_version: String,
     pub created_at: DateTime<Utc>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
-pub struct ComplexityEntry {
+pub struct FileEntry {
     pub id: Uuid,
     pub project_id: Uuid,
-    pub function_id: Uuid,
-    pub score: i32,
+    pub run_id: Uuid,
+    pub path: String,
+    pub module_name: Option<String>,
+    pub line_count: i32,
+    /// `line_count` broken down; see `LineCounts`.
+    pub code_lines: i32,
+    pub comment_lines: i32,
+    pub doc_lines: i32,
+    pub blank_lines: i32,
+    /// Public items, and how many of them have doc comments.
+    pub public_items: i32,
+    pub documented_items: i32,
+    pub halstead_volume: f64,
+    pub halstead_difficulty: f64,
+    pub halstead_effort: f64,
+    /// 0–100; see `complexity::maintainability_index`.
+    pub maintainability_index: f64,
+    /// Package and target the file is compiled in, when a manifest covers it.
+    pub crate_name: Option<String>,
+    pub target: Option<String>,
     pub created_at: DateTime<Utc>,
 }
 
 // ─── Parsed (in-memory, pre-DB) ────────────────────────────────────────────
 
 /// Parsed file data before persisting to the database.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ParsedFile {
     pub path: String,
+    /// BLAKE3 of the source, hex-encoded; see `parser::content_hash`.
+    #[serde(default)]
+    pub content_hash: String,
     pub module_name: Option<String>,
     pub line_count: usize,
+    /// `line_count` broken down by what is on each line.
+    pub lines: LineCounts,
+    pub syntax_errors: usize,
     pub functions: Vec<ParsedFunction>,
-    pub imports: Vec<String>,
-    pub structs: Vec<String>,
+    pub imports: Vec<ParsedImport>,
+    /// Items other than functions and `use` declarations.
+    pub symbols: Vec<ParsedSymbol>,
+    /// Token counts over the whole file.
+    pub halstead: Halstead,
+    /// `crate::a::b`, set by `resolve::link`.
+    pub module_path: Option<String>,
+    /// Package the file belongs to, set by `resolve::link`.
+    #[serde(default)]
+    pub crate_name: Option<String>,
+    /// Label of the target it is compiled into; see `CrateTarget::label`.
+    #[serde(default)]
+    pub target: Option<String>,
+    pub mods: Vec<ParsedMod>,
+    /// Calls and references in macros invoked or defined at item level, which
+    /// belong to no function.
+    pub item_references: Vec<CallSite>,
 }
 
-/// Parsed function data before persisting.
+/// A file as an earlier run parsed it; see `parser::reuse_unchanged`.
+#[derive(Debug, Clone)]
+pub struct StoredParse {
+    pub analyzer_version: String,
+    /// The run whose rows were written from `file`, as linked then. `None`
+    /// for parses stored before runs recorded it.
+    pub run_id: Option<Uuid>,
+    pub file: ParsedFile,
+}
+
+/// One leaf of a `use` tree; `pub use a::{b, c as d, e::*}` yields three.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ParsedImport {
+    /// Full path of the leaf. For globs, the module being expanded.
+    pub path: Vec<String>,
+    pub alias: Option<String>,
+    pub is_glob: bool,
+    /// `pub`, `pub(crate)`, … when the import is a re-export.
+    pub visibility: Option<String>,
+    /// Inline `mod` blocks the declaration sits in, outermost first.
+    pub scope: Vec<String>,
+    pub line: usize,
+    /// Filled in by `resolve::link` once every file is parsed.
+    #[serde(default)]
+    pub target: Option<ImportTarget>,
+}
+
+/// What an import refers to. Unresolvable imports have no target.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", content = "name", rename_all = "snake_case")]
+pub enum ImportTarget {
+    /// Another file of the project.
+    File(String),
+    /// An external crate.
+    Extern(String),
+    /// The importing file itself, e.g. `use super::*` in an inline test module.
+    Local,
+}
+
+impl ImportTarget {
+    /// Value of `dependencies.kind` for an import with this target.
+    pub fn kind(target: Option<&ImportTarget>) -> &'static str {
+        match target {
+            Some(ImportTarget::File(_)) => "use",
+            Some(ImportTarget::Extern(_)) => "extern",
+            Some(ImportTarget::Local) => "self",
+            None => "unresolved",
+        }
+    }
+}
+
+/// Halstead's operator and operand counts; see `complexity::halstead`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct Halstead {
+    pub distinct_operators: usize,
+    pub distinct_operands: usize,
+    pub operators: usize,
+    pub operands: usize,
+}
+
+impl Halstead {
+    /// V = N · log2(n), the bits needed to write the code down.
+    pub fn volume(&self) -> f64 {
+        let vocabulary = self.distinct_operators + self.distinct_operands;
+        if vocabulary < 2 {
+            return 0.0;
+        }
+        (self.operators + self.operands) as f64 * (vocabulary as f64).log2()
+    }
+
+    /// D = (n1 / 2) · (N2 / n2), how hard the code is to write or read.
+    pub fn difficulty(&self) -> f64 {
+        if self.distinct_operands == 0 {
+            return 0.0;
+        }
+        self.distinct_operators as f64 / 2.0 * self.operands as f64 / self.distinct_operands as f64
+    }
+
+    /// E = D · V.
+    pub fn effort(&self) -> f64 {
+        self.difficulty() * self.volume()
+    }
+}
+
+impl ParsedFile {
+    pub fn count(&self, kind: SymbolKind) -> usize {
+        self.symbols.iter().filter(|s| s.kind == kind).count()
+    }
+
+    /// (public items, documented public items) for doc coverage. Impls
+    /// carry no docs of their own and modules are documented in their own
+    /// file, so both are left out.
+    pub fn doc_items(&self) -> (usize, usize) {
+        let functions = self.functions.iter().filter(|f| f.is_public).map(|f| f.documented);
+        let symbols = self
+            .symbols
+            .iter()
+            .filter(|s| !matches!(s.kind, SymbolKind::Impl | SymbolKind::Mod))
+            .filter(|s| s.visibility.as_deref().is_some_and(|v| v.starts_with("pub")))
+            .map(|s| s.documented);
+        functions.chain(symbols).fold((0, 0), |(public, documented), d| (public + 1, documented + usize::from(d)))
+    }
+}
+
+/// What each line of a file holds. A line with any code on it is a code
+/// line, whatever comments follow it.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LineCounts {
+    pub code: usize,
+    /// `//` and `/* */` comments.
+    pub comment: usize,
+    /// `///`, `//!`, `/** */` and `/*! */` doc comments.
+    pub doc: usize,
+    pub blank: usize,
+}
+
+impl LineCounts {
+    /// Comment and doc lines over all non-blank lines; 0 for an empty file.
+    pub fn comment_ratio(&self) -> f64 {
+        let written = self.code + self.comment + self.doc;
+        if written == 0 {
+            return 0.0;
+        }
+        (self.comment + self.doc) as f64 / written as f64
+    }
+}
+
+impl std::iter::Sum for LineCounts {
+    fn sum<I: Iterator<Item = LineCounts>>(iter: I) -> LineCounts {
+        iter.fold(LineCounts::default(), |total, lines| LineCounts {
+            code: total.code + lines.code,
+            comment: total.comment + lines.comment,
+            doc: total.doc + lines.doc,
+            blank: total.blank + lines.blank,
+        })
+    }
+}
+
+/// Kind of a `ParsedSymbol`, stored as `symbols.kind`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SymbolKind {
+    Struct,
+    Enum,
+    Union,
+    Trait,
+    Impl,
+    Const,
+    Static,
+    Type,
+    Macro,
+    Mod,
+}
+
+impl SymbolKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            SymbolKind::Struct => "struct",
+            SymbolKind::Enum => "enum",
+            SymbolKind::Union => "union",
+            SymbolKind::Trait => "trait",
+            SymbolKind::Impl => "impl",
+            SymbolKind::Const => "const",
+            SymbolKind::Static => "static",
+            SymbolKind::Type => "type",
+            SymbolKind::Macro => "macro",
+            SymbolKind::Mod => "mod",
+        }
+    }
+}
+
+/// An item other than a function or `use`. Associated consts and types carry
+/// the `impl` or trait they belong to in `self_type` and `trait_name`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ParsedSymbol {
+    pub kind: SymbolKind,
+    /// For impls, `Foo` or `Display for Foo`.
+    pub name: String,
+    pub line_start: usize,
+    pub line_end: usize,
+    pub visibility: Option<String>,
+    /// Inline `mod` blocks enclosing the item, outermost first.
+    pub scope: Vec<String>,
+    pub self_type: Option<String>,
+    pub trait_name: Option<String>,
+    /// Has an outer doc comment or `#[doc = ...]`.
+    #[serde(default)]
+    pub documented: bool,
+}
+
+/// A stored symbol, as listed by `GET /api/symbols`.
 #[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SymbolEntry {
+    pub kind: String,
+    pub name: String,
+    pub file_path: String,
+    pub line_start: i32,
+    pub line_end: i32,
+    pub visibility: Option<String>,
+    pub self_type: Option<String>,
+    pub trait_name: Option<String>,
+}
+
+/// A `mod` item, either `mod foo;` or an inline `mod foo { .. }`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ParsedMod {
+    pub name: String,
+    pub inline: bool,
+    /// Value of a `#[path = "..."]` attribute, if any.
+    pub path_attr: Option<String>,
+    pub scope: Vec<String>,
+}
+
+/// Parsed function data before persisting.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ParsedFunction {
     pub name: String,
     pub line_start: usize,
     pub line_end: usize,
     pub is_public: bool,
     pub is_async: bool,
-    pub body_source: String,
+    /// `pub`, `pub(crate)`, … as written.
+    pub visibility: Option<String>,
+    /// Paths of the outer attributes, e.g. `test` or `tokio::main`.
+    pub attributes: Vec<String>,
+    /// Has an outer doc comment or `#[doc = ...]`.
+    #[serde(default)]
+    pub documented: bool,
+    /// Type of the enclosing `impl` block, or the enclosing trait.
+    pub owner: Option<String>,
+    /// Trait implemented by the enclosing `impl` block, or the enclosing trait.
+    pub trait_name: Option<String>,
+    /// Inline `mod` blocks the function sits in, outermost first.
+    pub scope: Vec<String>,
+    /// Index in `ParsedFile::functions` of the function whose body this one
+    /// is nested in.
+    pub parent: Option<usize>,
+    /// `crate::a::b`, including inline modules. Set by `resolve::link`.
+    #[serde(default)]
+    pub module_path: String,
+    /// The function's identity within its crate, e.g. `crate::config::Config::new`
+    /// or `<crate::a::Foo as Display>::fmt`. Set by `resolve::link`.
+    #[serde(default)]
+    pub qualified_name: String,
+    #[serde(default)]
+    pub shape: FunctionShape,
+    /// Scores read off the syntax tree while parsing; `complexity::compute_all`
+    /// completes them once calls are resolved.
+    #[serde(default)]
+    pub cyclomatic: usize,
+    /// Cognitive complexity without the recursion increment.
+    #[serde(default)]
+    pub cognitive: usize,
+    #[serde(default)]
+    pub halstead: Halstead,
+    pub calls: Vec<CallSite>,
+    /// Why no entry point reaches this function; `None` when one does.
+    /// Filled in by `callgraph::mark_unreachable`.
+    #[serde(default)]
+    pub unreachable: Option<String>,
+}
+
+/// Size and shape of a function, measured by the parser. Nested functions
+/// are measured on their own and left out of their parent's numbers.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FunctionShape {
+    /// Lines holding code; blank and comment-only lines are left out.
+    pub logical_lines: usize,
+    pub params: usize,
+    /// Lifetime, type and const parameters.
+    pub generic_params: usize,
+    /// Deepest nesting of `if`, `match`, loops and closures.
+    pub max_nesting: usize,
+    /// `return` expressions, not counting those inside closures.
+    pub returns: usize,
+    pub unsafe_blocks: usize,
+    /// `.unwrap()` and `.expect(..)` calls.
+    pub unwraps: usize,
+}
+
+/// A call inside a function body.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CallSite {
+    /// The callee as written, e.g. `["Self", "new"]`; just the method name
+    /// for method calls.
+    pub path: Vec<String>,
+    pub is_method: bool,
+    /// A method call on `self`.
+    pub on_self: bool,
+    /// The callee is named without being called, e.g. `.map(parse)` or a
+    /// path inside a macro.
+    #[serde(default)]
+    pub is_reference: bool,
+    pub line: usize,
+    /// Filled in by `callgraph::resolve_calls` when the callee is known.
+    #[serde(default)]
+    pub target: Option<FunctionRef>,
+}
+
+/// A function of the analysis, by file and position in `ParsedFile::functions`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct FunctionRef {
+    pub file: String,
+    pub index: usize,
 }
 
 
@@ -85,12 +398,25 @@ pub struct ParsedFunction {
 pub struct AnalysisSummary {
     pub project_id: Uuid,
     pub project_name: String,
+    pub run_id: Uuid,
+    pub commit_sha: Option<String>,
+    pub analyzed_at: DateTime<Utc>,
     pub total_files: i64,
     pub total_functions: i64,
     pub total_structs: i64,
     pub total_imports: i64,
+    /// Symbols per kind, e.g. `"enum" → 4`.
+    pub symbol_counts: BTreeMap<String, i64>,
     pub avg_complexity: f64,
-    pub dead_code_candidates: Vec<String>,
+    /// Share of non-blank lines that are comments or docs.
+    pub comment_ratio: f64,
+    /// Share of public items with a doc comment.
+    pub doc_coverage: f64,
+    /// Unreachable functions; listed by `GET /api/dead-code`.
+    pub dead_code_count: i64,
+    /// The numbers above per package, ordered by path.
+    pub crates: Vec<CrateSummary>,
+    pub layer_violations: Vec<LayerViolation>,
     pub architecture_notes: Vec<String>,
 }
 
@@ -114,32 +440,412 @@ pub struct GraphData {
     pub edges: Vec<GraphEdge>,
 }
 
+/// A `use` that crosses from one layer into another it may not use.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LayerViolation {
+    pub from_layer: String,
+    pub to_layer: String,
+    pub file_path: String,
+    pub line: i32,
+    pub use_path: String,
+    pub target_path: String,
+}
+
+/// Robert Martin's package metrics for one file of a run.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModuleMetrics {
+    pub path: String,
+    /// Afferent coupling (Ca): files that depend on this one.
+    pub afferent: i64,
+    /// Efferent coupling (Ce): files this one depends on.
+    pub efferent: i64,
+    /// I = Ce / (Ca + Ce); 0 for a file with no coupling at all.
+    pub instability: f64,
+    /// A = traits / all type definitions; 0 for a file defining no types.
+    pub abstractness: f64,
+    /// Distance from the main sequence, D = |A + I - 1|.
+    pub distance: f64,
+    pub abstract_types: i32,
+    pub concrete_types: i32,
+}
+
+/// A `use` statement, as an edge between the importing and imported file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ImportEdge {
+    pub from: String,
+    pub to: String,
+    /// The imported path, e.g. `crate::db::save_analysis`.
+    pub path: String,
+    pub line: i32,
+}
+
+/// Files that depend on each other. `files` is a cycle in import order (each
+/// imports the next, the last imports the first); `members` is the whole
+/// strongly connected component, which may be larger.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DependencyCycle {
+    pub files: Vec<String>,
+    pub members: Vec<String>,
+    /// The `use` statements along `files` that close the cycle.
+    pub imports: Vec<ImportEdge>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ComplexityItem {
     pub function_name: String,
+    #[serde(default)]
+    pub qualified_name: String,
     pub file_path: String,
+    /// Cyclomatic complexity.
     pub score: i32,
+    /// Cognitive complexity; see `complexity::cognitive`.
+    #[serde(default)]
+    pub cognitive: i32,
+    #[serde(default)]
+    pub halstead_volume: f64,
+    #[serde(default)]
+    pub halstead_difficulty: f64,
+    #[serde(default)]
+    pub halstead_effort: f64,
+    #[serde(default)]
+    pub maintainability_index: f64,
+    /// See `FunctionShape`.
+    #[serde(default)]
+    pub logical_lines: i32,
+    #[serde(default)]
+    pub params: i32,
+    #[serde(default)]
+    pub generic_params: i32,
+    #[serde(default)]
+    pub max_nesting: i32,
+    #[serde(default)]
+    pub returns: i32,
+    #[serde(default)]
+    pub unsafe_blocks: i32,
+    #[serde(default)]
+    pub unwraps: i32,
+    pub line_start: i32,
+    pub line_end: i32,
+}
+
+/// A function no entry point of the project reaches.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeadCodeItem {
+    pub function_name: String,
+    #[serde(default)]
+    pub qualified_name: String,
+    pub file_path: String,
     pub line_start: i32,
     pub line_end: i32,
+    pub reason: String,
+}
+
+/// One page of `GET /api/dead-code`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeadCodePage {
+    pub items: Vec<DeadCodeItem>,
+    pub total: i64,
+    pub page: i64,
+    pub per_page: i64,
+}
+
+// ─── Crates ────────────────────────────────────────────────────────────────
+
+/// A package read from its `Cargo.toml` by `crates::load`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CrateManifest {
+    pub name: String,
+    /// Directory of the manifest relative to the root; empty for the root.
+    pub path: String,
+    pub version: Option<String>,
+    /// Directory of the workspace root the package is a member of.
+    pub workspace: Option<String>,
+    pub targets: Vec<CrateTarget>,
+    pub dependencies: Vec<CrateDependency>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TargetKind {
+    Lib,
+    Bin,
+    Test,
+    Example,
+    Bench,
+    /// The build script.
+    Build,
+}
+
+impl TargetKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            TargetKind::Lib => "lib",
+            TargetKind::Bin => "bin",
+            TargetKind::Test => "test",
+            TargetKind::Example => "example",
+            TargetKind::Bench => "bench",
+            TargetKind::Build => "build",
+        }
+    }
+}
+
+/// One compilation target of a package.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CrateTarget {
+    pub kind: TargetKind,
+    pub name: String,
+    /// The crate root file, relative to the analysis root.
+    pub root: String,
+}
+
+impl CrateTarget {
+    /// `lib` and `build` alone, the others with their name: `bin:gravity`.
+    pub fn label(&self) -> String {
+        match self.kind {
+            TargetKind::Lib | TargetKind::Build => self.kind.as_str().to_owned(),
+            kind => format!("{}:{}", kind.as_str(), self.name),
+        }
+    }
+}
+
+/// Which table of the manifest a dependency is declared in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, sqlx::Type)]
+#[serde(rename_all = "lowercase")]
+#[sqlx(type_name = "TEXT", rename_all = "lowercase")]
+pub enum DependencyKind {
+    Normal,
+    Dev,
+    Build,
+}
+
+impl DependencyKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            DependencyKind::Normal => "normal",
+            DependencyKind::Dev => "dev",
+            DependencyKind::Build => "build",
+        }
+    }
+}
+
+/// An edge of the crate graph.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CrateDependency {
+    pub from: String,
+    /// Package name, after any `package = "..."` rename.
+    pub to: String,
+    pub kind: DependencyKind,
+    /// Whether `to` is one of the analyzed packages.
+    pub local: bool,
+}
+
+/// Summary numbers of one package of a run.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CrateSummary {
+    pub name: String,
+    pub path: String,
+    pub version: Option<String>,
+    /// Labels of its targets; see `CrateTarget::label`.
+    pub targets: Vec<String>,
+    pub files: i64,
+    pub functions: i64,
+    pub lines: i64,
+    pub avg_complexity: f64,
+    pub doc_coverage: f64,
+    pub dead_code: i64,
 }
 
+// ─── Run comparison ────────────────────────────────────────────────────────
+
+/// Everything `GET /api/diff` compares, loaded for one run.
+#[derive(Debug, Clone, Default)]
+pub struct RunSnapshot {
+    /// (path, line_count, content_hash); runs saved before files were hashed have no hash.
+    pub files: Vec<(String, i32, Option<String>)>,
+    pub functions: Vec<ComplexityItem>,
+    pub edges: Vec<GraphEdge>,
+    pub cycles: Vec<DependencyCycle>,
+    pub dead_code: Vec<DeadCodeItem>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileChange {
+    pub path: String,
+    pub line_count_from: i32,
+    pub line_count_to: i32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ComplexityDelta {
+    pub function_name: String,
+    pub qualified_name: String,
+    pub file_path: String,
+    pub score_from: i32,
+    pub score_to: i32,
+    pub delta: i32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunDiff {
+    pub from_run: Uuid,
+    pub to_run: Uuid,
+    pub files_added: Vec<String>,
+    pub files_removed: Vec<String>,
+    pub files_changed: Vec<FileChange>,
+    pub functions_added: Vec<ComplexityItem>,
+    pub functions_removed: Vec<ComplexityItem>,
+    pub complexity_deltas: Vec<ComplexityDelta>,
+    pub edges_added: Vec<GraphEdge>,
+    pub edges_removed: Vec<GraphEdge>,
+    pub new_dead_code: Vec<DeadCodeItem>,
+}
+
+// ─── Quality gates ─────────────────────────────────────────────────────────
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GateViolation {
+    pub file_path: Option<String>,
+    pub line: Option<i32>,
+    pub message: String,
+}
+
+/// Outcome of one `[gates]` threshold from `gravity.toml`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GateResult {
+    pub gate: String,
+    pub threshold: String,
+    pub actual: String,
+    pub passed: bool,
+    pub violations: Vec<GateViolation>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GateReport {
+    pub run_id: Option<Uuid>,
+    pub baseline_run_id: Option<Uuid>,
+    pub passed: bool,
+    pub gates: Vec<GateResult>,
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AnalyzeRequest {
     pub project_name: Option<String>,
     pub path: Option<String>,
     pub github_url: Option<String>,
+    /// Added to `[files] include` from `gravity.toml`.
+    #[serde(default)]
+    pub include: Vec<String>,
+    /// Added to `[files] exclude` from `gravity.toml`.
+    #[serde(default)]
+    pub exclude: Vec<String>,
+    /// Keep watching `path` and analyze it again whenever its sources change.
+    /// Watches live only as long as the backend process and are not stored:
+    /// after a restart, send the request again (or pass `serve --watch`) to
+    /// keep watching. The one exception is a request whose own job was still
+    /// running, which resumes along with its watch.
+    #[serde(default)]
+    pub watch: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AnalyzeResponse {
     pub project_id: Uuid,
+    pub run_id: Uuid,
     pub files_analyzed: usize,
+    /// Unchanged since the previous run, so taken from it instead of parsed.
+    #[serde(default)]
+    pub files_reused: usize,
+    /// New or changed since the previous run.
+    #[serde(default)]
+    pub files_reparsed: usize,
+    #[serde(default)]
+    pub files_skipped: SkippedFiles,
     pub functions_found: usize,
     pub message: String,
+}
+
+/// `.rs` files under the root that were left out of an analysis, by reason.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SkippedFiles {
+    /// Matched by `.gitignore`, `.ignore` or `.gravityignore`.
+    pub ignored: usize,
+    /// Matched by an `exclude` glob.
+    pub excluded: usize,
+    /// Outside every `include` glob.
+    pub not_included: usize,
+    /// Under a Cargo `target/` directory, which the analysis does not walk.
+    #[serde(default)]
+    pub target_files: usize,
+    /// The `target/` directories those files were in.
+    pub target_dirs: usize,
+}
+
+// ─── Analysis jobs ─────────────────────────────────────────────────────────
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[serde(rename_all = "lowercase")]
+#[sqlx(type_name = "TEXT", rename_all = "lowercase")]
+pub enum JobStatus {
+    Queued,
+    Cloning,
+    Parsing,
+    Scoring,
+    Saving,
+    Done,
+    Failed,
+}
+
+/// A background analysis run as reported by `GET /api/jobs/{id}`.
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct Job {
+    pub id: Uuid,
+    pub project_id: Option<Uuid>,
+    pub status: JobStatus,
+    pub files_total: i32,
+    pub files_parsed: i32,
+    pub functions_found: i32,
+    pub result: Option<Json<AnalyzeResponse>>,
+    pub error: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
 
-    #[serde(skip)]
-    pub parsed_files_internal: Option<Vec<ParsedFile>>,
-    #[serde(skip)]
-    pub complexity_scores_internal: Option<Vec<(String, String, usize)>>,
+/// Progress event pushed to `GET /api/analyze/{job}/events` subscribers.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum JobEvent {
+    Phase {
+        status: JobStatus,
+    },
+    File {
+        path: String,
+        functions_found: usize,
+        syntax_errors: usize,
+        error: Option<String>,
+        files_parsed: usize,
+        files_total: usize,
+    },
+    Done {
+        result: AnalyzeResponse,
+    },
+    Failed {
+        error: String,
+    },
+}
+
+/// Pushed to `GET /api/events` subscribers, whoever started the job.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum UpdateEvent {
+    /// A run was stored; views of its project are out of date.
+    Analyzed {
+        result: AnalyzeResponse,
+    },
+}
+
+/// Returned by `POST /api/analyze` once the job has been queued.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JobAccepted {
+    pub job_id: Uuid,
+    pub status: JobStatus,
 }
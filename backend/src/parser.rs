@@ -1,39 +1,113 @@
-use std::path::Path;
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
 use anyhow::{Context, Result};
-use walkdir::WalkDir;
-use tree_sitter::{Language, Node, Parser};
-use crate::models::{ParsedFile, ParsedFunction};
-
-extern "C" {
-    fn tree_sitter_rust() -> Language;
-}
-pub fn parse_directory(root_path: &str) -> Result<Vec<ParsedFile>> {
-    let mut parser = Parser::new();
-    let lang = unsafe { tree_sitter_rust() };
-    parser
-        .set_language(lang)
-        .context("Failed to set tree-sitter Rust language")?;
-
-    let mut results = Vec::new();
-    for entry in WalkDir::new(root_path)
-        .follow_links(false)
-        .into_iter()
-        .filter_map(|e| e.ok())
-        .filter(|e| e.file_type().is_file())
-        .filter(|e| e.path().extension().map(|x| x == "rs").unwrap_or(false))
-    {
-        let path = entry.path();
-        match parse_file(&mut parser, path) {
-            Ok(pf) => results.push(pf),
-            Err(e) => {
-                tracing::warn!("Skipping {:?}: {e}", path);
+use rayon::prelude::*;
+use tree_sitter::{Node, Parser};
+use crate::complexity;
+use crate::models::{
+    CallSite, FunctionShape, LineCounts, ParsedFile, ParsedFunction, ParsedImport, ParsedMod, ParsedSymbol, StoredParse,
+    SymbolKind,
+};
+
+/// Parses `paths` on the rayon pool, calling `on_file` from the worker with
+/// the outcome for each one as it finishes. Files that fail to parse are
+/// reported there and then skipped; the rest come back in the order of
+/// `paths`. Stored paths are relative to `root_dir` so runs of the same tree
+/// line up with each other.
+pub fn parse_files(
+    root_dir: &Path,
+    paths: &[PathBuf],
+    on_file: impl Fn(&Path, Result<&ParsedFile, &anyhow::Error>) + Sync,
+) -> Result<Vec<ParsedFile>> {
+    let results: Vec<Option<ParsedFile>> = paths
+        .par_iter()
+        .map(|path| {
+            let outcome = with_parser(|parser| parse_file(parser, root_dir, path));
+            match outcome {
+                Ok(pf) => {
+                    on_file(path, Ok(&pf));
+                    Some(pf)
+                }
+                Err(e) => {
+                    tracing::warn!("Skipping {:?}: {e}", path);
+                    on_file(path, Err(&e));
+                    None
+                }
+            }
+        })
+        .collect();
+
+    Ok(results.into_iter().flatten().collect())
+}
+
+/// Splits `paths` into the files whose contents still hash to what `previous`
+/// recorded for them under this `analyzer_version`, which are returned as
+/// they were stored then, and the paths that need parsing. Reused files keep
+/// what `resolve::link` set, which it overwrites when linking them again.
+/// Unreadable files are left for `parse_files` to report.
+pub fn reuse_unchanged(
+    root_dir: &Path,
+    paths: Vec<PathBuf>,
+    mut previous: HashMap<String, StoredParse>,
+    analyzer_version: &str,
+) -> (Vec<StoredParse>, Vec<PathBuf>) {
+    let hashes: Vec<Option<String>> = paths
+        .par_iter()
+        .map(|path| std::fs::read(path).ok().map(|source| content_hash(&source)))
+        .collect();
+
+    let mut reused = Vec::new();
+    let mut changed = Vec::new();
+    for (path, hash) in paths.into_iter().zip(hashes) {
+        match previous.remove(&stored_path(root_dir, &path)) {
+            Some(stored)
+                if stored.analyzer_version == analyzer_version
+                    && hash.as_ref() == Some(&stored.file.content_hash) =>
+            {
+                reused.push(stored)
             }
+            _ => changed.push(path),
         }
     }
+    (reused, changed)
+}
 
-    Ok(results)
+/// `ParsedFile::path` of the file at `path`: relative to `root_dir`, with `/`
+/// separators.
+pub fn stored_path(root_dir: &Path, path: &Path) -> String {
+    path.strip_prefix(root_dir)
+        .unwrap_or(path)
+        .to_str()
+        .unwrap_or_default()
+        .replace('\\', "/")
 }
-fn parse_file(parser: &mut Parser, path: &Path) -> Result<ParsedFile> {
+
+/// BLAKE3 hash of a file's contents, hex-encoded.
+pub fn content_hash(source: &[u8]) -> String {
+    blake3::hash(source).to_hex().to_string()
+}
+
+thread_local! {
+    /// One parser per worker thread, reused for every file it takes on.
+    static PARSER: RefCell<Option<Parser>> = const { RefCell::new(None) };
+}
+
+fn with_parser<T>(f: impl FnOnce(&mut Parser) -> Result<T>) -> Result<T> {
+    PARSER.with(|cell| {
+        let mut slot = cell.borrow_mut();
+        if slot.is_none() {
+            let mut parser = Parser::new();
+            parser
+                .set_language(&tree_sitter_rust::language())
+                .context("Failed to set tree-sitter Rust language")?;
+            *slot = Some(parser);
+        }
+        f(slot.as_mut().expect("parser was just created"))
+    })
+}
+
+fn parse_file(parser: &mut Parser, root_dir: &Path, path: &Path) -> Result<ParsedFile> {
     let source = std::fs::read_to_string(path)
         .with_context(|| format!("Cannot read {}", path.display()))?;
 
@@ -48,62 +122,254 @@ fn parse_file(parser: &mut Parser, path: &Path) -> Result<ParsedFile> {
         .and_then(|s| s.to_str())
         .map(str::to_owned);
 
-    let mut functions = Vec::new();
-    let mut imports = Vec::new();
-    let mut structs = Vec::new();
+    let syntax_errors = count_syntax_errors(&root);
 
-    visit_node(&root, &source, &mut functions, &mut imports, &mut structs);
-
-    let path_str = path
-        .to_str()
-        .unwrap_or_default()
-        .replace('\\', "/")
-        .to_owned();
+    let mut items = Items::default();
+    visit_node(&root, &source, &mut items, &Enclosing::default());
 
     Ok(ParsedFile {
-        path: path_str,
+        path: stored_path(root_dir, path),
+        content_hash: content_hash(source.as_bytes()),
         module_name,
         line_count,
-        functions,
-        imports,
-        structs,
+        lines: classify_lines(&root, &source),
+        syntax_errors,
+        functions: items.functions,
+        imports: items.imports,
+        symbols: items.symbols,
+        halstead: complexity::halstead(&root, &source),
+        module_path: None,
+        crate_name: None,
+        target: None,
+        mods: items.mods,
+        item_references: items.item_references,
     })
 }
 
-/// 
-fn visit_node(
-    node: &Node,
-    source: &str,
-    functions: &mut Vec<ParsedFunction>,
-    imports: &mut Vec<String>,
-    structs: &mut Vec<String>,
-) {
+/// Counts the `ERROR` and missing nodes tree-sitter inserted while recovering.
+fn count_syntax_errors(node: &Node) -> usize {
+    if !node.has_error() {
+        return 0;
+    }
+    if node.is_error() || node.is_missing() {
+        return 1;
+    }
+
+    let mut cursor = node.walk();
+    let count = node.children(&mut cursor).map(|c| count_syntax_errors(&c)).sum();
+    count
+}
+
+/// What is on a line, in order of precedence.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum LineKind {
+    Blank,
+    Comment,
+    Doc,
+    Code,
+}
+
+fn classify_lines(root: &Node, source: &str) -> LineCounts {
+    let mut kinds = vec![LineKind::Blank; source.lines().count()];
+    mark_lines(root, &mut kinds);
+
+    let mut counts = LineCounts::default();
+    for (kind, line) in kinds.into_iter().zip(source.lines()) {
+        match kind {
+            // Text no token covers, e.g. inside an unparsable stretch.
+            LineKind::Blank if !line.trim().is_empty() => counts.code += 1,
+            LineKind::Blank => counts.blank += 1,
+            LineKind::Comment => counts.comment += 1,
+            LineKind::Doc => counts.doc += 1,
+            LineKind::Code => counts.code += 1,
+        }
+    }
+    counts
+}
+
+fn mark_lines(node: &Node, kinds: &mut [LineKind]) {
+    let kind = match node.kind() {
+        "line_comment" | "block_comment" if node.child_by_field_name("doc").is_some() => LineKind::Doc,
+        "line_comment" | "block_comment" => LineKind::Comment,
+        _ if node.child_count() == 0 => LineKind::Code,
+        _ => {
+            let mut cursor = node.walk();
+            for child in node.children(&mut cursor) {
+                mark_lines(&child, kinds);
+            }
+            return;
+        }
+    };
+
+    let start = node.start_position().row;
+    let mut end = node.end_position().row;
+    // Line comments end on the next line, after their newline.
+    if end > start && node.end_position().column == 0 {
+        end -= 1;
+    }
+    for line in kinds.iter_mut().take(end + 1).skip(start) {
+        *line = (*line).max(kind);
+    }
+}
+
+/// Everything collected from one file's syntax tree.
+#[derive(Default)]
+struct Items {
+    functions: Vec<ParsedFunction>,
+    imports: Vec<ParsedImport>,
+    symbols: Vec<ParsedSymbol>,
+    mods: Vec<ParsedMod>,
+    item_references: Vec<CallSite>,
+}
+
+/// Where the walk currently is.
+#[derive(Debug, Clone, Default)]
+struct Enclosing {
+    /// Inline `mod` blocks, outermost first.
+    scope: Vec<String>,
+    /// Type of the enclosing `impl`, or the enclosing trait.
+    owner: Option<String>,
+    /// Trait implemented by the enclosing `impl`, or the enclosing trait.
+    trait_name: Option<String>,
+    /// Inside a function body rather than at item level.
+    in_body: bool,
+    /// Index in `Items::functions` of the innermost enclosing function.
+    function: Option<usize>,
+}
+
+fn visit_node(node: &Node, source: &str, items: &mut Items, ctx: &Enclosing) {
+    // Set when the children of this node live in a different context.
+    let mut inner = None;
     match node.kind() {
         "function_item" => {
-            if let Some(func) = extract_function(node, source) {
-                functions.push(func);
+            let mut function = ctx.function;
+            if let Some(func) = extract_function(node, source, ctx) {
+                function = Some(items.functions.len());
+                items.functions.push(func);
             }
+            // Functions nested in a body are free functions, not methods.
+            inner = Some(Enclosing {
+                owner: None,
+                trait_name: None,
+                in_body: true,
+                function,
+                ..ctx.clone()
+            });
         }
         "use_declaration" => {
-            if let Some(import) = extract_use(node, source) {
-                imports.push(import);
+            items.imports.extend(extract_use(node, source, &ctx.scope));
+        }
+        "struct_item" | "enum_item" | "union_item" | "const_item" | "static_item" | "type_item"
+        | "associated_type" => {
+            let kind = match node.kind() {
+                "struct_item" => SymbolKind::Struct,
+                "enum_item" => SymbolKind::Enum,
+                "union_item" => SymbolKind::Union,
+                "const_item" => SymbolKind::Const,
+                "static_item" => SymbolKind::Static,
+                _ => SymbolKind::Type,
+            };
+            if let Some(name) = extract_name(node, source) {
+                items.symbols.push(symbol(node, source, ctx, kind, name));
             }
         }
-        "struct_item" => {
+        "mod_item" => {
             if let Some(name) = extract_name(node, source) {
-                structs.push(name);
+                items.symbols.push(symbol(node, source, ctx, SymbolKind::Mod, name.clone()));
+                let inline = node.child_by_field_name("body").is_some();
+                items.mods.push(ParsedMod {
+                    name: name.clone(),
+                    inline,
+                    path_attr: path_attribute(node, source),
+                    scope: ctx.scope.clone(),
+                });
+                if inline {
+                    let mut scope = ctx.scope.clone();
+                    scope.push(name);
+                    inner = Some(Enclosing { scope, ..Enclosing::default() });
+                }
+            }
+        }
+        "impl_item" => {
+            let owner = node.child_by_field_name("type").map(|t| type_name(&t, source));
+            let trait_name = node.child_by_field_name("trait").map(|t| type_name(&t, source));
+            let name = match (&trait_name, &owner) {
+                (Some(t), Some(o)) => format!("{t} for {o}"),
+                (_, o) => o.clone().unwrap_or_default(),
+            };
+            items.symbols.push(ParsedSymbol {
+                self_type: owner.clone(),
+                trait_name: trait_name.clone(),
+                ..symbol(node, source, ctx, SymbolKind::Impl, name)
+            });
+            inner = Some(Enclosing { owner, trait_name, ..ctx.clone() });
+        }
+        "trait_item" => {
+            let name = extract_name(node, source);
+            if let Some(name) = &name {
+                items.symbols.push(symbol(node, source, ctx, SymbolKind::Trait, name.clone()));
+            }
+            inner = Some(Enclosing {
+                owner: name.clone(),
+                trait_name: name,
+                ..ctx.clone()
+            });
+        }
+        // Functions named by item-level macros, e.g. `criterion_group!(benches, bench)`.
+        "macro_invocation" if !ctx.in_body => {
+            let mut cursor = node.walk();
+            for tree in node.children(&mut cursor).filter(|c| c.kind() == "token_tree") {
+                collect_macro_calls(&tree, source, &mut items.item_references);
+            }
+        }
+        // The expansion of a `macro_rules!` may call anything its body names.
+        "macro_definition" => {
+            if let Some(name) = extract_name(node, source) {
+                items.symbols.push(symbol(node, source, ctx, SymbolKind::Macro, name));
+            }
+            let mut cursor = node.walk();
+            for rule in node.children(&mut cursor).filter(|c| c.kind() == "macro_rule") {
+                if let Some(body) = rule.child_by_field_name("right") {
+                    collect_macro_calls(&body, source, &mut items.item_references);
+                }
             }
         }
         _ => {}
     }
 
+    let ctx = inner.as_ref().unwrap_or(ctx);
     let mut cursor = node.walk();
     for child in node.children(&mut cursor) {
-        visit_node(&child, source, functions, imports, structs);
+        visit_node(&child, source, items, ctx);
     }
 }
 
-fn extract_function(node: &Node, source: &str) -> Option<ParsedFunction> {
+/// An item at `node`. Items directly in an `impl` or trait body are
+/// associated with it; anything else, e.g. a nested impl, stands alone.
+fn symbol(node: &Node, source: &str, ctx: &Enclosing, kind: SymbolKind, name: String) -> ParsedSymbol {
+    let associated = node.parent().is_some_and(|p| p.kind() == "declaration_list");
+    ParsedSymbol {
+        kind,
+        name,
+        line_start: node.start_position().row + 1,
+        line_end: node.end_position().row + 1,
+        visibility: child_text(node, "visibility_modifier", source),
+        scope: ctx.scope.clone(),
+        self_type: ctx.owner.clone().filter(|_| associated),
+        trait_name: ctx.trait_name.clone().filter(|_| associated),
+        documented: has_doc(node, source),
+    }
+}
+
+/// `Foo` for `Foo`, `Foo<T>`, `&mut Foo` or `crate::a::Foo<T>`.
+fn type_name(node: &Node, source: &str) -> String {
+    let text = node.utf8_text(source.as_bytes()).unwrap_or_default();
+    let text = text.split('<').next().unwrap_or(text);
+    let text = text.rsplit("::").next().unwrap_or(text);
+    text.trim_start_matches(['&', ' ']).trim_start_matches("mut ").trim().to_owned()
+}
+
+fn extract_function(node: &Node, source: &str, ctx: &Enclosing) -> Option<ParsedFunction> {
     let name = node
         .child_by_field_name("name")
         .and_then(|n| n.utf8_text(source.as_bytes()).ok())
@@ -112,27 +378,17 @@ fn extract_function(node: &Node, source: &str) -> Option<ParsedFunction> {
     let line_start = node.start_position().row + 1;
     let line_end = node.end_position().row + 1;
 
-    // Check for `pub` visibility
-    let is_public = node
-        .child_by_field_name("visibility_modifier")
-        .map(|n| {
-            n.utf8_text(source.as_bytes())
-                .unwrap_or("")
-                .starts_with("pub")
-        })
-        .unwrap_or(false);
+    let visibility = child_text(node, "visibility_modifier", source);
+    let is_public = visibility.as_deref().is_some_and(|v| v.starts_with("pub"));
 
-    // Check for async keyword
-    let is_async = {
-        let mut cursor = node.walk();
-        node.children(&mut cursor)
-            .any(|c| c.kind() == "async")
-    };
+    // `async` sits among the other qualifiers, e.g. `pub async unsafe fn`.
+    let is_async = child_text(node, "function_modifiers", source)
+        .is_some_and(|m| m.split_whitespace().any(|q| q == "async"));
 
-    let body_source = node
-        .utf8_text(source.as_bytes())
-        .unwrap_or("")
-        .to_owned();
+    let mut calls = Vec::new();
+    if let Some(body) = node.child_by_field_name("body") {
+        collect_calls(&body, source, &mut calls);
+    }
 
     Some(ParsedFunction {
         name,
@@ -140,14 +396,402 @@ fn extract_function(node: &Node, source: &str) -> Option<ParsedFunction> {
         line_end,
         is_public,
         is_async,
-        body_source,
+        visibility,
+        attributes: outer_attributes(node)
+            .filter_map(|attr| attr.named_child(0)?.named_child(0))
+            .map(|path| path_segments(&path, source).join("::"))
+            .collect(),
+        documented: has_doc(node, source),
+        owner: ctx.owner.clone(),
+        trait_name: ctx.trait_name.clone(),
+        scope: ctx.scope.clone(),
+        parent: ctx.function,
+        module_path: String::new(),
+        qualified_name: String::new(),
+        shape: function_shape(node, source),
+        cyclomatic: complexity::cyclomatic(node),
+        cognitive: complexity::cognitive(node),
+        halstead: complexity::halstead(node, source),
+        calls,
+        unreachable: None,
     })
 }
 
-fn extract_use(node: &Node, source: &str) -> Option<String> {
-    node.utf8_text(source.as_bytes())
-        .ok()
-        .map(|s| s.trim().trim_end_matches(';').to_owned())
+/// Expressions whose direct `identifier`/`scoped_identifier` operands may
+/// name a function passed around as a value, e.g. `.map(parse)` or
+/// `get(handler)`.
+const REFERENCE_PARENTS: &[&str] = &[
+    "arguments",
+    "array_expression",
+    "tuple_expression",
+    "field_initializer",
+    "let_declaration",
+    "assignment_expression",
+    "return_expression",
+];
+
+/// Records the call sites in a function body, along with paths used as
+/// values and the calls inside macro arguments. tree-sitter-rust has no
+/// separate method call node: `a.b()` is a `call_expression` whose function
+/// is a `field_expression`. Nested functions are visited on their own.
+fn collect_calls(node: &Node, source: &str, out: &mut Vec<CallSite>) {
+    match node.kind() {
+        "function_item" => return,
+        "call_expression" => {
+            if let Some(function) = node.child_by_field_name("function") {
+                if let Some(call) = call_site(&function, source, node.start_position().row + 1) {
+                    out.push(call);
+                }
+            }
+        }
+        "macro_invocation" => {
+            let mut cursor = node.walk();
+            for tree in node.children(&mut cursor).filter(|c| c.kind() == "token_tree") {
+                collect_macro_calls(&tree, source, out);
+            }
+            return;
+        }
+        "identifier" | "scoped_identifier"
+            if node.parent().is_some_and(|p| REFERENCE_PARENTS.contains(&p.kind())) =>
+        {
+            out.push(CallSite {
+                path: path_segments(node, source),
+                is_method: false,
+                on_self: false,
+                is_reference: true,
+                line: node.start_position().row + 1,
+                target: None,
+            });
+            return;
+        }
+        _ => {}
+    }
+
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        collect_calls(&child, source, out);
+    }
+}
+
+fn call_site(function: &Node, source: &str, line: usize) -> Option<CallSite> {
+    match function.kind() {
+        "identifier" | "scoped_identifier" => Some(CallSite {
+            path: path_segments(function, source),
+            is_method: false,
+            on_self: false,
+            is_reference: false,
+            line,
+            target: None,
+        }),
+        // `foo::<T>()`
+        "generic_function" => call_site(&function.child_by_field_name("function")?, source, line),
+        "field_expression" => Some(CallSite {
+            path: path_segments(&function.child_by_field_name("field")?, source),
+            is_method: true,
+            on_self: function
+                .child_by_field_name("value")
+                .is_some_and(|v| v.kind() == "self"),
+            is_reference: false,
+            line,
+            target: None,
+        }),
+        _ => None,
+    }
+}
+
+/// Macro arguments are a flat token stream, so calls are recognised by shape:
+/// a path followed by a parenthesised group is a call, `.name(..)` a method
+/// call, and any other path a reference.
+fn collect_macro_calls(tree: &Node, source: &str, out: &mut Vec<CallSite>) {
+    let mut cursor = tree.walk();
+    let tokens: Vec<Node> = tree.children(&mut cursor).collect();
+    let text = |i: usize| tokens.get(i).and_then(|t| t.utf8_text(source.as_bytes()).ok()).unwrap_or_default();
+    let is_segment = |i: usize| {
+        tokens.get(i).is_some_and(|t| matches!(t.kind(), "identifier" | "self" | "super" | "crate"))
+            || text(i) == "$crate"
+    };
+
+    let mut i = 0;
+    while i < tokens.len() {
+        if tokens[i].kind() == "token_tree" {
+            collect_macro_calls(&tokens[i], source, out);
+            i += 1;
+            continue;
+        }
+        if !is_segment(i) {
+            i += 1;
+            continue;
+        }
+
+        let start = i;
+        let mut path = vec![text(i).to_owned()];
+        while text(i + 1) == "::" && is_segment(i + 2) {
+            path.push(text(i + 2).to_owned());
+            i += 2;
+        }
+        i += 1;
+
+        let is_call = tokens.get(i).is_some_and(|t| t.kind() == "token_tree") && text(i).starts_with('(');
+        let after_dot = start > 0 && text(start - 1) == ".";
+        // Field accesses, field names, nested macro names and lone keywords.
+        if (after_dot && !is_call)
+            || matches!(text(i), ":" | "!")
+            || (path.len() == 1 && tokens[start].kind() != "identifier")
+        {
+            continue;
+        }
+        out.push(CallSite {
+            path,
+            is_method: after_dot,
+            on_self: after_dot && start > 1 && tokens[start - 2].kind() == "self",
+            is_reference: !is_call,
+            line: tokens[start].start_position().row + 1,
+            target: None,
+        });
+    }
+}
+
+fn function_shape(node: &Node, source: &str) -> FunctionShape {
+    // Attributes on parameters are not parameters.
+    let count_named = |field: &str| {
+        node.child_by_field_name(field).map_or(0, |list| {
+            let mut cursor = list.walk();
+            list.named_children(&mut cursor).filter(|c| c.kind() != "attribute_item").count()
+        })
+    };
+    let mut shape = FunctionShape {
+        params: count_named("parameters"),
+        generic_params: count_named("type_parameters"),
+        ..FunctionShape::default()
+    };
+
+    let mut lines = HashSet::new();
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        measure(&child, source, 0, false, &mut lines, &mut shape);
+    }
+    shape.logical_lines = lines.len();
+    shape
+}
+
+fn measure(
+    node: &Node,
+    source: &str,
+    depth: usize,
+    in_closure: bool,
+    lines: &mut HashSet<usize>,
+    shape: &mut FunctionShape,
+) {
+    let kind = node.kind();
+    if kind == "function_item" || node.is_extra() || kind.ends_with("comment") {
+        return;
+    }
+    if node.child_count() == 0 {
+        lines.extend(node.start_position().row..=node.end_position().row);
+        return;
+    }
+
+    let nests = match kind {
+        // `else if` stays at the level of the first `if`.
+        "if_expression" => node.parent().is_none_or(|p| p.kind() != "else_clause"),
+        "match_expression" | "for_expression" | "while_expression" | "loop_expression"
+        | "closure_expression" => true,
+        _ => false,
+    };
+    let depth = depth + usize::from(nests);
+    shape.max_nesting = shape.max_nesting.max(depth);
+
+    match kind {
+        "return_expression" if !in_closure => shape.returns += 1,
+        "unsafe_block" => shape.unsafe_blocks += 1,
+        "call_expression" => {
+            let method = node
+                .child_by_field_name("function")
+                .filter(|f| f.kind() == "field_expression")
+                .and_then(|f| f.child_by_field_name("field"))
+                .and_then(|f| f.utf8_text(source.as_bytes()).ok());
+            if matches!(method, Some("unwrap" | "expect")) {
+                shape.unwraps += 1;
+            }
+        }
+        "macro_invocation" => {
+            let mut cursor = node.walk();
+            for tree in node.children(&mut cursor).filter(|c| c.kind() == "token_tree") {
+                shape.unwraps += macro_unwraps(&tree, source);
+            }
+        }
+        _ => {}
+    }
+
+    let in_closure = in_closure || kind == "closure_expression";
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        measure(&child, source, depth, in_closure, lines, shape);
+    }
+}
+
+/// `.unwrap()` and `.expect(..)` among a macro's tokens, e.g. in
+/// `println!("{}", x.unwrap())`.
+fn macro_unwraps(tree: &Node, source: &str) -> usize {
+    let mut cursor = tree.walk();
+    let tokens: Vec<Node> = tree.children(&mut cursor).collect();
+    tokens
+        .iter()
+        .enumerate()
+        .map(|(i, token)| {
+            if token.kind() == "token_tree" {
+                return macro_unwraps(token, source);
+            }
+            let after_dot = i > 0 && tokens[i - 1].kind() == ".";
+            let called = tokens.get(i + 1).is_some_and(|t| t.kind() == "token_tree");
+            let name = token.utf8_text(source.as_bytes()).unwrap_or_default();
+            usize::from(after_dot && called && matches!(name, "unwrap" | "expect"))
+        })
+        .sum()
+}
+
+/// Expands a `use` declaration into one import per leaf of its tree.
+fn extract_use(node: &Node, source: &str, scope: &[String]) -> Vec<ParsedImport> {
+    let visibility = child_text(node, "visibility_modifier", source);
+
+    let mut leaves = Vec::new();
+    if let Some(argument) = node.child_by_field_name("argument") {
+        collect_use_leaves(&argument, source, &[], &mut leaves);
+    }
+
+    leaves
+        .into_iter()
+        .map(|leaf| ParsedImport {
+            path: leaf.path,
+            alias: leaf.alias,
+            is_glob: leaf.is_glob,
+            visibility: visibility.clone(),
+            scope: scope.to_vec(),
+            line: node.start_position().row + 1,
+            target: None,
+        })
+        .collect()
+}
+
+struct UseLeaf {
+    path: Vec<String>,
+    alias: Option<String>,
+    is_glob: bool,
+}
+
+/// Walks `scoped_use_list`, `use_list`, `use_as_clause` and `use_wildcard`
+/// nodes, collecting every leaf under `prefix`.
+fn collect_use_leaves(node: &Node, source: &str, prefix: &[String], out: &mut Vec<UseLeaf>) {
+    let with_prefix = |segments: Vec<String>| [prefix, segments.as_slice()].concat();
+    match node.kind() {
+        "use_as_clause" => {
+            if let Some(path) = node.child_by_field_name("path") {
+                out.push(UseLeaf {
+                    path: with_prefix(path_segments(&path, source)),
+                    alias: node
+                        .child_by_field_name("alias")
+                        .and_then(|a| a.utf8_text(source.as_bytes()).ok())
+                        .map(str::to_owned),
+                    is_glob: false,
+                });
+            }
+        }
+        "use_wildcard" => {
+            // `a::b::*` names module `a::b`; a bare `*` in a list names the prefix.
+            let segments = node
+                .named_child(0)
+                .map(|path| path_segments(&path, source))
+                .unwrap_or_default();
+            out.push(UseLeaf {
+                path: with_prefix(segments),
+                alias: None,
+                is_glob: true,
+            });
+        }
+        "scoped_use_list" => {
+            let prefix = node
+                .child_by_field_name("path")
+                .map(|path| with_prefix(path_segments(&path, source)))
+                .unwrap_or_else(|| prefix.to_vec());
+            if let Some(list) = node.child_by_field_name("list") {
+                collect_use_leaves(&list, source, &prefix, out);
+            }
+        }
+        "use_list" => {
+            let mut cursor = node.walk();
+            for item in node.named_children(&mut cursor).filter(|c| !c.is_extra()) {
+                collect_use_leaves(&item, source, prefix, out);
+            }
+        }
+        _ => out.push(UseLeaf {
+            path: with_prefix(path_segments(node, source)),
+            alias: None,
+            is_glob: false,
+        }),
+    }
+}
+
+/// `a::b::c` as `["a", "b", "c"]`; a leading `::` becomes an empty segment.
+fn path_segments(node: &Node, source: &str) -> Vec<String> {
+    if node.kind() != "scoped_identifier" {
+        return node
+            .utf8_text(source.as_bytes())
+            .map(|t| vec![t.to_owned()])
+            .unwrap_or_default();
+    }
+
+    let mut segments = match node.child_by_field_name("path") {
+        Some(path) => path_segments(&path, source),
+        None => vec![String::new()],
+    };
+    if let Some(name) = node.child_by_field_name("name") {
+        segments.extend(path_segments(&name, source));
+    }
+    segments
+}
+
+/// The `#[..]` items directly above `node`, nearest first. Comments between
+/// them are skipped.
+fn outer_attributes<'t>(node: &Node<'t>) -> impl Iterator<Item = Node<'t>> {
+    std::iter::successors(node.prev_named_sibling(), |n| n.prev_named_sibling())
+        .take_while(|n| n.kind() == "attribute_item" || n.is_extra())
+        .filter(|n| n.kind() == "attribute_item")
+}
+
+/// Whether an outer doc comment or `#[doc = ...]` sits directly above `node`,
+/// among its other attributes.
+fn has_doc(node: &Node, source: &str) -> bool {
+    std::iter::successors(node.prev_named_sibling(), |n| n.prev_named_sibling())
+        .take_while(|n| n.kind() == "attribute_item" || n.is_extra())
+        .any(|n| match n.kind() {
+            "attribute_item" => n
+                .utf8_text(source.as_bytes())
+                .ok()
+                .and_then(|text| text.trim_start_matches("#[").trim_start().strip_prefix("doc"))
+                .is_some_and(|rest| rest.trim_start().starts_with('=')),
+            _ => n.child_by_field_name("outer").is_some(),
+        })
+}
+
+/// Reads `#[path = "..."]` from the attributes directly above an item.
+fn path_attribute(node: &Node, source: &str) -> Option<String> {
+    outer_attributes(node).find_map(|attr| {
+        let text = attr.utf8_text(source.as_bytes()).ok()?;
+        let inner = text.trim_start_matches("#[").trim_end_matches(']').trim();
+        let value = inner.strip_prefix("path")?.trim_start().strip_prefix('=')?;
+        Some(value.trim().trim_matches('"').to_owned())
+    })
+}
+
+/// Text of the first child of `node` with the given kind.
+fn child_text(node: &Node, kind: &str, source: &str) -> Option<String> {
+    let mut cursor = node.walk();
+    let found = node
+        .children(&mut cursor)
+        .find(|c| c.kind() == kind)
+        .and_then(|c| c.utf8_text(source.as_bytes()).ok())
+        .map(str::to_owned);
+    found
 }
 
 fn extract_name(node: &Node, source: &str) -> Option<String> {
@@ -155,3 +799,63 @@ fn extract_name(node: &Node, source: &str) -> Option<String> {
         .and_then(|n| n.utf8_text(source.as_bytes()).ok())
         .map(str::to_owned)
 }
+
+/// Parses `(path, source)` pairs as the files of one project, for tests.
+#[cfg(test)]
+pub fn parse_sources(files: &[(&str, &str)]) -> Vec<ParsedFile> {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    static PROJECTS: AtomicUsize = AtomicUsize::new(0);
+
+    let project = PROJECTS.fetch_add(1, Ordering::Relaxed);
+    let root = std::env::temp_dir().join(format!("gravity-test-{}-{project}", std::process::id()));
+    let paths: Vec<PathBuf> = files
+        .iter()
+        .map(|(path, source)| {
+            let path = root.join(path);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(&path, source).unwrap();
+            path
+        })
+        .collect();
+    let parsed = parse_files(&root, &paths, |_, _| {}).unwrap();
+    std::fs::remove_dir_all(&root).unwrap();
+    parsed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reuses_files_with_the_same_hash_and_analyzer_version() {
+        let root = std::env::temp_dir().join(format!("gravity-reuse-{}", std::process::id()));
+        std::fs::create_dir_all(root.join("src")).unwrap();
+        let sources = [
+            ("src/same.rs", "fn same() {}\n"),
+            ("src/edited.rs", "fn edited() {}\n"),
+            ("src/older.rs", "fn older() {}\n"),
+            ("src/new.rs", "fn new() {}\n"),
+        ];
+        for (path, source) in sources {
+            std::fs::write(root.join(path), source).unwrap();
+        }
+
+        let previous: HashMap<String, StoredParse> = parse_sources(&sources[..3])
+            .into_iter()
+            .map(|file| {
+                let analyzer_version = if file.path == "src/older.rs" { "0.0.1" } else { "1.0.0" };
+                (file.path.clone(), StoredParse { analyzer_version: analyzer_version.to_owned(), run_id: None, file })
+            })
+            .collect();
+        std::fs::write(root.join("src/edited.rs"), "fn edited() { todo!() }\n").unwrap();
+
+        let paths = sources.iter().map(|(path, _)| root.join(path)).collect();
+        let (reused, changed) = reuse_unchanged(&root, paths, previous, "1.0.0");
+        std::fs::remove_dir_all(&root).unwrap();
+
+        let reused: Vec<&str> = reused.iter().map(|s| s.file.path.as_str()).collect();
+        assert_eq!(reused, ["src/same.rs"]);
+        let changed: Vec<String> = changed.iter().map(|p| stored_path(&root, p)).collect();
+        assert_eq!(changed, ["src/edited.rs", "src/older.rs", "src/new.rs"]);
+    }
+}
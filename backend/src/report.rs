@@ -0,0 +1,320 @@
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, HashMap};
+
+use serde::{Deserialize, Serialize};
+
+use crate::complexity::{self, ScoreMap};
+use crate::graph::DependencyGraph;
+use crate::graph;
+use crate::metrics;
+use crate::models::{
+    ComplexityItem, CrateDependency, CrateManifest, CrateSummary, DeadCodeItem, DependencyCycle,
+    GraphData, LayerViolation, LineCounts, ModuleMetrics, ParsedFile, RunSnapshot, SkippedFiles,
+    SymbolKind,
+};
+
+/// Project-level numbers shared by the dashboard summary and the CLI report.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReportSummary {
+    pub total_files: i64,
+    pub total_functions: i64,
+    pub total_structs: i64,
+    pub total_imports: i64,
+    #[serde(default)]
+    pub symbol_counts: BTreeMap<String, i64>,
+    pub avg_complexity: f64,
+    #[serde(default)]
+    pub comment_ratio: f64,
+    #[serde(default)]
+    pub doc_coverage: f64,
+    pub dead_code_count: i64,
+    #[serde(default)]
+    pub crates: Vec<CrateSummary>,
+    pub architecture_notes: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReportFile {
+    pub path: String,
+    pub module_name: Option<String>,
+    #[serde(default)]
+    pub crate_name: Option<String>,
+    #[serde(default)]
+    pub target: Option<String>,
+    pub line_count: usize,
+    #[serde(default)]
+    pub lines: LineCounts,
+    pub functions: usize,
+    #[serde(default)]
+    pub halstead_volume: f64,
+    #[serde(default)]
+    pub maintainability_index: f64,
+    /// Absent from reports written before files were hashed.
+    #[serde(default)]
+    pub content_hash: Option<String>,
+}
+
+/// The result of an in-memory analysis, as printed by `gravity analyze`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Report {
+    pub root: String,
+    pub summary: ReportSummary,
+    pub files: Vec<ReportFile>,
+    /// Files the walk left out; see `walk::rust_files`.
+    #[serde(default)]
+    pub skipped: SkippedFiles,
+    pub complexity: Vec<ComplexityItem>,
+    pub dead_code: Vec<DeadCodeItem>,
+    pub cycles: Vec<DependencyCycle>,
+    #[serde(default)]
+    pub modules: Vec<ModuleMetrics>,
+    #[serde(default)]
+    pub layer_violations: Vec<LayerViolation>,
+    pub graph: GraphData,
+    /// Dependencies between packages and on external crates, from the manifests.
+    #[serde(default)]
+    pub crate_dependencies: Vec<CrateDependency>,
+}
+
+impl Report {
+    pub fn build(
+        root: &str,
+        files: &[ParsedFile],
+        skipped: SkippedFiles,
+        crates: &[CrateManifest],
+        graph: &DependencyGraph,
+        complexity_scores: &ScoreMap,
+        layer_violations: Vec<LayerViolation>,
+    ) -> Self {
+        let mut complexity: Vec<ComplexityItem> = Vec::new();
+        for pf in files {
+            for func in &pf.functions {
+                let scores = complexity::scores_of(complexity_scores, pf, func);
+                complexity.push(ComplexityItem {
+                    function_name: func.name.clone(),
+                    qualified_name: func.qualified_name.clone(),
+                    file_path: pf.path.clone(),
+                    score: scores.cyclomatic as i32,
+                    cognitive: scores.cognitive as i32,
+                    halstead_volume: scores.halstead.volume(),
+                    halstead_difficulty: scores.halstead.difficulty(),
+                    halstead_effort: scores.halstead.effort(),
+                    maintainability_index: scores.maintainability_index,
+                    logical_lines: func.shape.logical_lines as i32,
+                    params: func.shape.params as i32,
+                    generic_params: func.shape.generic_params as i32,
+                    max_nesting: func.shape.max_nesting as i32,
+                    returns: func.shape.returns as i32,
+                    unsafe_blocks: func.shape.unsafe_blocks as i32,
+                    unwraps: func.shape.unwraps as i32,
+                    line_start: func.line_start as i32,
+                    line_end: func.line_end as i32,
+                });
+            }
+        }
+        complexity.sort_by_key(|c| Reverse(c.score));
+
+        let total_files = files.len() as i64;
+        let total_imports = files.iter().map(|f| f.imports.len()).sum::<usize>() as i64;
+        let avg_complexity = if complexity.is_empty() {
+            1.0
+        } else {
+            complexity.iter().map(|c| c.score as f64).sum::<f64>() / complexity.len() as f64
+        };
+
+        let dead_code = dead_code(files);
+        let cycles = graph.cycles();
+        let modules = metrics::module_metrics(graph, &type_counts(files));
+        let lines: LineCounts = files.iter().map(|f| f.lines).sum();
+        let (public_items, documented_items) = files
+            .iter()
+            .map(ParsedFile::doc_items)
+            .fold((0, 0), |(p, d), (fp, fd)| (p + fp, d + fd));
+        let summary = ReportSummary {
+            total_files,
+            total_functions: complexity.len() as i64,
+            total_structs: files.iter().map(|f| f.count(SymbolKind::Struct)).sum::<usize>() as i64,
+            total_imports,
+            symbol_counts: symbol_counts(files),
+            avg_complexity,
+            comment_ratio: lines.comment_ratio(),
+            doc_coverage: doc_coverage(public_items, documented_items),
+            dead_code_count: dead_code.len() as i64,
+            crates: crate_summaries(crates, files, &complexity),
+            architecture_notes: architecture_notes(total_files, avg_complexity, &complexity, &modules, &cycles),
+        };
+
+        Report {
+            root: root.to_owned(),
+            summary,
+            files: files
+                .iter()
+                .map(|f| ReportFile {
+                    path: f.path.clone(),
+                    module_name: f.module_name.clone(),
+                    crate_name: f.crate_name.clone(),
+                    target: f.target.clone(),
+                    line_count: f.line_count,
+                    lines: f.lines,
+                    functions: f.functions.len(),
+                    halstead_volume: f.halstead.volume(),
+                    maintainability_index: complexity::file_maintainability_index(f, complexity_scores),
+                    content_hash: Some(f.content_hash.clone()),
+                })
+                .collect(),
+            skipped,
+            complexity,
+            dead_code,
+            cycles,
+            modules,
+            layer_violations,
+            graph: graph.to_graph_data(),
+            crate_dependencies: crates.iter().flat_map(|c| c.dependencies.clone()).collect(),
+        }
+    }
+
+    /// The report in the shape the run comparison and quality gates work on.
+    pub fn snapshot(&self) -> RunSnapshot {
+        RunSnapshot {
+            files: self
+                .files
+                .iter()
+                .map(|f| (f.path.clone(), f.line_count as i32, f.content_hash.clone()))
+                .collect(),
+            functions: self.complexity.clone(),
+            edges: self.graph.edges.clone(),
+            cycles: self.cycles.clone(),
+            dead_code: self.dead_code.clone(),
+        }
+    }
+}
+
+/// Functions marked unreachable by `callgraph::mark_unreachable`, ordered
+/// like `db::fetch_dead_code`.
+fn dead_code(files: &[ParsedFile]) -> Vec<DeadCodeItem> {
+    let mut items: Vec<DeadCodeItem> = files
+        .iter()
+        .flat_map(|pf| {
+            pf.functions.iter().filter_map(|func| {
+                Some(DeadCodeItem {
+                    function_name: func.name.clone(),
+                    qualified_name: func.qualified_name.clone(),
+                    file_path: pf.path.clone(),
+                    line_start: func.line_start as i32,
+                    line_end: func.line_end as i32,
+                    reason: func.unreachable.clone()?,
+                })
+            })
+        })
+        .collect();
+    items.sort_by(|a, b| (&a.file_path, a.line_start).cmp(&(&b.file_path, b.line_start)));
+    items
+}
+
+/// Per-package numbers, as `db::fetch_crate_summaries` computes them.
+fn crate_summaries(crates: &[CrateManifest], files: &[ParsedFile], complexity: &[ComplexityItem]) -> Vec<CrateSummary> {
+    let crate_of: HashMap<&str, &str> = files
+        .iter()
+        .filter_map(|f| Some((f.path.as_str(), f.crate_name.as_deref()?)))
+        .collect();
+    crates
+        .iter()
+        .map(|krate| {
+            let in_crate: Vec<&ParsedFile> =
+                files.iter().filter(|f| f.crate_name.as_deref() == Some(&krate.name)).collect();
+            let scores: Vec<i32> = complexity
+                .iter()
+                .filter(|c| crate_of.get(c.file_path.as_str()) == Some(&krate.name.as_str()))
+                .map(|c| c.score)
+                .collect();
+            let (public_items, documented_items) = in_crate
+                .iter()
+                .map(|f| f.doc_items())
+                .fold((0, 0), |(p, d), (fp, fd)| (p + fp, d + fd));
+            CrateSummary {
+                name: krate.name.clone(),
+                path: krate.path.clone(),
+                version: krate.version.clone(),
+                targets: krate.targets.iter().map(|t| t.label()).collect(),
+                files: in_crate.len() as i64,
+                functions: scores.len() as i64,
+                lines: in_crate.iter().map(|f| f.line_count).sum::<usize>() as i64,
+                avg_complexity: if scores.is_empty() {
+                    1.0
+                } else {
+                    scores.iter().map(|&s| s as f64).sum::<f64>() / scores.len() as f64
+                },
+                doc_coverage: doc_coverage(public_items, documented_items),
+                dead_code: in_crate
+                    .iter()
+                    .flat_map(|f| &f.functions)
+                    .filter(|func| func.unreachable.is_some())
+                    .count() as i64,
+            }
+        })
+        .collect()
+}
+
+/// Documented public items over all public items; 1 when nothing is public.
+pub fn doc_coverage(public_items: usize, documented_items: usize) -> f64 {
+    if public_items == 0 {
+        return 1.0;
+    }
+    documented_items as f64 / public_items as f64
+}
+
+/// Like `db::fetch_symbol_counts`.
+fn symbol_counts(files: &[ParsedFile]) -> BTreeMap<String, i64> {
+    let mut counts = BTreeMap::new();
+    for symbol in files.iter().flat_map(|f| &f.symbols) {
+        *counts.entry(symbol.kind.as_str().to_owned()).or_insert(0) += 1;
+    }
+    counts
+}
+
+/// (path, abstract types, concrete types) per file, as stored by
+/// `db::save_analysis`.
+pub fn type_counts(files: &[ParsedFile]) -> Vec<(String, i32, i32)> {
+    files
+        .iter()
+        .map(|f| {
+            let concrete = f.count(SymbolKind::Struct) + f.count(SymbolKind::Enum);
+            (f.path.clone(), f.count(SymbolKind::Trait) as i32, concrete as i32)
+        })
+        .collect()
+}
+
+/// Cycles spelled out in the notes; the rest are only counted.
+const NOTED_CYCLES: usize = 5;
+
+pub fn architecture_notes(
+    total_files: i64,
+    avg_complexity: f64,
+    functions: &[ComplexityItem],
+    modules: &[ModuleMetrics],
+    cycles: &[DependencyCycle],
+) -> Vec<String> {
+    let mut notes = Vec::new();
+    if total_files > 20 {
+        notes.push("Large project: consider splitting into smaller crates.".into());
+    }
+    if avg_complexity > 10.0 {
+        notes.push("High average cyclomatic complexity: refactoring recommended.".into());
+    }
+    notes.extend(metrics::function_notes(functions));
+    notes.extend(metrics::coupling_notes(modules));
+    for cycle in cycles.iter().take(NOTED_CYCLES) {
+        let closing = match cycle.imports.last() {
+            Some(i) => format!(" (closed by `use {}` at {}:{})", i.path, i.from, i.line),
+            None => String::new(),
+        };
+        notes.push(format!("Dependency cycle: {}{closing}.", graph::describe_cycle(cycle)));
+    }
+    if cycles.len() > NOTED_CYCLES {
+        notes.push(format!("…and {} more dependency cycles.", cycles.len() - NOTED_CYCLES));
+    }
+    if notes.is_empty() {
+        notes.push("Project structure looks healthy.".into());
+    }
+    notes
+}
@@ -0,0 +1,430 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::callgraph;
+use crate::crates;
+use crate::models::{CrateManifest, ImportTarget, ParsedFile, TargetKind};
+
+/// Maps module paths to the files that define them, following `mod`
+/// declarations from each crate root the way rustc does.
+pub struct ModuleTree {
+    /// (crate root, module path) → file defining that module.
+    modules: HashMap<(String, Vec<String>), String>,
+    /// file → (crate root, module path of the file).
+    files: HashMap<String, (String, Vec<String>)>,
+    /// Crate roots of library targets.
+    libraries: HashSet<String>,
+}
+
+impl ModuleTree {
+    /// Crate roots are the targets `crates` declare or Cargo would discover,
+    /// and files that look like one by name, for trees without manifests.
+    pub fn build(parsed: &[ParsedFile], crates: &[CrateManifest]) -> Self {
+        let by_path: HashMap<&str, &ParsedFile> =
+            parsed.iter().map(|f| (f.path.as_str(), f)).collect();
+        let targets: HashMap<&str, TargetKind> = crates
+            .iter()
+            .flat_map(|c| &c.targets)
+            .map(|t| (t.root.as_str(), t.kind))
+            .collect();
+        let mut tree = ModuleTree {
+            modules: HashMap::new(),
+            files: HashMap::new(),
+            libraries: HashSet::new(),
+        };
+
+        let mut roots: Vec<&str> = by_path
+            .keys()
+            .copied()
+            .filter(|p| is_crate_root(p) || targets.contains_key(p))
+            .collect();
+        // A file reachable from several roots, like one a bench pulls in with
+        // `#[path]`, stays with the first: libraries, then binaries.
+        let rank = |root: &str| match targets.get(root) {
+            Some(TargetKind::Lib) => 0,
+            Some(TargetKind::Bin) => 1,
+            Some(_) => 2,
+            None if root.ends_with("lib.rs") => 0,
+            None if root.ends_with("main.rs") => 1,
+            None => 2,
+        };
+        roots.sort_by_key(|root| (rank(root), *root));
+        tree.libraries = roots
+            .iter()
+            .filter(|r| match targets.get(*r) {
+                Some(kind) => *kind == TargetKind::Lib,
+                None => r.ends_with("lib.rs"),
+            })
+            .map(|r| (*r).to_owned())
+            .collect();
+        for root in &roots {
+            tree.visit(&by_path, root, root, Vec::new(), parent_dir(root));
+        }
+
+        // Files no root declares still get a module path from their layout,
+        // so their own imports resolve.
+        let mut orphans: Vec<&str> =
+            by_path.keys().copied().filter(|p| !tree.files.contains_key(*p)).collect();
+        orphans.sort();
+        for path in orphans {
+            if tree.files.contains_key(path) {
+                continue;
+            }
+            let (krate, module) = layout_module(path, &roots);
+            let dir = module_dir(path, false);
+            tree.visit(&by_path, path, &krate, module, dir);
+        }
+
+        tree
+    }
+
+    fn visit(
+        &mut self,
+        by_path: &HashMap<&str, &ParsedFile>,
+        file: &str,
+        krate: &str,
+        module: Vec<String>,
+        dir: String,
+    ) {
+        if self.files.contains_key(file) {
+            return;
+        }
+        let Some(parsed) = by_path.get(file) else { return };
+        self.files.insert(file.to_owned(), (krate.to_owned(), module.clone()));
+        self.modules
+            .entry((krate.to_owned(), module.clone()))
+            .or_insert_with(|| file.to_owned());
+
+        for m in &parsed.mods {
+            let child: Vec<String> = [module.as_slice(), &m.scope, std::slice::from_ref(&m.name)].concat();
+            if m.inline {
+                self.modules.entry((krate.to_owned(), child)).or_insert_with(|| file.to_owned());
+                continue;
+            }
+
+            let scope_dir = join(&dir, &m.scope.join("/"));
+            let (target, mod_rs) = match &m.path_attr {
+                Some(attr) => {
+                    // Outside inline blocks `#[path]` is relative to the declaring file.
+                    let base = if m.scope.is_empty() { parent_dir(file) } else { scope_dir };
+                    (Some(join(&base, attr)), true)
+                }
+                None => {
+                    let flat = join(&scope_dir, &format!("{}.rs", m.name));
+                    let nested = join(&scope_dir, &format!("{}/mod.rs", m.name));
+                    if by_path.contains_key(flat.as_str()) {
+                        (Some(flat), false)
+                    } else if by_path.contains_key(nested.as_str()) {
+                        (Some(nested), true)
+                    } else {
+                        (None, false)
+                    }
+                }
+            };
+
+            if let Some(target) = target.filter(|t| by_path.contains_key(t.as_str())) {
+                let child_dir = module_dir(&target, mod_rs);
+                self.visit(by_path, &target, krate, child, child_dir);
+            }
+        }
+    }
+
+    /// Resolves one `use` path written in `file`, inside the inline modules
+    /// `scope`. Paths into the crate resolve to the deepest module that exists.
+    pub fn resolve(&self, file: &str, scope: &[String], path: &[String]) -> Option<ImportTarget> {
+        let (krate, file_module) = self.files.get(file)?;
+        let current: Vec<String> = [file_module.as_slice(), scope].concat();
+
+        let (first, rest) = path.split_first()?;
+        let (mut module, rest) = match first.as_str() {
+            "crate" | "$crate" => (Vec::new(), rest),
+            "self" => (current, rest),
+            "super" => {
+                let mut module = current;
+                module.pop()?;
+                let mut rest = rest;
+                while rest.first().is_some_and(|s| s == "super") {
+                    module.pop()?;
+                    rest = &rest[1..];
+                }
+                (module, rest)
+            }
+            // `::name` always means an external crate.
+            "" => return rest.first().map(|name| ImportTarget::Extern(name.clone())),
+            name => {
+                let child = [current.as_slice(), std::slice::from_ref(first)].concat();
+                if self.modules.contains_key(&(krate.clone(), child)) {
+                    (current, path)
+                } else {
+                    return Some(ImportTarget::Extern(name.to_owned()));
+                }
+            }
+        };
+
+        for segment in rest {
+            if segment == "self" {
+                break;
+            }
+            module.push(segment.clone());
+            if !self.modules.contains_key(&(krate.clone(), module.clone())) {
+                module.pop();
+                break;
+            }
+        }
+
+        self.modules
+            .get(&(krate.clone(), module))
+            .map(|file| ImportTarget::File(file.clone()))
+    }
+
+    /// Root file of the crate `file` belongs to.
+    pub fn crate_root(&self, file: &str) -> Option<&str> {
+        self.files.get(file).map(|(krate, _)| krate.as_str())
+    }
+
+    /// Whether the crate rooted at `root` is a library.
+    pub fn is_library(&self, root: &str) -> bool {
+        self.libraries.contains(root)
+    }
+
+    /// `crate::a::b` for the module `file` defines.
+    pub fn module_path(&self, file: &str) -> Option<String> {
+        let (_, module) = self.files.get(file)?;
+        Some(std::iter::once("crate").chain(module.iter().map(String::as_str)).collect::<Vec<_>>().join("::"))
+    }
+
+    /// Sets `target` on every import. Imports that land back in the importing
+    /// file, such as `use super::*` in an inline test module, become `Local`.
+    pub fn resolve_imports(&self, parsed: &mut [ParsedFile]) {
+        for pf in parsed.iter_mut() {
+            for import in &mut pf.imports {
+                import.target = match self.resolve(&pf.path, &import.scope, &import.path) {
+                    Some(ImportTarget::File(target)) if target == pf.path => Some(ImportTarget::Local),
+                    target => target,
+                };
+            }
+        }
+    }
+}
+
+/// Resolves what needs every file parsed first: imports, module paths and
+/// crates, then call sites, then which functions are reachable from an entry
+/// point.
+pub fn link(parsed: &mut [ParsedFile], crates: &[CrateManifest]) {
+    let tree = ModuleTree::build(parsed, crates);
+    tree.resolve_imports(parsed);
+    crates::attribute(crates, &tree, parsed);
+    for pf in parsed.iter_mut() {
+        pf.module_path = tree.module_path(&pf.path);
+        qualify_functions(pf);
+    }
+    callgraph::resolve_calls(&tree, parsed);
+    callgraph::mark_unreachable(&tree, parsed);
+}
+
+/// Fills in `module_path` and `qualified_name` of every function in `pf`.
+/// Functions nested in another function's body are qualified by it, so
+/// `helper` inside `Foo::run` becomes `crate::a::Foo::run::helper`.
+fn qualify_functions(pf: &mut ParsedFile) {
+    let file_module = pf.module_path.clone().unwrap_or_else(|| "crate".to_owned());
+    for i in 0..pf.functions.len() {
+        let func = &pf.functions[i];
+        let module_path = std::iter::once(file_module.as_str())
+            .chain(func.scope.iter().map(String::as_str))
+            .collect::<Vec<_>>()
+            .join("::");
+        // Parents come before the functions nested in them.
+        let base = match func.parent {
+            Some(parent) => pf.functions[parent].qualified_name.clone(),
+            None => module_path.clone(),
+        };
+        let qualified_name = match (&func.owner, &func.trait_name) {
+            (Some(owner), Some(trait_name)) if owner != trait_name => {
+                format!("<{base}::{owner} as {trait_name}>::{}", func.name)
+            }
+            (Some(owner), _) => format!("{base}::{owner}::{}", func.name),
+            (None, _) => format!("{base}::{}", func.name),
+        };
+        let func = &mut pf.functions[i];
+        func.module_path = module_path;
+        func.qualified_name = qualified_name;
+    }
+}
+
+fn is_crate_root(path: &str) -> bool {
+    let mut parts = path.rsplit('/');
+    let name = parts.next().unwrap_or_default();
+    let dir = parts.next();
+    let grandparent = parts.next();
+
+    matches!(name, "main.rs" | "lib.rs" | "build.rs")
+        || (dir == Some("bin") && grandparent == Some("src"))
+        || (matches!(dir, Some("tests" | "examples" | "benches")) && grandparent != Some("src"))
+}
+
+/// Module path of a file that no `mod` declaration reaches, inferred from its
+/// position under the nearest `src/` and attributed to the crate rooted there.
+fn layout_module(path: &str, roots: &[&str]) -> (String, Vec<String>) {
+    let parts: Vec<&str> = path.split('/').collect();
+    let src = parts.iter().rposition(|p| *p == "src");
+    let (src_dir, rel) = match src {
+        Some(i) => (parts[..=i].join("/"), &parts[i + 1..]),
+        None => (String::new(), &parts[..]),
+    };
+
+    // Prefer the library when a directory holds both roots.
+    let krate = ["lib.rs", "main.rs"]
+        .iter()
+        .map(|name| join(&src_dir, name))
+        .find(|root| roots.contains(&root.as_str()))
+        .unwrap_or_else(|| path.to_owned());
+
+    let mut module: Vec<String> = rel.iter().map(|p| p.trim_end_matches(".rs").to_owned()).collect();
+    if module.last().is_some_and(|m| m == "mod") {
+        module.pop();
+    }
+    (krate, module)
+}
+
+/// Directory holding the child modules of `file`: its own directory for
+/// crate roots and `mod.rs`, otherwise a directory named after the file.
+fn module_dir(file: &str, mod_rs: bool) -> String {
+    if mod_rs || is_crate_root(file) || file.ends_with("/mod.rs") {
+        parent_dir(file)
+    } else {
+        file.trim_end_matches(".rs").to_owned()
+    }
+}
+
+fn parent_dir(path: &str) -> String {
+    path.rsplit_once('/').map(|(dir, _)| dir.to_owned()).unwrap_or_default()
+}
+
+/// Joins two relative paths, folding `.` and `..` segments.
+pub fn join(base: &str, rel: &str) -> String {
+    let mut parts: Vec<&str> = base.split('/').filter(|p| !p.is_empty()).collect();
+    for part in rel.split('/') {
+        match part {
+            "" | "." => {}
+            ".." => {
+                parts.pop();
+            }
+            p => parts.push(p),
+        }
+    }
+    parts.join("/")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser;
+
+    fn tree(files: &[(&str, &str)]) -> ModuleTree {
+        ModuleTree::build(&parser::parse_sources(files), &[])
+    }
+
+    fn path(p: &str) -> Vec<String> {
+        p.split("::").map(str::to_owned).collect()
+    }
+
+    fn file(target: &str) -> Option<ImportTarget> {
+        Some(ImportTarget::File(target.to_owned()))
+    }
+
+    #[test]
+    fn follows_mod_declarations_into_flat_and_mod_rs_files() {
+        let tree = tree(&[
+            ("src/main.rs", "mod flat; mod nested;"),
+            ("src/flat.rs", "mod child;"),
+            ("src/flat/child.rs", ""),
+            ("src/nested/mod.rs", "mod inner;"),
+            ("src/nested/inner.rs", ""),
+        ]);
+        assert_eq!(tree.module_path("src/main.rs").as_deref(), Some("crate"));
+        assert_eq!(tree.module_path("src/flat.rs").as_deref(), Some("crate::flat"));
+        assert_eq!(tree.module_path("src/flat/child.rs").as_deref(), Some("crate::flat::child"));
+        assert_eq!(tree.module_path("src/nested/mod.rs").as_deref(), Some("crate::nested"));
+        assert_eq!(tree.module_path("src/nested/inner.rs").as_deref(), Some("crate::nested::inner"));
+        assert_eq!(tree.crate_root("src/nested/inner.rs"), Some("src/main.rs"));
+    }
+
+    #[test]
+    fn path_attributes_are_relative_to_the_declaring_file() {
+        let tree = tree(&[
+            ("src/main.rs", r#"#[path = "platform/unix.rs"] mod sys;"#),
+            ("src/platform/unix.rs", "mod helpers;"),
+            ("src/platform/helpers.rs", ""),
+        ]);
+        assert_eq!(tree.module_path("src/platform/unix.rs").as_deref(), Some("crate::sys"));
+        // A `#[path]` file owns its directory like a `mod.rs`.
+        assert_eq!(tree.module_path("src/platform/helpers.rs").as_deref(), Some("crate::sys::helpers"));
+    }
+
+    #[test]
+    fn orphan_files_get_a_module_path_from_their_layout() {
+        let tree = tree(&[
+            ("src/lib.rs", ""),
+            ("src/main.rs", ""),
+            ("src/stray/mod.rs", ""),
+            ("src/stray/deep.rs", ""),
+        ]);
+        assert_eq!(tree.module_path("src/stray/mod.rs").as_deref(), Some("crate::stray"));
+        assert_eq!(tree.module_path("src/stray/deep.rs").as_deref(), Some("crate::stray::deep"));
+        // The library is preferred when a directory holds both roots.
+        assert_eq!(tree.crate_root("src/stray/deep.rs"), Some("src/lib.rs"));
+        assert!(tree.is_library("src/lib.rs"));
+        assert!(!tree.is_library("src/main.rs"));
+    }
+
+    #[test]
+    fn resolves_crate_self_super_and_bare_paths() {
+        let tree = tree(&[
+            ("src/main.rs", "mod a;"),
+            ("src/a.rs", "mod b; mod tests { }"),
+            ("src/a/b.rs", ""),
+        ]);
+        let none: &[String] = &[];
+
+        assert_eq!(tree.resolve("src/main.rs", none, &path("crate::a::b::Item")), file("src/a/b.rs"));
+        assert_eq!(tree.resolve("src/a/b.rs", none, &path("crate::a::Item")), file("src/a.rs"));
+        assert_eq!(tree.resolve("src/a.rs", none, &path("self::b::Item")), file("src/a/b.rs"));
+        assert_eq!(tree.resolve("src/a/b.rs", none, &path("super::Item")), file("src/a.rs"));
+        assert_eq!(tree.resolve("src/a/b.rs", none, &path("super::super::a")), file("src/a.rs"));
+        // A bare name is a child module when one exists, else an external crate.
+        assert_eq!(tree.resolve("src/main.rs", none, &path("a::Item")), file("src/a.rs"));
+        assert_eq!(
+            tree.resolve("src/main.rs", none, &path("serde::Serialize")),
+            Some(ImportTarget::Extern("serde".into()))
+        );
+        assert_eq!(
+            tree.resolve("src/a.rs", none, &path("::b::Item")),
+            Some(ImportTarget::Extern("b".into()))
+        );
+        // `super` above the crate root does not resolve.
+        assert_eq!(tree.resolve("src/main.rs", none, &path("super::Item")), None);
+    }
+
+    #[test]
+    fn resolves_from_inside_inline_modules() {
+        let tree = tree(&[
+            ("src/main.rs", "mod a;"),
+            ("src/a.rs", "mod b; mod tests { }"),
+            ("src/a/b.rs", ""),
+        ]);
+        let scope = ["tests".to_owned()];
+        assert_eq!(tree.resolve("src/a.rs", &scope, &path("super::*")), file("src/a.rs"));
+        assert_eq!(tree.resolve("src/a.rs", &scope, &path("super::b::Item")), file("src/a/b.rs"));
+        assert_eq!(tree.resolve("src/a.rs", &scope, &path("self::Item")), file("src/a.rs"));
+    }
+
+    #[test]
+    fn imports_back_into_the_same_file_become_local() {
+        let mut parsed = parser::parse_sources(&[
+            ("src/main.rs", "mod a;"),
+            ("src/a.rs", "fn f() {}\nmod tests {\n    use super::*;\n    use crate::a::f;\n}"),
+        ]);
+        ModuleTree::build(&parsed, &[]).resolve_imports(&mut parsed);
+        let targets: Vec<_> = parsed[1].imports.iter().map(|i| i.target.clone()).collect();
+        assert_eq!(targets, vec![Some(ImportTarget::Local), Some(ImportTarget::Local)]);
+    }
+}
@@ -0,0 +1,264 @@
+use serde::Serialize;
+
+use crate::config::GateConfig;
+use crate::db::ANALYZER_VERSION;
+use crate::graph;
+use crate::models::{ComplexityItem, DeadCodeItem, ImportEdge, RunSnapshot};
+
+const SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+
+/// Complexity above which a function is reported when gravity.toml sets no
+/// `max_function_complexity`.
+const DEFAULT_MAX_COMPLEXITY: i32 = 10;
+
+const RULE_HIGH_COMPLEXITY: &str = "gravity/high-complexity";
+const RULE_DEAD_CODE: &str = "gravity/dead-code";
+const RULE_DEPENDENCY_CYCLE: &str = "gravity/dependency-cycle";
+
+// ─── SARIF 2.1.0 (the subset Gravity emits) ────────────────────────────────
+
+#[derive(Debug, Serialize)]
+pub struct SarifLog {
+    #[serde(rename = "$schema")]
+    pub schema: &'static str,
+    pub version: &'static str,
+    pub runs: Vec<SarifRun>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SarifRun {
+    pub tool: Tool,
+    pub results: Vec<SarifResult>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Tool {
+    pub driver: Driver,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Driver {
+    pub name: &'static str,
+    pub version: &'static str,
+    pub rules: Vec<Rule>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Rule {
+    pub id: &'static str,
+    pub name: &'static str,
+    pub short_description: Message,
+    pub default_configuration: RuleConfiguration,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RuleConfiguration {
+    pub level: &'static str,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SarifResult {
+    pub rule_id: &'static str,
+    pub rule_index: usize,
+    pub level: &'static str,
+    pub message: Message,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub locations: Vec<Location>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub related_locations: Vec<Location>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Message {
+    pub text: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Location {
+    pub physical_location: PhysicalLocation,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub logical_locations: Vec<LogicalLocation>,
+}
+
+/// The function a result is about, by its qualified name.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LogicalLocation {
+    pub name: String,
+    pub fully_qualified_name: String,
+    pub kind: &'static str,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PhysicalLocation {
+    pub artifact_location: ArtifactLocation,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub region: Option<Region>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ArtifactLocation {
+    pub uri: String,
+    pub uri_base_id: &'static str,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Region {
+    pub start_line: i32,
+    pub end_line: i32,
+}
+
+// ─── Export ────────────────────────────────────────────────────────────────
+
+/// Turns a run's findings into a SARIF log. Paths stay relative to the
+/// analysis root (`%SRCROOT%`). Thresholds come from the `[gates]` section.
+pub fn export(run: &RunSnapshot, gates: &GateConfig) -> SarifLog {
+    let max_complexity = gates.max_function_complexity.unwrap_or(DEFAULT_MAX_COMPLEXITY);
+    let cycle_level = if gates.forbid_cycles { "error" } else { "warning" };
+
+    let rules = vec![
+        rule(RULE_HIGH_COMPLEXITY, "HighComplexity", "Function exceeds the cyclomatic complexity limit", "warning"),
+        rule(RULE_DEAD_CODE, "DeadCode", "Function that no entry point of the project reaches", "note"),
+        rule(RULE_DEPENDENCY_CYCLE, "DependencyCycle", "Modules depend on each other in a cycle", cycle_level),
+    ];
+
+    let mut results = Vec::new();
+
+    let mut complex: Vec<&ComplexityItem> =
+        run.functions.iter().filter(|f| f.score > max_complexity).collect();
+    complex.sort_by_key(|f| (&f.file_path, f.line_start));
+    for f in complex {
+        results.push(SarifResult {
+            rule_id: RULE_HIGH_COMPLEXITY,
+            rule_index: 0,
+            // Twice the limit is no longer a judgement call.
+            level: if f.score > max_complexity * 2 { "error" } else { "warning" },
+            message: Message {
+                text: format!("`{}` has cyclomatic complexity {} (limit {max_complexity}).", f.qualified_name, f.score),
+            },
+            locations: vec![function_location(f)],
+            related_locations: Vec::new(),
+        });
+    }
+
+    for item in &run.dead_code {
+        results.push(SarifResult {
+            rule_id: RULE_DEAD_CODE,
+            rule_index: 1,
+            level: "note",
+            message: Message {
+                text: format!("`{}` is unreachable: {}.", item.qualified_name, item.reason),
+            },
+            locations: vec![dead_code_location(item)],
+            related_locations: Vec::new(),
+        });
+    }
+
+    // Reported at the `use` that leads back to the first file, with the
+    // other imports of the cycle as related locations.
+    for cycle in &run.cycles {
+        let mut locations: Vec<Location> = cycle.imports.iter().map(import_location).collect();
+        let primary = locations.pop();
+        results.push(SarifResult {
+            rule_id: RULE_DEPENDENCY_CYCLE,
+            rule_index: 2,
+            level: cycle_level,
+            message: Message {
+                text: format!("Dependency cycle: {}.", graph::describe_cycle(cycle)),
+            },
+            locations: primary.into_iter().collect(),
+            related_locations: locations,
+        });
+    }
+
+    SarifLog {
+        schema: SCHEMA,
+        version: "2.1.0",
+        runs: vec![SarifRun {
+            tool: Tool {
+                driver: Driver {
+                    name: "Gravity",
+                    version: ANALYZER_VERSION,
+                    rules,
+                },
+            },
+            results,
+        }],
+    }
+}
+
+fn rule(id: &'static str, name: &'static str, description: &str, level: &'static str) -> Rule {
+    Rule {
+        id,
+        name,
+        short_description: Message { text: description.into() },
+        default_configuration: RuleConfiguration { level },
+    }
+}
+
+fn function_location(f: &ComplexityItem) -> Location {
+    Location {
+        physical_location: PhysicalLocation {
+            region: Some(Region {
+                start_line: f.line_start,
+                end_line: f.line_end,
+            }),
+            ..file_location(&f.file_path).physical_location
+        },
+        logical_locations: function_logical_location(&f.function_name, &f.qualified_name),
+    }
+}
+
+fn dead_code_location(item: &DeadCodeItem) -> Location {
+    Location {
+        physical_location: PhysicalLocation {
+            region: Some(Region {
+                start_line: item.line_start,
+                end_line: item.line_end,
+            }),
+            ..file_location(&item.file_path).physical_location
+        },
+        logical_locations: function_logical_location(&item.function_name, &item.qualified_name),
+    }
+}
+
+fn function_logical_location(name: &str, qualified_name: &str) -> Vec<LogicalLocation> {
+    vec![LogicalLocation {
+        name: name.to_owned(),
+        fully_qualified_name: qualified_name.to_owned(),
+        kind: "function",
+    }]
+}
+
+fn import_location(import: &ImportEdge) -> Location {
+    Location {
+        physical_location: PhysicalLocation {
+            region: Some(Region {
+                start_line: import.line,
+                end_line: import.line,
+            }),
+            ..file_location(&import.from).physical_location
+        },
+        logical_locations: Vec::new(),
+    }
+}
+
+fn file_location(path: &str) -> Location {
+    Location {
+        physical_location: PhysicalLocation {
+            artifact_location: ArtifactLocation {
+                uri: path.to_owned(),
+                uri_base_id: "%SRCROOT%",
+            },
+            region: None,
+        },
+        logical_locations: Vec::new(),
+    }
+}
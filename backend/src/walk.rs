@@ -0,0 +1,157 @@
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use ignore::{DirEntry, WalkBuilder};
+
+use crate::config::FilesConfig;
+use crate::layers::glob_matches;
+use crate::models::SkippedFiles;
+
+/// Per-directory ignore file read on top of `.gitignore` and `.ignore`.
+pub const IGNORE_FILE: &str = ".gravityignore";
+
+/// Cargo manifest, collected alongside the sources.
+pub const MANIFEST: &str = "Cargo.toml";
+
+/// The `.rs` files an analysis covers, sorted by path.
+#[derive(Debug)]
+pub struct RustFiles {
+    pub paths: Vec<PathBuf>,
+    pub manifests: Vec<PathBuf>,
+    pub skipped: SkippedFiles,
+}
+
+/// Collects the `.rs` files under `root` that are not ignored, excluded or
+/// in a `target/` directory. Ignore files apply even outside a git checkout.
+/// The tree is walked a second time without them to count what they hide.
+/// `Cargo.toml` files are subject to the same rules except `include`, which
+/// is about sources, and are not counted as skipped.
+pub fn rust_files(root: &Path, files: &FilesConfig) -> RustFiles {
+    let (all, target_dirs) = walk(root, false, files.include_target);
+    let (visible, _) = walk(root, true, files.include_target);
+    let visible: HashSet<PathBuf> = visible.into_iter().collect();
+
+    let mut skipped = SkippedFiles {
+        target_files: target_dirs.iter().map(|dir| count_rust_files(dir)).sum(),
+        target_dirs: target_dirs.len(),
+        ..SkippedFiles::default()
+    };
+    let mut paths = Vec::new();
+    let mut manifests = Vec::new();
+    for path in all {
+        let relative = relative_path(root, &path);
+        let excluded = files.exclude.iter().any(|g| glob_matches(g, &relative, "/"));
+        if path.file_name().is_some_and(|n| n == MANIFEST) {
+            if visible.contains(&path) && !excluded {
+                manifests.push(path);
+            }
+        } else if !visible.contains(&path) {
+            skipped.ignored += 1;
+        } else if excluded {
+            skipped.excluded += 1;
+        } else if !files.include.is_empty() && !files.include.iter().any(|g| glob_matches(g, &relative, "/")) {
+            skipped.not_included += 1;
+        } else {
+            paths.push(path);
+        }
+    }
+    RustFiles { paths, manifests, skipped }
+}
+
+/// `.rs` and `Cargo.toml` files under `root`, and the `target/` directories
+/// pruned.
+/// Entries that cannot be read are passed over.
+fn walk(root: &Path, respect_ignore_files: bool, include_target: bool) -> (Vec<PathBuf>, Vec<PathBuf>) {
+    let target_dirs = Arc::new(Mutex::new(Vec::new()));
+    let pruned = Arc::clone(&target_dirs);
+
+    let mut builder = WalkBuilder::new(root);
+    builder
+        .standard_filters(false)
+        .follow_links(false)
+        .sort_by_file_name(|a, b| a.cmp(b))
+        .filter_entry(move |entry| {
+            if entry.depth() == 0 || !entry.file_type().is_some_and(|t| t.is_dir()) {
+                return true;
+            }
+            if entry.file_name() == ".git" {
+                return false;
+            }
+            if !include_target && is_target_dir(entry.path()) {
+                pruned.lock().unwrap().push(entry.path().to_path_buf());
+                return false;
+            }
+            true
+        });
+    if respect_ignore_files {
+        builder
+            .git_ignore(true)
+            .git_exclude(true)
+            .ignore(true)
+            .parents(true)
+            .require_git(false)
+            .add_custom_ignore_filename(IGNORE_FILE);
+    }
+
+    let paths = builder
+        .build()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_some_and(|t| t.is_file()))
+        .filter(|e| e.path().extension().is_some_and(|x| x == "rs") || e.file_name() == MANIFEST)
+        .map(DirEntry::into_path)
+        .collect();
+    let target_dirs = std::mem::take(&mut *target_dirs.lock().unwrap());
+    (paths, target_dirs)
+}
+
+/// `.rs` files anywhere under `dir`, whatever the ignore files say.
+fn count_rust_files(dir: &Path) -> usize {
+    WalkBuilder::new(dir)
+        .standard_filters(false)
+        .follow_links(false)
+        .build()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_some_and(|t| t.is_file()))
+        .filter(|e| e.path().extension().is_some_and(|x| x == "rs"))
+        .count()
+}
+
+/// A `target` directory Cargo made: tagged as a cache, or next to a manifest.
+/// A `src/target/` module directory is neither.
+pub fn is_target_dir(path: &Path) -> bool {
+    path.file_name().is_some_and(|n| n == "target")
+        && (path.join("CACHEDIR.TAG").is_file()
+            || path.join(".rustc_info.json").is_file()
+            || path.parent().is_some_and(|p| p.join("Cargo.toml").is_file()))
+}
+
+/// `path` relative to `root` with `/` separators, as globs are written.
+pub fn relative_path(root: &Path, path: &Path) -> String {
+    path.strip_prefix(root)
+        .unwrap_or(path)
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// `3 ignored, 1 excluded, 12 in 1 target/ directory`, or `None` when
+/// nothing was skipped.
+pub fn describe(skipped: &SkippedFiles) -> Option<String> {
+    let mut parts = Vec::new();
+    if skipped.ignored > 0 {
+        parts.push(format!("{} ignored", skipped.ignored));
+    }
+    if skipped.excluded > 0 {
+        parts.push(format!("{} excluded", skipped.excluded));
+    }
+    if skipped.not_included > 0 {
+        parts.push(format!("{} not included", skipped.not_included));
+    }
+    if skipped.target_files > 0 {
+        let plural = if skipped.target_dirs == 1 { "directory" } else { "directories" };
+        parts.push(format!("{} in {} target/ {plural}", skipped.target_files, skipped.target_dirs));
+    }
+    (!parts.is_empty()).then(|| parts.join(", "))
+}
@@ -0,0 +1,176 @@
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use notify::{Event, RecommendedWatcher, RecursiveMode, Watcher};
+use tokio::sync::mpsc;
+use tracing::{error, info, warn};
+
+use crate::{
+    api::AppState,
+    config::CONFIG_FILE,
+    db,
+    jobs,
+    models::AnalyzeRequest,
+    walk::{self, IGNORE_FILE, MANIFEST},
+};
+
+/// Quiet time after the last change before analyzing again, so saving several
+/// files at once, or an editor's write-then-rename, makes a single run.
+const DEBOUNCE: Duration = Duration::from_millis(500);
+
+/// Directories watched by this process.
+#[derive(Clone, Default)]
+pub struct Watched {
+    dirs: Arc<Mutex<HashSet<PathBuf>>>,
+}
+
+/// Watches the directory `req` analyzes and runs `req` again after every burst
+/// of changes to the files it reads, `gravity.toml` or ignore files, until the
+/// process exits. Each run is incremental and announced on `GET /api/events`.
+/// Returns `false` when the directory is watched already.
+pub fn start(state: Arc<AppState>, req: AnalyzeRequest) -> Result<bool> {
+    let path = req.path.clone().unwrap_or_else(|| state.analyze_path.clone());
+    let dir = Path::new(&path)
+        .canonicalize()
+        .with_context(|| format!("Cannot watch {path}"))?;
+
+    let mut watched = state.watched.dirs.lock().unwrap();
+    if watched.contains(&dir) {
+        return Ok(false);
+    }
+    let (tx, rx) = mpsc::unbounded_channel();
+    let mut watcher = notify::recommended_watcher(move |event| {
+        // The receiver only goes away with the watcher itself.
+        let _ = tx.send(event);
+    })
+    .context("Failed to create file watcher")?;
+    watcher
+        .watch(&dir, RecursiveMode::Recursive)
+        .with_context(|| format!("Cannot watch {path}"))?;
+    watched.insert(dir.clone());
+    drop(watched);
+
+    info!("Watching {} for changes", dir.display());
+    let req = AnalyzeRequest { watch: false, ..req };
+    tokio::spawn(rerun_on_change(state, dir, req, watcher, rx));
+    Ok(true)
+}
+
+/// The request `gravity serve --watch <path>` analyzes with, named after the
+/// directory.
+pub fn request_for(path: &str) -> AnalyzeRequest {
+    let name = Path::new(path)
+        .canonicalize()
+        .ok()
+        .and_then(|dir| dir.file_name().map(|n| n.to_string_lossy().into_owned()));
+    AnalyzeRequest {
+        project_name: name,
+        path: Some(path.to_owned()),
+        github_url: None,
+        include: Vec::new(),
+        exclude: Vec::new(),
+        watch: true,
+    }
+}
+
+async fn rerun_on_change(
+    state: Arc<AppState>,
+    dir: PathBuf,
+    req: AnalyzeRequest,
+    // Watching stops when this is dropped.
+    _watcher: RecommendedWatcher,
+    mut events: mpsc::UnboundedReceiver<notify::Result<Event>>,
+) {
+    let mut analyzed = analyzed_files(&dir, &req).await.unwrap_or_default();
+    while let Some(first) = next_change(&mut events, &dir).await {
+        let mut changed: HashSet<PathBuf> = first.into_iter().collect();
+        loop {
+            match tokio::time::timeout(DEBOUNCE, next_change(&mut events, &dir)).await {
+                Ok(Some(paths)) => changed.extend(paths),
+                Ok(None) => return,
+                Err(_) => break,
+            }
+        }
+
+        // A file counts when the walk takes it in now or did before the
+        // change, so deleting a source triggers a run but touching an ignored
+        // one does not. New rules may change any of that.
+        let rules_changed = changed.iter().any(|p| is_walk_rule(p));
+        let now = match analyzed_files(&dir, &req).await {
+            Ok(files) => files,
+            Err(e) => {
+                warn!("Cannot list the files of {}: {e:#}", dir.display());
+                analyzed.clone()
+            }
+        };
+        changed.retain(|p| rules_changed || now.contains(p) || analyzed.contains(p));
+        analyzed = now;
+        if changed.is_empty() {
+            continue;
+        }
+
+        info!("{} file(s) changed under {}, analyzing again", changed.len(), dir.display());
+        // Changes made meanwhile queue up and start the next run.
+        if let Err(e) = rerun(&state, &req).await {
+            error!("Re-analysis of {} failed to start: {e:#}", dir.display());
+        }
+    }
+}
+
+/// The sources and manifests an analysis of `dir` reads, by the same walk.
+async fn analyzed_files(dir: &Path, req: &AnalyzeRequest) -> Result<HashSet<PathBuf>> {
+    let config = jobs::load_config(dir, req)?;
+    let dir = dir.to_owned();
+    let found = tokio::task::spawn_blocking(move || walk::rust_files(&dir, &config.files)).await?;
+    Ok(found.paths.into_iter().chain(found.manifests).collect())
+}
+
+async fn rerun(state: &Arc<AppState>, req: &AnalyzeRequest) -> Result<()> {
+    let job = db::create_job(&state.pool, req).await?;
+    jobs::spawn(state.clone(), job.id, req.clone()).await?;
+    Ok(())
+}
+
+/// The files an analysis of `dir` looks at among those of the next event that
+/// touches any, such as both sides of a rename, or `None` once the watcher has
+/// stopped.
+async fn next_change(
+    events: &mut mpsc::UnboundedReceiver<notify::Result<Event>>,
+    dir: &Path,
+) -> Option<Vec<PathBuf>> {
+    loop {
+        match events.recv().await? {
+            Ok(event) => {
+                let paths: Vec<PathBuf> = event.paths.into_iter().filter(|p| is_watched(dir, p)).collect();
+                if !paths.is_empty() {
+                    return Some(paths);
+                }
+            }
+            Err(e) => warn!("Error watching {}: {e}", dir.display()),
+        }
+    }
+}
+
+/// Rust sources, manifests, configuration and ignore files outside `.git` and
+/// Cargo's `target/` directories. Whether the walk takes them in is settled
+/// once the burst is over.
+fn is_watched(dir: &Path, path: &Path) -> bool {
+    let relevant = path.extension().is_some_and(|x| x == "rs")
+        || path.file_name().is_some_and(|n| n == MANIFEST)
+        || is_walk_rule(path);
+    relevant
+        && !path
+            .ancestors()
+            .skip(1)
+            .take_while(|a| *a != dir)
+            .any(|a| a.file_name().is_some_and(|n| n == ".git") || walk::is_target_dir(a))
+}
+
+/// `gravity.toml` or an ignore file, which decide what the walk takes in.
+fn is_walk_rule(path: &Path) -> bool {
+    path.file_name()
+        .is_some_and(|n| n == CONFIG_FILE || n == IGNORE_FILE || n == ".gitignore" || n == ".ignore")
+}
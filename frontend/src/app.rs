@@ -1,4 +1,8 @@
+use std::collections::BTreeMap;
+
 use leptos::*;
+use futures::StreamExt;
+use gloo_net::eventsource::futures::EventSource;
 use gloo_net::http::Request;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
@@ -18,25 +22,139 @@ pub struct AnalyzeResponse {
     pub message: String,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JobAccepted {
+    pub job_id: String,
+    pub status: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum JobEvent {
+    Phase {
+        status: String,
+    },
+    File {
+        path: String,
+        functions_found: usize,
+        syntax_errors: usize,
+        error: Option<String>,
+        files_parsed: usize,
+        files_total: usize,
+    },
+    Done {
+        result: AnalyzeResponse,
+    },
+    Failed {
+        error: String,
+    },
+}
+
+/// Pushed on `GET /api/events` whenever a run is stored.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum UpdateEvent {
+    Analyzed {
+        result: AnalyzeResponse,
+    },
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AnalysisSummary {
     pub project_id: String,
     pub project_name: String,
+    pub run_id: String,
+    pub commit_sha: Option<String>,
+    pub analyzed_at: String,
     pub total_files: i64,
     pub total_functions: i64,
     pub total_structs: i64,
     pub total_imports: i64,
+    pub symbol_counts: BTreeMap<String, i64>,
     pub avg_complexity: f64,
-    pub dead_code_candidates: Vec<String>,
+    #[serde(default)]
+    pub comment_ratio: f64,
+    #[serde(default)]
+    pub doc_coverage: f64,
+    pub dead_code_count: i64,
+    #[serde(default)]
+    pub crates: Vec<CrateSummary>,
+    pub layer_violations: Vec<LayerViolation>,
     pub architecture_notes: Vec<String>,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CrateSummary {
+    pub name: String,
+    pub path: String,
+    pub version: Option<String>,
+    pub targets: Vec<String>,
+    pub files: i64,
+    pub functions: i64,
+    pub lines: i64,
+    pub avg_complexity: f64,
+    pub doc_coverage: f64,
+    pub dead_code: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LayerViolation {
+    pub from_layer: String,
+    pub to_layer: String,
+    pub file_path: String,
+    pub line: i32,
+    pub use_path: String,
+    pub target_path: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeadCodeItem {
+    pub function_name: String,
+    pub qualified_name: String,
+    pub file_path: String,
+    pub line_start: i32,
+    pub line_end: i32,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeadCodePage {
+    pub items: Vec<DeadCodeItem>,
+    pub total: i64,
+    pub page: i64,
+    pub per_page: i64,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FileEntry {
     pub id: String,
     pub path: String,
     pub module_name: Option<String>,
     pub line_count: i32,
+    #[serde(default)]
+    pub code_lines: i32,
+    #[serde(default)]
+    pub comment_lines: i32,
+    #[serde(default)]
+    pub doc_lines: i32,
+    #[serde(default)]
+    pub blank_lines: i32,
+    #[serde(default)]
+    pub public_items: i32,
+    #[serde(default)]
+    pub documented_items: i32,
+    #[serde(default)]
+    pub halstead_volume: f64,
+    #[serde(default)]
+    pub halstead_difficulty: f64,
+    #[serde(default)]
+    pub halstead_effort: f64,
+    #[serde(default)]
+    pub maintainability_index: f64,
+    #[serde(default)]
+    pub crate_name: Option<String>,
+    #[serde(default)]
+    pub target: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -59,15 +177,160 @@ pub struct GraphData {
     pub edges: Vec<GraphEdge>,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportEdge {
+    pub from: String,
+    pub to: String,
+    pub path: String,
+    pub line: i32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DependencyCycle {
+    pub files: Vec<String>,
+    pub members: Vec<String>,
+    pub imports: Vec<ImportEdge>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModuleMetrics {
+    pub path: String,
+    pub afferent: i64,
+    pub efferent: i64,
+    pub instability: f64,
+    pub abstractness: f64,
+    pub distance: f64,
+    pub abstract_types: i32,
+    pub concrete_types: i32,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ComplexityItem {
     pub function_name: String,
+    pub qualified_name: String,
     pub file_path: String,
     pub score: i32,
+    pub cognitive: i32,
+    #[serde(default)]
+    pub halstead_volume: f64,
+    #[serde(default)]
+    pub halstead_difficulty: f64,
+    #[serde(default)]
+    pub halstead_effort: f64,
+    #[serde(default)]
+    pub maintainability_index: f64,
+    #[serde(default)]
+    pub logical_lines: i32,
+    #[serde(default)]
+    pub params: i32,
+    #[serde(default)]
+    pub generic_params: i32,
+    #[serde(default)]
+    pub max_nesting: i32,
+    #[serde(default)]
+    pub returns: i32,
+    #[serde(default)]
+    pub unsafe_blocks: i32,
+    #[serde(default)]
+    pub unwraps: i32,
     pub line_start: i32,
     pub line_end: i32,
 }
 
+/// Lines kept in the header's analysis log; older ones scroll away.
+const PROGRESS_LOG_LINES: usize = 200;
+
+/// Live state of the running analysis, fed by the job's event stream.
+#[derive(Debug, Clone, Default)]
+struct JobProgress {
+    phase: String,
+    files_parsed: usize,
+    files_total: usize,
+    log: Vec<(String, bool)>, // (line, is_error)
+}
+
+impl JobProgress {
+    fn push_log(&mut self, line: String, is_error: bool) {
+        self.log.push((line, is_error));
+        if self.log.len() > PROGRESS_LOG_LINES {
+            self.log.remove(0);
+        }
+    }
+
+    fn percent(&self) -> usize {
+        match self.phase.as_str() {
+            "scoring" | "saving" | "done" => 100,
+            _ if self.files_total == 0 => 0,
+            _ => self.files_parsed * 100 / self.files_total,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AnalysisRun {
+    pub id: String,
+    pub project_id: String,
+    pub commit_sha: Option<String>,
+    pub analyzer_version: String,
+    pub created_at: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileChange {
+    pub path: String,
+    pub line_count_from: i32,
+    pub line_count_to: i32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ComplexityDelta {
+    pub function_name: String,
+    pub qualified_name: String,
+    pub file_path: String,
+    pub score_from: i32,
+    pub score_to: i32,
+    pub delta: i32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunDiff {
+    pub from_run: String,
+    pub to_run: String,
+    pub files_added: Vec<String>,
+    pub files_removed: Vec<String>,
+    pub files_changed: Vec<FileChange>,
+    pub functions_added: Vec<ComplexityItem>,
+    pub functions_removed: Vec<ComplexityItem>,
+    pub complexity_deltas: Vec<ComplexityDelta>,
+    pub edges_added: Vec<GraphEdge>,
+    pub edges_removed: Vec<GraphEdge>,
+    pub new_dead_code: Vec<DeadCodeItem>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GateViolation {
+    pub file_path: Option<String>,
+    pub line: Option<i32>,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GateResult {
+    pub gate: String,
+    pub threshold: String,
+    pub actual: String,
+    pub passed: bool,
+    pub violations: Vec<GateViolation>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GateReport {
+    pub run_id: Option<String>,
+    pub baseline_run_id: Option<String>,
+    pub passed: bool,
+    pub gates: Vec<GateResult>,
+}
+
 // ───  ──────────────────────────────────────────────────────────────
 
 #[derive(Debug, Clone, PartialEq)]
@@ -76,6 +339,8 @@ enum Tab {
     Files,
     Graph,
     Complexity,
+    Modules,
+    Compare,
 }
 
 #[component]
@@ -87,35 +352,45 @@ pub fn App() -> impl IntoView {
     let (error, set_error)            = create_signal::<Option<String>>(None);
     let (analyze_msg, set_analyze_msg)= create_signal::<Option<String>>(None);
     let (github_url, set_github_url)  = create_signal(String::new());
+    let (progress, set_progress)      = create_signal::<Option<JobProgress>>(None);
+    // Bumped when a new run of the project on screen is stored.
+    let (revision, set_revision)      = create_signal(0u32);
+
+    spawn_local(follow_updates(project_id, set_project_id, set_revision, set_analyze_msg));
 
     let run_analyze = move |_| {
         set_analyzing(true);
         set_error(None);
+        set_analyze_msg(None);
         spawn_local(async move {
-            match Request::post("/api/analyze")
-                .header("Content-Type", "application/json")
-                .body(serde_json::to_string(&AnalyzeRequest {
+            let accepted = match Request::post("/api/analyze")
+                .json(&AnalyzeRequest {
                     project_name: Some("gravity-project".into()),
                     path: None,
                     github_url: if github_url().is_empty() { None } else { Some(github_url()) },
-                }).unwrap())
+                })
+                .unwrap()
                 .send()
                 .await
             {
                 Ok(resp) => {
                     if resp.ok() {
-                        match resp.json::<AnalyzeResponse>().await {
-                            Ok(data) => {
-                                set_project_id(Some(data.project_id.clone()));
-                                set_analyze_msg(Some(data.message));
-                            }
-                            Err(e) => set_error(Some(format!("Parse error: {e}"))),
+                        match resp.json::<JobAccepted>().await {
+                            Ok(data) => Some(data),
+                            Err(e) => { set_error(Some(format!("Parse error: {e}"))); None }
                         }
                     } else {
                         set_error(Some(format!("HTTP {}", resp.status())));
+                        None
                     }
                 }
-                Err(e) => set_error(Some(format!("Request failed: {e}"))),
+                Err(e) => { set_error(Some(format!("Request failed: {e}"))); None }
+            };
+
+            if let Some(accepted) = accepted {
+                set_progress(Some(JobProgress { phase: accepted.status, ..Default::default() }));
+                follow_job(&accepted.job_id, set_progress, set_project_id, set_analyze_msg, set_error).await;
+                set_progress(None);
             }
             set_analyzing(false);
         });
@@ -164,6 +439,7 @@ pub fn App() -> impl IntoView {
                         </button>
                     </div>
                 </div>
+                {move || progress().map(|p| view! { <AnalysisProgress progress=p /> })}
             </header>
 
             <nav class="max-w-7xl mx-auto px-6 pt-6">
@@ -174,6 +450,8 @@ pub fn App() -> impl IntoView {
                         (Tab::Files,      "📁 Files"),
                         (Tab::Graph,      "🔗 Graph"),
                         (Tab::Complexity, "🌡 Complexity"),
+                        (Tab::Modules,    "📐 Modules"),
+                        (Tab::Compare,    "⚖ Compare"),
                     ].into_iter().map(|(tab, label)| {
                         let tab_clone = tab.clone();
                         view! {
@@ -196,10 +474,12 @@ pub fn App() -> impl IntoView {
             // ── Content ───────────────────────────────────────────────────
             <main class="max-w-7xl mx-auto px-6 py-6">
                 {move || match active_tab() {
-                    Tab::Summary    => view! { <SummaryPanel project_id=project_id /> }.into_view(),
+                    Tab::Summary    => view! { <SummaryPanel project_id=project_id revision=revision /> }.into_view(),
                     Tab::Files      => view! { <FilesPanel project_id=project_id /> }.into_view(),
-                    Tab::Graph      => view! { <GraphPanel project_id=project_id /> }.into_view(),
-                    Tab::Complexity => view! { <ComplexityPanel project_id=project_id /> }.into_view(),
+                    Tab::Graph      => view! { <GraphPanel project_id=project_id revision=revision /> }.into_view(),
+                    Tab::Complexity => view! { <ComplexityPanel project_id=project_id revision=revision /> }.into_view(),
+                    Tab::Modules    => view! { <ModulesPanel project_id=project_id /> }.into_view(),
+                    Tab::Compare    => view! { <ComparePanel project_id=project_id /> }.into_view(),
                 }}
             </main>
         </div>
@@ -207,9 +487,137 @@ pub fn App() -> impl IntoView {
 }
 
 
+/// Follows `GET /api/analyze/{job}/events` until the job finishes or fails.
+async fn follow_job(
+    job_id: &str,
+    set_progress: WriteSignal<Option<JobProgress>>,
+    set_project_id: WriteSignal<Option<String>>,
+    set_analyze_msg: WriteSignal<Option<String>>,
+    set_error: WriteSignal<Option<String>>,
+) {
+    let mut source = match EventSource::new(&format!("/api/analyze/{job_id}/events")) {
+        Ok(source) => source,
+        Err(e) => return set_error(Some(format!("Event stream failed: {e:?}"))),
+    };
+    let mut messages = match source.subscribe("message") {
+        Ok(messages) => messages,
+        Err(e) => return set_error(Some(format!("Event stream failed: {e:?}"))),
+    };
+
+    while let Some(message) = messages.next().await {
+        let event = match message {
+            Ok((_, msg)) => msg
+                .data()
+                .as_string()
+                .and_then(|data| serde_json::from_str::<JobEvent>(&data).ok()),
+            Err(e) => {
+                set_error(Some(format!("Event stream failed: {e}")));
+                break;
+            }
+        };
+
+        match event {
+            Some(JobEvent::Phase { status }) => set_progress.update(|p| {
+                if let Some(p) = p {
+                    p.push_log(format!("── {status}"), false);
+                    p.phase = status;
+                }
+            }),
+            Some(JobEvent::File { path, functions_found, syntax_errors, error, files_parsed, files_total }) => {
+                set_progress.update(|p| {
+                    if let Some(p) = p {
+                        p.files_parsed = files_parsed;
+                        p.files_total = files_total;
+                        match error {
+                            Some(e) => p.push_log(format!("{path}: {e}"), true),
+                            None if syntax_errors > 0 => p.push_log(
+                                format!("{path}: {functions_found} fn, {syntax_errors} syntax errors"),
+                                true,
+                            ),
+                            None => p.push_log(format!("{path}: {functions_found} fn"), false),
+                        }
+                    }
+                })
+            }
+            Some(JobEvent::Done { result }) => {
+                set_project_id(Some(result.project_id.clone()));
+                set_analyze_msg(Some(result.message));
+                break;
+            }
+            Some(JobEvent::Failed { error }) => {
+                set_error(Some(error));
+                break;
+            }
+            None => {}
+        }
+    }
+
+    source.close();
+}
+
+/// Follows `GET /api/events` while the page is open, so runs started elsewhere,
+/// such as by `gravity serve --watch`, show up without another click. Only
+/// runs of the project on screen count, or of any project before one is.
+async fn follow_updates(
+    project_id: ReadSignal<Option<String>>,
+    set_project_id: WriteSignal<Option<String>>,
+    set_revision: WriteSignal<u32>,
+    set_analyze_msg: WriteSignal<Option<String>>,
+) {
+    let Ok(mut source) = EventSource::new("/api/events") else { return };
+    let Ok(mut messages) = source.subscribe("message") else { return };
+
+    // The browser reconnects on its own after an error.
+    while let Some(message) = messages.next().await {
+        let event = message.ok().and_then(|(_, msg)| {
+            msg.data()
+                .as_string()
+                .and_then(|data| serde_json::from_str::<UpdateEvent>(&data).ok())
+        });
+        let Some(UpdateEvent::Analyzed { result }) = event else { continue };
+
+        match project_id.get_untracked() {
+            Some(id) if id != result.project_id => continue,
+            Some(_) => set_revision.update(|r| *r += 1),
+            None => set_project_id(Some(result.project_id.clone())),
+        }
+        set_analyze_msg(Some(result.message));
+    }
+
+    source.close();
+}
+
+#[component]
+fn AnalysisProgress(progress: JobProgress) -> impl IntoView {
+    let percent = progress.percent();
+    let bar_style = format!("width: {percent}%; background: linear-gradient(135deg, #7c3aed, #4f46e5);");
+    let counts = format!("{}/{} files", progress.files_parsed, progress.files_total);
+
+    view! {
+        <div class="max-w-7xl mx-auto px-6 pb-4">
+            <div class="flex items-center justify-between mb-1 text-xs" style="color: var(--text-muted);">
+                <span class="uppercase tracking-wider">{progress.phase.clone()}</span>
+                <span class="mono">{counts}</span>
+            </div>
+            <div class="h-2 rounded-full overflow-hidden" style="background: var(--bg-card);">
+                <div class="h-2 rounded-full transition-all" style=bar_style></div>
+            </div>
+            <div class="mt-2 h-24 overflow-y-auto rounded-lg px-3 py-2 mono flex flex-col-reverse"
+                 style="background: var(--bg-card); border: 1px solid var(--border); font-size: 0.7rem;">
+                <div>
+                    {progress.log.into_iter().map(|(line, is_error)| {
+                        let color = if is_error { "color: var(--danger);" } else { "color: var(--text-muted);" };
+                        view! { <div style=color>{line}</div> }
+                    }).collect_view()}
+                </div>
+            </div>
+        </div>
+    }
+}
+
 #[component]
-fn SummaryPanel(project_id: ReadSignal<Option<String>>) -> impl IntoView {
-    let summary = create_resource(project_id, |pid| async move {
+fn SummaryPanel(project_id: ReadSignal<Option<String>>, revision: ReadSignal<u32>) -> impl IntoView {
+    let summary = create_resource(move || (project_id(), revision()), |(pid, _)| async move {
         let url = match &pid {
             Some(id) => format!("/api/summary?project_id={id}"),
             None => "/api/summary".into(),
@@ -227,14 +635,31 @@ fn SummaryPanel(project_id: ReadSignal<Option<String>>) -> impl IntoView {
                             <h2 class="text-2xl font-bold" style="color: var(--text-primary);">
                                 {s.project_name.clone()}
                             </h2>
-                            <p class="text-sm mt-1" style="color: var(--text-muted);">"Project analysis results"</p>
+                            <p class="text-sm mt-1" style="color: var(--text-muted);">
+                                {match &s.commit_sha {
+                                    Some(sha) => format!("Analysis of {} · {}", &sha[..sha.len().min(7)], s.analyzed_at),
+                                    None => format!("Analysis from {}", s.analyzed_at),
+                                }}
+                            </p>
                         </div>
-                        <div class="grid grid-cols-2 gap-4 mb-6 lg:grid-cols-4">
+                        <div class="grid grid-cols-2 gap-4 mb-6 md:grid-cols-3 lg:grid-cols-6">
                             <StatCard label="Files" value=s.total_files.to_string() icon="📁" />
                             <StatCard label="Functions" value=s.total_functions.to_string() icon="⚙️" />
                             <StatCard label="Imports" value=s.total_imports.to_string() icon="🔗" />
                             <StatCard label="Avg Complexity"
                                       value=format!("{:.1}", s.avg_complexity) icon="🌡" />
+                            <StatCard label="Comment Ratio"
+                                      value=format!("{:.0}%", s.comment_ratio * 100.0) icon="💬" />
+                            <StatCard label="Doc Coverage"
+                                      value=format!("{:.0}%", s.doc_coverage * 100.0) icon="📝" />
+                        </div>
+                        <div class="flex flex-wrap gap-2 mb-6">
+                            {s.symbol_counts.iter().map(|(kind, count)| view! {
+                                <span class="text-xs px-3 py-1 rounded-full mono"
+                                      style="background: var(--bg-card); border: 1px solid var(--border); color: var(--text-muted);">
+                                    {format!("{count} {kind}")}
+                                </span>
+                            }).collect_view()}
                         </div>
                         <div class="grid gap-4 lg:grid-cols-2">
                             <div class="p-5 rounded-xl" style="background: var(--bg-card); border: 1px solid var(--border);">
@@ -248,23 +673,20 @@ fn SummaryPanel(project_id: ReadSignal<Option<String>>) -> impl IntoView {
                                     }).collect_view()}
                                 </ul>
                             </div>
-                            <div class="p-5 rounded-xl" style="background: var(--bg-card); border: 1px solid var(--border);">
-                                <h3 class="font-semibold mb-3" style="color: var(--warning);">"💀 Dead Code Candidates"</h3>
-                                {if s.dead_code_candidates.is_empty() {
-                                    view! { <p class="text-sm" style="color: var(--success);">"✓ No dead code detected."</p> }.into_view()
-                                } else {
-                                    view! {
-                                        <ul class="space-y-1">
-                                            {s.dead_code_candidates.iter().map(|fn_name| view! {
-                                                <li class="text-sm mono px-2 py-1 rounded"
-                                                    style="background: rgba(210,153,34,0.1); color: var(--warning);">
-                                                    {fn_name.clone()}
-                                                </li>
-                                            }).collect_view()}
-                                        </ul>
-                                    }.into_view()
-                                }}
+                            <DeadCodeCard project_id=project_id revision=revision />
+                        </div>
+                        {(!s.crates.is_empty()).then(|| view! {
+                            <div class="mt-4">
+                                <CratesCard crates=s.crates.clone() />
+                            </div>
+                        })}
+                        {(!s.layer_violations.is_empty()).then(|| view! {
+                            <div class="mt-4">
+                                <LayerViolationsCard violations=s.layer_violations.clone() />
                             </div>
+                        })}
+                        <div class="mt-4">
+                            <GatesCard project_id=project_id revision=revision />
                         </div>
                     </div>
                 })}
@@ -286,6 +708,218 @@ fn SummaryPanel(project_id: ReadSignal<Option<String>>) -> impl IntoView {
     }
 }
 
+/// The summary numbers per Cargo package.
+#[component]
+fn CratesCard(crates: Vec<CrateSummary>) -> impl IntoView {
+    view! {
+        <div class="p-5 rounded-xl" style="background: var(--bg-card); border: 1px solid var(--border);">
+            <h3 class="font-semibold mb-3" style="color: var(--accent-light);">
+                {format!("📦 Crates ({})", crates.len())}
+            </h3>
+            <table class="w-full text-sm">
+                <thead>
+                    <tr>
+                        <th class="text-left py-1 font-semibold" style="color: var(--text-muted);">"Crate"</th>
+                        <th class="text-left py-1 font-semibold" style="color: var(--text-muted);">"Targets"</th>
+                        <th class="text-right py-1 font-semibold" style="color: var(--text-muted);">"Files"</th>
+                        <th class="text-right py-1 font-semibold" style="color: var(--text-muted);">"Functions"</th>
+                        <th class="text-right py-1 font-semibold" style="color: var(--text-muted);">"Lines"</th>
+                        <th class="text-right py-1 font-semibold" style="color: var(--text-muted);">"Avg Complexity"</th>
+                        <th class="text-right py-1 font-semibold" style="color: var(--text-muted);">"Docs"</th>
+                        <th class="text-right py-1 font-semibold" style="color: var(--text-muted);">"Unreachable"</th>
+                    </tr>
+                </thead>
+                <tbody>
+                    {crates.into_iter().map(|c| view! {
+                        <tr>
+                            <td class="py-1">
+                                <span class="mono" style="color: var(--accent-light);">{c.name.clone()}</span>
+                                <span class="ml-2 text-xs mono" style="color: var(--text-muted);">
+                                    {match &c.version {
+                                        Some(v) => format!("{v} · {}", if c.path.is_empty() { "." } else { &c.path }),
+                                        None => c.path.clone(),
+                                    }}
+                                </span>
+                            </td>
+                            <td class="py-1 text-xs mono" style="color: var(--text-muted);">{c.targets.join(", ")}</td>
+                            {[c.files, c.functions, c.lines].into_iter().map(|n| view! {
+                                <td class="py-1 text-right mono" style="color: var(--text-primary);">{n}</td>
+                            }).collect_view()}
+                            <td class="py-1 text-right mono" style="color: var(--text-primary);">{format!("{:.1}", c.avg_complexity)}</td>
+                            <td class="py-1 text-right mono" style="color: var(--text-primary);">{format!("{:.0}%", c.doc_coverage * 100.0)}</td>
+                            <td class="py-1 text-right mono" style="color: var(--text-muted);">{c.dead_code}</td>
+                        </tr>
+                    }).collect_view()}
+                </tbody>
+            </table>
+        </div>
+    }
+}
+
+/// Uses that cross the layering declared in the project's `gravity.toml`.
+#[component]
+fn LayerViolationsCard(violations: Vec<LayerViolation>) -> impl IntoView {
+    view! {
+        <div class="p-5 rounded-xl" style="background: var(--bg-card); border: 1px solid var(--border);">
+            <h3 class="font-semibold mb-3" style="color: var(--warning);">
+                {format!("🧱 Layer Violations ({})", violations.len())}
+            </h3>
+            <ul class="space-y-2">
+                {violations.into_iter().map(|v| view! {
+                    <li class="text-sm flex gap-2 items-baseline">
+                        <span class="px-2 py-0.5 rounded-md text-xs font-bold mono"
+                              style="background: rgba(210,153,34,0.15); color: var(--warning);">
+                            {format!("{} → {}", v.from_layer, v.to_layer)}
+                        </span>
+                        <span class="mono" style="color: var(--accent-light); font-size: 0.8rem;">
+                            {format!("use {}", v.use_path)}
+                        </span>
+                        <span class="mono" style="color: var(--text-muted); font-size: 0.75rem;">
+                            {format!("{}:{}", v.file_path, v.line)}
+                        </span>
+                    </li>
+                }).collect_view()}
+            </ul>
+        </div>
+    }
+}
+
+/// Pass/fail of the quality gates from the project's `gravity.toml`.
+/// Unreachable functions, a page at a time.
+#[component]
+fn DeadCodeCard(project_id: ReadSignal<Option<String>>, revision: ReadSignal<u32>) -> impl IntoView {
+    const PER_PAGE: i64 = 20;
+    let (page, set_page) = create_signal(1i64);
+    // A different project starts over from its first page.
+    create_effect(move |_| {
+        project_id.track();
+        set_page(1);
+    });
+
+    let dead = create_resource(
+        move || (project_id(), page(), revision()),
+        |(pid, page, _)| async move {
+            let mut url = format!("/api/dead-code?page={page}&per_page={PER_PAGE}");
+            if let Some(id) = &pid {
+                url.push_str(&format!("&project_id={id}"));
+            }
+            Request::get(&url).send().await.ok()?
+                .json::<DeadCodePage>().await.ok()
+        },
+    );
+
+    let button_style = "background: var(--bg-secondary); border: 1px solid var(--border); color: var(--text-primary);";
+
+    view! {
+        <div class="p-5 rounded-xl" style="background: var(--bg-card); border: 1px solid var(--border);">
+            <h3 class="font-semibold mb-3" style="color: var(--warning);">"💀 Dead Code"</h3>
+            <Suspense fallback=move || view! { <p class="text-sm" style="color: var(--text-muted);">"Loading…"</p> }>
+                {move || dead.get().flatten().map(|d| {
+                    if d.total == 0 {
+                        return view! { <p class="text-sm" style="color: var(--success);">"✓ No dead code detected."</p> }.into_view();
+                    }
+                    let pages = (d.total + d.per_page - 1) / d.per_page;
+                    let first = (d.page - 1) * d.per_page + 1;
+                    let last = (first + d.items.len() as i64 - 1).max(first);
+                    view! {
+                        <div>
+                            <ul class="space-y-1">
+                                {d.items.into_iter().map(|item| view! {
+                                    <li class="text-sm px-2 py-1 rounded"
+                                        style="background: rgba(210,153,34,0.1);">
+                                        <span class="mono" style="color: var(--warning);">{item.qualified_name}</span>
+                                        <span class="mono text-xs ml-2" style="color: var(--text-muted);">
+                                            {format!("{}:{}-{}", item.file_path, item.line_start, item.line_end)}
+                                        </span>
+                                        <div class="text-xs" style="color: var(--text-muted);">{item.reason}</div>
+                                    </li>
+                                }).collect_view()}
+                            </ul>
+                            <div class="mt-3 flex items-center justify-between text-xs" style="color: var(--text-muted);">
+                                <span>{format!("{first}–{last} of {}", d.total)}</span>
+                                <div class="flex gap-2">
+                                    <button class="px-2 py-1 rounded" style=button_style
+                                            disabled={d.page <= 1}
+                                            on:click=move |_| set_page.update(|p| *p -= 1)>"‹ Prev"</button>
+                                    <button class="px-2 py-1 rounded" style=button_style
+                                            disabled={d.page >= pages}
+                                            on:click=move |_| set_page.update(|p| *p += 1)>"Next ›"</button>
+                                </div>
+                            </div>
+                        </div>
+                    }.into_view()
+                })}
+            </Suspense>
+        </div>
+    }
+}
+
+#[component]
+fn GatesCard(project_id: ReadSignal<Option<String>>, revision: ReadSignal<u32>) -> impl IntoView {
+    let report = create_resource(move || (project_id(), revision()), |(pid, _)| async move {
+        let url = match &pid {
+            Some(id) => format!("/api/gates?project_id={id}"),
+            None => "/api/gates".into(),
+        };
+        Request::get(&url).send().await.ok()?
+            .json::<GateReport>().await.ok()
+    });
+
+    view! {
+        <div class="p-5 rounded-xl" style="background: var(--bg-card); border: 1px solid var(--border);">
+            <Suspense fallback=move || view! { <p class="text-sm" style="color: var(--text-muted);">"Checking gates…"</p> }>
+                {move || report.get().flatten().map(|r| view! {
+                    <div>
+                        <div class="flex items-center justify-between mb-3">
+                            <h3 class="font-semibold" style="color: var(--accent-light);">"🚦 Quality Gates"</h3>
+                            {(!r.gates.is_empty()).then(|| view! {
+                                <DeltaBadge good=r.passed text=(if r.passed { "PASS" } else { "FAIL" }).to_string() />
+                            })}
+                        </div>
+                        {if r.gates.is_empty() {
+                            view! {
+                                <p class="text-sm" style="color: var(--text-muted);">
+                                    "No gates configured. Add a [gates] section to gravity.toml."
+                                </p>
+                            }.into_view()
+                        } else {
+                            view! {
+                                <ul class="space-y-2">
+                                    {r.gates.into_iter().map(|g| view! {
+                                        <li class="text-sm">
+                                            <div class="flex items-center gap-2">
+                                                <DeltaBadge good=g.passed text=(if g.passed { "✓" } else { "✗" }).to_string() />
+                                                <span class="mono" style="color: var(--text-primary);">{g.gate.clone()}</span>
+                                                <span style="color: var(--text-muted);">
+                                                    {format!("{} (limit {})", g.actual, g.threshold)}
+                                                </span>
+                                            </div>
+                                            <ul class="mt-1 ml-8 space-y-1">
+                                                {g.violations.into_iter().map(|v| {
+                                                    let location = match (v.file_path, v.line) {
+                                                        (Some(path), Some(line)) => format!("{path}:{line} "),
+                                                        (Some(path), None) => format!("{path} "),
+                                                        _ => String::new(),
+                                                    };
+                                                    view! {
+                                                        <li class="mono" style="color: var(--danger); font-size: 0.75rem;">
+                                                            {location}{v.message}
+                                                        </li>
+                                                    }
+                                                }).collect_view()}
+                                            </ul>
+                                        </li>
+                                    }).collect_view()}
+                                </ul>
+                            }.into_view()
+                        }}
+                    </div>
+                })}
+            </Suspense>
+        </div>
+    }
+}
+
 #[component]
 fn FilesPanel(project_id: ReadSignal<Option<String>>) -> impl IntoView {
     let files = create_resource(project_id, |pid| async move {
@@ -310,7 +944,13 @@ fn FilesPanel(project_id: ReadSignal<Option<String>>) -> impl IntoView {
                                     <tr style="background: var(--bg-secondary);">
                                         <th class="text-left px-4 py-3 font-semibold" style="color: var(--text-muted);">"File Path"</th>
                                         <th class="text-left px-4 py-3 font-semibold" style="color: var(--text-muted);">"Module"</th>
+                                        <th class="text-left px-4 py-3 font-semibold" style="color: var(--text-muted);">"Crate"</th>
                                         <th class="text-right px-4 py-3 font-semibold" style="color: var(--text-muted);">"Lines"</th>
+                                        <th class="text-right px-4 py-3 font-semibold" style="color: var(--text-muted);">"Code"</th>
+                                        <th class="text-right px-4 py-3 font-semibold" style="color: var(--text-muted);">"Comments"</th>
+                                        <th class="text-right px-4 py-3 font-semibold" style="color: var(--text-muted);">"Docs"</th>
+                                        <th class="text-right px-4 py-3 font-semibold" style="color: var(--text-muted);">"Blank"</th>
+                                        <th class="text-right px-4 py-3 font-semibold" style="color: var(--text-muted);" title="Maintainability Index (0–100)">"MI"</th>
                                     </tr>
                                 </thead>
                                 <tbody>
@@ -325,9 +965,18 @@ fn FilesPanel(project_id: ReadSignal<Option<String>>) -> impl IntoView {
                                                 <td class="px-4 py-2" style="color: var(--text-muted);">
                                                     {f.module_name.clone().unwrap_or_default()}
                                                 </td>
+                                                <td class="px-4 py-2 mono" style="color: var(--text-muted); font-size: 0.8rem;" title=f.target.clone().unwrap_or_default()>
+                                                    {f.crate_name.clone().unwrap_or_default()}
+                                                </td>
                                                 <td class="px-4 py-2 text-right mono" style="color: var(--text-primary);">
                                                     {f.line_count}
                                                 </td>
+                                                {[f.code_lines, f.comment_lines, f.doc_lines, f.blank_lines].into_iter().map(|n| view! {
+                                                    <td class="px-4 py-2 text-right mono" style="color: var(--text-muted);">{n}</td>
+                                                }).collect_view()}
+                                                <td class="px-4 py-2 text-right mono font-bold" style=format!("color: {};", mi_color(f.maintainability_index))>
+                                                    {format!("{:.0}", f.maintainability_index)}
+                                                </td>
                                             </tr>
                                         }
                                     }).collect_view()}
@@ -341,77 +990,240 @@ fn FilesPanel(project_id: ReadSignal<Option<String>>) -> impl IntoView {
     }
 }
 
+/// Maintainability Index bands, as in `complexity::maintainability_index`.
+const MI_GOOD: f64 = 20.0;
+const MI_POOR: f64 = 10.0;
+
+fn mi_color(index: f64) -> &'static str {
+    if index >= MI_GOOD {
+        "var(--success)"
+    } else if index >= MI_POOR {
+        "var(--warning)"
+    } else {
+        "var(--danger)"
+    }
+}
+
 #[component]
-fn GraphPanel(project_id: ReadSignal<Option<String>>) -> impl IntoView {
-    let graph = create_resource(project_id, |pid| async move {
+fn GraphPanel(project_id: ReadSignal<Option<String>>, revision: ReadSignal<u32>) -> impl IntoView {
+    let (mode, set_mode) = create_signal(GraphMode::Files);
+    let (root, set_root) = create_signal(String::new());
+    let (depth, set_depth) = create_signal(3usize);
+
+    let graph = create_resource(
+        move || (project_id(), mode(), root(), depth(), revision()),
+        |(pid, mode, root, depth, _)| async move {
+            let mut params = Vec::new();
+            if let Some(id) = &pid {
+                params.push(format!("project_id={id}"));
+            }
+            let endpoint = match mode {
+                GraphMode::Files => "/api/graph",
+                GraphMode::Crates => "/api/crates/graph",
+                GraphMode::Calls => {
+                    if !root.is_empty() {
+                        params.push(format!("root={}", js_sys::encode_uri_component(&root)));
+                    }
+                    params.push(format!("depth={depth}"));
+                    "/api/callgraph"
+                }
+            };
+            let url = if params.is_empty() {
+                endpoint.to_string()
+            } else {
+                format!("{endpoint}?{}", params.join("&"))
+            };
+            Request::get(&url).send().await.ok()?
+                .json::<GraphData>().await.ok()
+        },
+    );
+
+    let cycles = create_resource(move || (project_id(), revision()), |(pid, _)| async move {
+        let url = match &pid {
+            Some(id) => format!("/api/cycles?project_id={id}"),
+            None => "/api/cycles".into(),
+        };
+        Request::get(&url).send().await.ok()?
+            .json::<Vec<DependencyCycle>>().await.ok()
+    });
+
+    let violations = create_resource(move || (project_id(), revision()), |(pid, _)| async move {
         let url = match &pid {
-            Some(id) => format!("/api/graph?project_id={id}"),
-            None => "/api/graph".into(),
+            Some(id) => format!("/api/layers/violations?project_id={id}"),
+            None => "/api/layers/violations".into(),
         };
         Request::get(&url).send().await.ok()?
-            .json::<GraphData>().await.ok()
+            .json::<Vec<LayerViolation>>().await.ok()
     });
 
+    let input_style = "background: var(--bg-card); border: 1px solid var(--border); color: var(--text-primary); outline: none;";
+
     view! {
-        <Suspense fallback=move || view! { <LoadingCard /> }>
-            {move || graph.get().flatten().map(|g| {
-                if g.nodes.is_empty() {
-                    return view! { <EmptyState icon="🔗" title="No graph data" hint="Run analysis first." /> }.into_view();
-                }
+        <div>
+            <div class="mb-4 flex items-center gap-3 text-sm">
+                <div class="flex gap-1 p-1 rounded-lg"
+                     style="background: var(--bg-secondary); border: 1px solid var(--border);">
+                    {[(GraphMode::Files, "Files"), (GraphMode::Crates, "Crates"), (GraphMode::Calls, "Calls")].into_iter().map(|(m, label)| view! {
+                        <button
+                            on:click=move |_| set_mode(m)
+                            class="px-3 py-1 rounded-md font-medium transition-all"
+                            style=move || {
+                                if mode() == m {
+                                    "background: var(--accent); color: white;"
+                                } else {
+                                    "color: var(--text-muted); background: transparent;"
+                                }
+                            }
+                        >{label}</button>
+                    }).collect_view()}
+                </div>
+                <Show when=move || mode() == GraphMode::Calls>
+                    <input
+                        type="text"
+                        placeholder="root function (all when empty)"
+                        on:change=move |ev| set_root(event_target_value(&ev).trim().to_string())
+                        prop:value=root
+                        class="px-3 py-1 rounded-lg mono w-64"
+                        style=input_style
+                    />
+                    <span style="color: var(--text-muted);">"depth"</span>
+                    <input
+                        type="number"
+                        min="1"
+                        max="10"
+                        on:change=move |ev| {
+                            if let Ok(d) = event_target_value(&ev).parse() {
+                                set_depth(d);
+                            }
+                        }
+                        prop:value=move || depth().to_string()
+                        class="px-3 py-1 rounded-lg mono w-20"
+                        style=input_style
+                    />
+                </Show>
+            </div>
+            <Suspense fallback=move || view! { <LoadingCard /> }>
+                {move || graph.get().flatten().map(|g| {
+                    if g.nodes.is_empty() {
+                        let hint = match mode() {
+                            GraphMode::Files => "Run analysis first.",
+                            GraphMode::Crates => "No Cargo.toml found in the analyzed tree.",
+                            GraphMode::Calls => "No resolved calls from this function.",
+                        };
+                        return view! { <EmptyState icon="🔗" title="No graph data" hint=hint /> }.into_view();
+                    }
+                    // Cycles and layers are between files, so the call graph has none to show.
+                    let (cycles, violations) = match mode() {
+                        GraphMode::Files => (
+                            cycles.get().flatten().unwrap_or_default(),
+                            violations.get().flatten().unwrap_or_default(),
+                        ),
+                        GraphMode::Crates | GraphMode::Calls => (Vec::new(), Vec::new()),
+                    };
+                    view! { <GraphView graph=g cycles=cycles violations=violations /> }.into_view()
+                })}
+            </Suspense>
+        </div>
+    }
+}
 
-                let nodes_json = serde_json::to_string(&g.nodes).unwrap_or_default();
-                let edges_json = serde_json::to_string(&g.edges).unwrap_or_default();
-
-                let script_content = format!(r#"
-                    (function() {{
-                        var rawNodes = {nodes_json};
-                        var rawEdges = {edges_json};
-                        var nodes = new vis.DataSet(rawNodes.map(function(n) {{
-                            var color = n.kind === 'file' ? '#7c3aed' : n.kind === 'module' ? '#4f46e5' : '#374151';
-                            return {{ id: n.id, label: n.label, color: {{ background: color, border: '#a78bfa' }},
-                                     font: {{ color: '#e6edf3', size: 13 }}, shape: 'box',
-                                     borderWidth: 1, shadow: true }};
-                        }}));
-                        var edges = new vis.DataSet(rawEdges.map(function(e) {{
-                            return {{ from: e.from, to: e.to, arrows: 'to',
-                                     color: {{ color: '#4b5563', highlight: '#7c3aed' }},
-                                     smooth: {{ type: 'cubicBezier' }} }};
-                        }}));
-                        var container = document.getElementById('graph-container');
-                        if (container) {{
-                            new vis.Network(container, {{ nodes: nodes, edges: edges }}, {{
-                                layout: {{ improvedLayout: true }},
-                                physics: {{ barnesHut: {{ gravitationalConstant: -3000 }} }},
-                                interaction: {{ hover: true, tooltipDelay: 100 }}
-                            }});
-                        }}
-                    }})();
-                "#);
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum GraphMode {
+    Files,
+    Crates,
+    Calls,
+}
 
-                view! {
-                    <div>
-                        <div class="mb-4 flex items-center gap-4">
-                            <span class="text-sm px-3 py-1 rounded-full"
-                                  style="background: rgba(124,58,237,0.2); color: var(--accent-light);">
-                                {format!("{} nodes", g.nodes.len())}
-                            </span>
-                            <span class="text-sm px-3 py-1 rounded-full"
-                                  style="background: rgba(124,58,237,0.1); color: var(--text-muted);">
-                                {format!("{} edges", g.edges.len())}
-                            </span>
-                        </div>
-                        <div id="graph-container"></div>
-                        <script dangerously_set_inner_html=script_content />
-                    </div>
-                }.into_view()
-            })}
-        </Suspense>
+#[component]
+fn GraphView(
+    graph: GraphData,
+    cycles: Vec<DependencyCycle>,
+    violations: Vec<LayerViolation>,
+) -> impl IntoView {
+    let nodes_json = serde_json::to_string(&graph.nodes).unwrap_or_default();
+    let edges_json = serde_json::to_string(&graph.edges).unwrap_or_default();
+    // Every edge inside a strongly connected component lies on some cycle.
+    let components: Vec<&Vec<String>> = cycles.iter().map(|c| &c.members).collect();
+    let components_json = serde_json::to_string(&components).unwrap_or_default();
+    let crossings: Vec<(&str, &str, String)> = violations
+        .iter()
+        .map(|v| (v.file_path.as_str(), v.target_path.as_str(), format!("{} → {}", v.from_layer, v.to_layer)))
+        .collect();
+    let crossings_json = serde_json::to_string(&crossings).unwrap_or_default();
+
+    let script_content = format!(r#"
+        (function() {{
+            var rawNodes = {nodes_json};
+            var rawEdges = {edges_json};
+            var component = {{}};
+            {components_json}.forEach(function(members, i) {{
+                members.forEach(function(m) {{ component[m] = i; }});
+            }});
+            var crossing = {{}};
+            {crossings_json}.forEach(function(c) {{ crossing[c[0] + '\n' + c[1]] = c[2]; }});
+            var colors = {{ file: '#7c3aed', module: '#4f46e5', crate: '#b45309', function: '#0e7490', root: '#db2777' }};
+            var nodes = new vis.DataSet(rawNodes.map(function(n) {{
+                var color = colors[n.kind] || '#374151';
+                return {{ id: n.id, label: n.label, color: {{ background: color, border: '#a78bfa' }},
+                         font: {{ color: '#e6edf3', size: 13 }}, shape: 'box',
+                         borderWidth: 1, shadow: true }};
+            }}));
+            var edges = new vis.DataSet(rawEdges.map(function(e) {{
+                var inCycle = e.from in component && component[e.from] === component[e.to];
+                var layers = crossing[e.from + '\n' + e.to];
+                var color = inCycle ? '#f85149' : layers ? '#d29922' : null;
+                return {{ from: e.from, to: e.to, arrows: 'to',
+                         color: color ? {{ color: color, highlight: color }}
+                                      : {{ color: '#4b5563', highlight: '#7c3aed' }},
+                         width: color ? 2 : 1,
+                         dashes: !!layers,
+                         title: layers ? 'Layer violation: ' + layers : undefined,
+                         smooth: {{ type: 'cubicBezier' }} }};
+            }}));
+            var container = document.getElementById('graph-container');
+            if (container) {{
+                new vis.Network(container, {{ nodes: nodes, edges: edges }}, {{
+                    layout: {{ improvedLayout: true }},
+                    physics: {{ barnesHut: {{ gravitationalConstant: -3000 }} }},
+                    interaction: {{ hover: true, tooltipDelay: 100 }}
+                }});
+            }}
+        }})();
+    "#);
+
+    view! {
+        <div>
+            <div class="mb-4 flex items-center gap-4">
+                <span class="text-sm px-3 py-1 rounded-full"
+                      style="background: rgba(124,58,237,0.2); color: var(--accent-light);">
+                    {format!("{} nodes", graph.nodes.len())}
+                </span>
+                <span class="text-sm px-3 py-1 rounded-full"
+                      style="background: rgba(124,58,237,0.1); color: var(--text-muted);">
+                    {format!("{} edges", graph.edges.len())}
+                </span>
+                {(!cycles.is_empty()).then(|| view! {
+                    <span class="text-sm px-3 py-1 rounded-full"
+                          style="background: rgba(248,81,73,0.15); color: var(--danger);">
+                        {format!("{} dependency cycle{}", cycles.len(), if cycles.len() == 1 { "" } else { "s" })}
+                    </span>
+                })}
+                {(!violations.is_empty()).then(|| view! {
+                    <span class="text-sm px-3 py-1 rounded-full"
+                          style="background: rgba(210,153,34,0.15); color: var(--warning);">
+                        {format!("{} layer violation{}", violations.len(), if violations.len() == 1 { "" } else { "s" })}
+                    </span>
+                })}
+            </div>
+            <div id="graph-container"></div>
+            <script dangerously_set_inner_html=script_content />
+        </div>
     }
 }
 
 #[component]
-fn ComplexityPanel(project_id: ReadSignal<Option<String>>) -> impl IntoView {
-    let items = create_resource(project_id, |pid| async move {
+fn ComplexityPanel(project_id: ReadSignal<Option<String>>, revision: ReadSignal<u32>) -> impl IntoView {
+    let items = create_resource(move || (project_id(), revision()), |(pid, _)| async move {
         let url = match &pid {
             Some(id) => format!("/api/complexity?project_id={id}"),
             None => "/api/complexity".into(),
@@ -419,6 +1231,14 @@ fn ComplexityPanel(project_id: ReadSignal<Option<String>>) -> impl IntoView {
         Request::get(&url).send().await.ok()?
             .json::<Vec<ComplexityItem>>().await.ok()
     });
+    let (sort, set_sort) = create_signal(ComplexityColumn::Cyclomatic);
+    let header = move |column: ComplexityColumn, label: &'static str, title: &'static str| view! {
+        <th class="text-center px-4 py-3 font-semibold cursor-pointer select-none"
+            style="color: var(--text-muted);" title=title
+            on:click=move |_| set_sort(column)>
+            {label}{move || if sort() == column { " ▼" } else { "" }}
+        </th>
+    };
 
     view! {
         <Suspense fallback=move || view! { <LoadingCard /> }>
@@ -426,6 +1246,12 @@ fn ComplexityPanel(project_id: ReadSignal<Option<String>>) -> impl IntoView {
                 if cx.is_empty() {
                     return view! { <EmptyState icon="🌡" title="No complexity data" hint="Run analysis first." /> }.into_view();
                 }
+                let rows = move || {
+                    let mut cx = cx.clone();
+                    let column = sort();
+                    cx.sort_by_key(|item| std::cmp::Reverse(column.value(item)));
+                    cx
+                };
                 view! {
                     <div class="rounded-xl overflow-hidden" style="border: 1px solid var(--border);">
                         <table class="w-full text-sm">
@@ -434,11 +1260,13 @@ fn ComplexityPanel(project_id: ReadSignal<Option<String>>) -> impl IntoView {
                                     <th class="text-left px-4 py-3 font-semibold" style="color: var(--text-muted);">"Function"</th>
                                     <th class="text-left px-4 py-3 font-semibold" style="color: var(--text-muted);">"File"</th>
                                     <th class="text-center px-4 py-3 font-semibold" style="color: var(--text-muted);">"Lines"</th>
-                                    <th class="text-center px-4 py-3 font-semibold" style="color: var(--text-muted);">"Score"</th>
+                                    {header(ComplexityColumn::Cyclomatic, "Cyclomatic", "Cyclomatic complexity")}
+                                    {header(ComplexityColumn::Cognitive, "Cognitive", "Cognitive complexity")}
+                                    {SHAPE_COLUMNS.iter().map(|&(column, label, title)| header(column, label, title)).collect_view()}
                                 </tr>
                             </thead>
                             <tbody>
-                                {cx.iter().enumerate().map(|(i, item)| {
+                                {move || rows().into_iter().enumerate().map(|(i, item)| {
                                     let bg = if i % 2 == 0 { "var(--bg-card)" } else { "var(--bg-secondary)" };
                                     let score_color = if item.score >= 10 {
                                         "var(--danger)"
@@ -457,12 +1285,20 @@ fn ComplexityPanel(project_id: ReadSignal<Option<String>>) -> impl IntoView {
                                     let row_style = format!("background: {};", bg);
                                     let score_style = format!("background: {}; color: {};", score_badge_bg, score_color);
                                     let line_range = format!("{}-{}", item.line_start, item.line_end);
+                                    // Sonar's default limit for cognitive complexity is 15.
+                                    let cognitive_color = if item.cognitive > 15 {
+                                        "var(--danger)"
+                                    } else if item.cognitive > 7 {
+                                        "var(--warning)"
+                                    } else {
+                                        "var(--text-muted)"
+                                    };
 
                                     view! {
                                         <tr style=row_style>
                                             <td class="px-4 py-2 mono font-medium"
                                                 style="color: var(--accent-light); font-size: 0.8rem;">
-                                                {item.function_name.clone()}
+                                                {item.qualified_name.clone()}
                                             </td>
                                             <td class="px-4 py-2 mono"
                                                 style="color: var(--text-muted); font-size: 0.75rem;">
@@ -477,6 +1313,19 @@ fn ComplexityPanel(project_id: ReadSignal<Option<String>>) -> impl IntoView {
                                                     {item.score}
                                                 </span>
                                             </td>
+                                            <td class="px-4 py-2 text-center mono" style=format!("color: {cognitive_color};")>
+                                                {item.cognitive}
+                                            </td>
+                                            {SHAPE_COLUMNS.iter().map(|&(column, _, _)| {
+                                                let value = column.value(&item);
+                                                let over = column.limit().is_some_and(|limit| value > limit);
+                                                let color = if over { "var(--warning)" } else { "var(--text-muted)" };
+                                                view! {
+                                                    <td class="px-4 py-2 text-center mono" style=format!("color: {color};")>
+                                                        {value}
+                                                    </td>
+                                                }
+                                            }).collect_view()}
                                         </tr>
                                     }
                                 }).collect_view()}
@@ -489,6 +1338,379 @@ fn ComplexityPanel(project_id: ReadSignal<Option<String>>) -> impl IntoView {
     }
 }
 
+/// Sort keys of the complexity table; every column sorts worst first.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ComplexityColumn {
+    Cyclomatic,
+    Cognitive,
+    LogicalLines,
+    Params,
+    GenericParams,
+    Nesting,
+    Returns,
+    UnsafeBlocks,
+    Unwraps,
+}
+
+impl ComplexityColumn {
+    fn value(self, item: &ComplexityItem) -> i32 {
+        match self {
+            ComplexityColumn::Cyclomatic    => item.score,
+            ComplexityColumn::Cognitive     => item.cognitive,
+            ComplexityColumn::LogicalLines  => item.logical_lines,
+            ComplexityColumn::Params        => item.params,
+            ComplexityColumn::GenericParams => item.generic_params,
+            ComplexityColumn::Nesting       => item.max_nesting,
+            ComplexityColumn::Returns       => item.returns,
+            ComplexityColumn::UnsafeBlocks  => item.unsafe_blocks,
+            ComplexityColumn::Unwraps       => item.unwraps,
+        }
+    }
+
+    /// The limit past which the backend notes a function (see
+    /// `metrics::function_notes`).
+    fn limit(self) -> Option<i32> {
+        match self {
+            ComplexityColumn::LogicalLines  => Some(100),
+            ComplexityColumn::Params        => Some(7),
+            ComplexityColumn::GenericParams => Some(4),
+            ComplexityColumn::Nesting       => Some(4),
+            ComplexityColumn::Returns       => Some(5),
+            ComplexityColumn::UnsafeBlocks  => Some(0),
+            ComplexityColumn::Unwraps       => Some(5),
+            ComplexityColumn::Cyclomatic | ComplexityColumn::Cognitive => None,
+        }
+    }
+}
+
+/// (column, header, tooltip) of the size and shape columns.
+const SHAPE_COLUMNS: &[(ComplexityColumn, &str, &str)] = &[
+    (ComplexityColumn::LogicalLines, "LOC", "Logical lines of code"),
+    (ComplexityColumn::Params, "Params", "Parameters"),
+    (ComplexityColumn::GenericParams, "Generics", "Generic parameters"),
+    (ComplexityColumn::Nesting, "Nesting", "Maximum nesting depth"),
+    (ComplexityColumn::Returns, "Returns", "return expressions"),
+    (ComplexityColumn::UnsafeBlocks, "Unsafe", "unsafe blocks"),
+    (ComplexityColumn::Unwraps, "Unwraps", ".unwrap() and .expect() calls"),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ModuleColumn {
+    Path,
+    Afferent,
+    Efferent,
+    Instability,
+    Abstractness,
+    Distance,
+}
+
+impl ModuleColumn {
+    fn compare(self, a: &ModuleMetrics, b: &ModuleMetrics) -> std::cmp::Ordering {
+        match self {
+            ModuleColumn::Path         => a.path.cmp(&b.path),
+            ModuleColumn::Afferent     => a.afferent.cmp(&b.afferent),
+            ModuleColumn::Efferent     => a.efferent.cmp(&b.efferent),
+            ModuleColumn::Instability  => a.instability.total_cmp(&b.instability),
+            ModuleColumn::Abstractness => a.abstractness.total_cmp(&b.abstractness),
+            ModuleColumn::Distance     => a.distance.total_cmp(&b.distance),
+        }
+    }
+}
+
+/// Distance from the main sequence at which a module is flagged.
+const DISTANCE_WARNING: f64 = 0.5;
+const DISTANCE_DANGER: f64 = 0.7;
+
+#[component]
+fn ModulesPanel(project_id: ReadSignal<Option<String>>) -> impl IntoView {
+    let modules = create_resource(project_id, |pid| async move {
+        let url = match &pid {
+            Some(id) => format!("/api/metrics/modules?project_id={id}"),
+            None => "/api/metrics/modules".into(),
+        };
+        Request::get(&url).send().await.ok()?
+            .json::<Vec<ModuleMetrics>>().await.ok()
+    });
+    // Column and whether it sorts descending; worst distance first by default.
+    let (sort, set_sort) = create_signal((ModuleColumn::Distance, true));
+
+    let header = move |column: ModuleColumn, label: &'static str| {
+        let arrow = move || match sort.get() {
+            (c, true) if c == column => " ▼",
+            (c, false) if c == column => " ▲",
+            _ => "",
+        };
+        let align = if column == ModuleColumn::Path { "text-left" } else { "text-center" };
+        view! {
+            <th class=format!("{align} px-4 py-3 font-semibold cursor-pointer select-none")
+                style="color: var(--text-muted);"
+                on:click=move |_| set_sort.update(|(c, desc)| {
+                    if *c == column {
+                        *desc = !*desc;
+                    } else {
+                        *c = column;
+                        *desc = column != ModuleColumn::Path;
+                    }
+                })>
+                {label}{arrow}
+            </th>
+        }
+    };
+
+    view! {
+        <Suspense fallback=move || view! { <LoadingCard /> }>
+            {move || modules.get().flatten().map(|mods| {
+                if mods.is_empty() {
+                    return view! { <EmptyState icon="📐" title="No module metrics" hint="Run analysis first." /> }.into_view();
+                }
+                let scatter = mods.clone();
+                let rows = move || {
+                    let (column, desc) = sort.get();
+                    let mut sorted = mods.clone();
+                    sorted.sort_by(|a, b| {
+                        let order = column.compare(a, b);
+                        if desc { order.reverse() } else { order }
+                    });
+                    sorted.into_iter().enumerate().map(|(i, m)| {
+                        let bg = if i % 2 == 0 { "var(--bg-card)" } else { "var(--bg-secondary)" };
+                        let distance_color = distance_color(m.distance);
+                        let types = format!("{} / {}", m.abstract_types, m.abstract_types + m.concrete_types);
+                        view! {
+                            <tr style=format!("background: {bg};")>
+                                <td class="px-4 py-2 mono" style="color: var(--accent-light); font-size: 0.8rem;">
+                                    {m.path}
+                                </td>
+                                <td class="px-4 py-2 text-center mono">{m.afferent}</td>
+                                <td class="px-4 py-2 text-center mono">{m.efferent}</td>
+                                <td class="px-4 py-2 text-center mono">{format!("{:.2}", m.instability)}</td>
+                                <td class="px-4 py-2 text-center mono" title=format!("{types} types abstract")>
+                                    {format!("{:.2}", m.abstractness)}
+                                </td>
+                                <td class="px-4 py-2 text-center mono font-bold" style=format!("color: {distance_color};")>
+                                    {format!("{:.2}", m.distance)}
+                                </td>
+                            </tr>
+                        }
+                    }).collect_view()
+                };
+                view! {
+                    <div class="space-y-4">
+                        <MainSequencePlot modules=scatter />
+                        <div class="rounded-xl overflow-hidden" style="border: 1px solid var(--border);">
+                            <table class="w-full text-sm">
+                                <thead>
+                                    <tr style="background: var(--bg-secondary);">
+                                        {header(ModuleColumn::Path, "Module")}
+                                        {header(ModuleColumn::Afferent, "Ca")}
+                                        {header(ModuleColumn::Efferent, "Ce")}
+                                        {header(ModuleColumn::Instability, "I")}
+                                        {header(ModuleColumn::Abstractness, "A")}
+                                        {header(ModuleColumn::Distance, "D")}
+                                    </tr>
+                                </thead>
+                                <tbody>{rows}</tbody>
+                            </table>
+                        </div>
+                    </div>
+                }.into_view()
+            })}
+        </Suspense>
+    }
+}
+
+fn distance_color(distance: f64) -> &'static str {
+    if distance >= DISTANCE_DANGER {
+        "var(--danger)"
+    } else if distance >= DISTANCE_WARNING {
+        "var(--warning)"
+    } else {
+        "var(--success)"
+    }
+}
+
+/// Abstractness against instability, with the main sequence A + I = 1.
+#[component]
+fn MainSequencePlot(modules: Vec<ModuleMetrics>) -> impl IntoView {
+    const SIZE: f64 = 320.0;
+    const PAD: f64 = 36.0;
+    let x = |instability: f64| PAD + instability * SIZE;
+    let y = |abstractness: f64| PAD + (1.0 - abstractness) * SIZE;
+    let extent = SIZE + 2.0 * PAD;
+
+    view! {
+        <div class="rounded-xl p-4" style="background: var(--bg-card); border: 1px solid var(--border);">
+            <h3 class="text-sm font-semibold mb-2" style="color: var(--text-muted);">
+                "Abstractness vs. instability"
+            </h3>
+            <svg viewBox=format!("0 0 {extent} {extent}") width=extent height=extent>
+                <rect x=PAD y=PAD width=SIZE height=SIZE style="fill: none; stroke: var(--border);" />
+                <line x1=x(0.0) y1=y(1.0) x2=x(1.0) y2=y(0.0)
+                      stroke-dasharray="4 4" style="stroke: var(--text-muted);" />
+                <text x=x(0.02) y=y(0.03) font-size="10" style="fill: var(--danger);">"zone of pain"</text>
+                <text x=x(0.62) y=y(0.95) font-size="10" style="fill: var(--warning);">"zone of uselessness"</text>
+                <text x=x(0.5) y={extent - 8.0} font-size="11" text-anchor="middle" style="fill: var(--text-muted);">
+                    "Instability (I)"
+                </text>
+                <text x=12 y=y(0.5) font-size="11" text-anchor="middle" style="fill: var(--text-muted);"
+                      transform=format!("rotate(-90 12 {})", y(0.5))>
+                    "Abstractness (A)"
+                </text>
+                {modules.into_iter().map(|m| view! {
+                    <circle cx=x(m.instability) cy=y(m.abstractness) r="5"
+                            fill-opacity="0.7" style=format!("fill: {};", distance_color(m.distance))>
+                        <title>{format!("{} (I={:.2}, A={:.2}, D={:.2})", m.path, m.instability, m.abstractness, m.distance)}</title>
+                    </circle>
+                }).collect_view()}
+            </svg>
+        </div>
+    }
+}
+
+#[component]
+fn ComparePanel(project_id: ReadSignal<Option<String>>) -> impl IntoView {
+    let runs = create_resource(project_id, |pid| async move {
+        let url = match &pid {
+            Some(id) => format!("/api/runs?project_id={id}"),
+            None => "/api/runs".into(),
+        };
+        Request::get(&url).send().await.ok()?
+            .json::<Vec<AnalysisRun>>().await.ok()
+    });
+
+    // Empty until the user picks; then the two latest runs are compared.
+    let (from_run, set_from_run) = create_signal(String::new());
+    let (to_run, set_to_run)     = create_signal(String::new());
+
+    let selected = move || {
+        let available = runs.get().flatten().unwrap_or_default();
+        let pick = |chosen: String, fallback: Option<&AnalysisRun>| {
+            if chosen.is_empty() { fallback.map(|r| r.id.clone()) } else { Some(chosen) }
+        };
+        (pick(from_run(), available.get(1)), pick(to_run(), available.first()))
+    };
+
+    let diff = create_resource(selected, |(from, to)| async move {
+        let (from, to) = (from?, to?);
+        Request::get(&format!("/api/diff?from={from}&to={to}")).send().await.ok()?
+            .json::<RunDiff>().await.ok()
+    });
+
+    view! {
+        <Suspense fallback=move || view! { <LoadingCard /> }>
+            {move || runs.get().flatten().map(|rs| {
+                if rs.len() < 2 {
+                    return view! {
+                        <EmptyState icon="⚖" title="Nothing to compare" hint="Run analysis at least twice to compare runs." />
+                    }.into_view();
+                }
+                let (from_id, to_id) = selected();
+                let options = move |current: Option<String>| rs.iter().map(|r| {
+                    let label = format!(
+                        "{} {}",
+                        r.created_at.chars().take(19).collect::<String>().replace('T', " "),
+                        r.commit_sha.as_deref().map(|s| &s[..s.len().min(7)]).unwrap_or(""),
+                    );
+                    view! { <option value=r.id.clone() selected=current.as_deref() == Some(r.id.as_str())>{label}</option> }
+                }).collect_view();
+                let select_style = "background: var(--bg-card); border: 1px solid var(--border); color: var(--text-primary);";
+
+                view! {
+                    <div>
+                        <div class="mb-6 flex items-center gap-3 text-sm">
+                            <span style="color: var(--text-muted);">"From"</span>
+                            <select class="px-3 py-2 rounded-lg mono" style=select_style
+                                    on:change=move |ev| set_from_run(event_target_value(&ev))>
+                                {options.clone()(from_id)}
+                            </select>
+                            <span style="color: var(--text-muted);">"to"</span>
+                            <select class="px-3 py-2 rounded-lg mono" style=select_style
+                                    on:change=move |ev| set_to_run(event_target_value(&ev))>
+                                {options(to_id)}
+                            </select>
+                        </div>
+                        {move || diff.get().flatten().map(|d| view! { <RunDiffView diff=d /> })}
+                    </div>
+                }.into_view()
+            })}
+        </Suspense>
+    }
+}
+
+#[component]
+fn RunDiffView(diff: RunDiff) -> impl IntoView {
+    let file_rows = diff.files_added.iter().map(|p| (true, "added".to_string(), p.clone()))
+        .chain(diff.files_removed.iter().map(|p| (false, "removed".to_string(), p.clone())))
+        .chain(diff.files_changed.iter().map(|c| {
+            let delta = c.line_count_to - c.line_count_from;
+            (delta <= 0, format!("{delta:+} lines"), c.path.clone())
+        }))
+        .collect::<Vec<_>>();
+    let fn_rows = diff.functions_added.iter().map(|f| (true, "added".to_string(), f))
+        .chain(diff.functions_removed.iter().map(|f| (false, "removed".to_string(), f)))
+        .map(|(good, badge, f)| (good, badge, format!("{} · {}", f.qualified_name, f.file_path)))
+        .collect::<Vec<_>>();
+    let cx_rows = diff.complexity_deltas.iter().map(|d| (
+        d.delta < 0,
+        format!("{:+}", d.delta),
+        format!("{} · {} ({} → {})", d.qualified_name, d.file_path, d.score_from, d.score_to),
+    )).collect::<Vec<_>>();
+    let edge_rows = diff.edges_added.iter().map(|e| (false, "new".to_string(), e))
+        .chain(diff.edges_removed.iter().map(|e| (true, "removed".to_string(), e)))
+        .map(|(good, badge, e)| (good, badge, format!("{} → {}", e.from, e.to)))
+        .collect::<Vec<_>>();
+    let dead_rows = diff.new_dead_code.iter()
+        .map(|d| (false, "new".to_string(), format!("{} · {}:{}", d.qualified_name, d.file_path, d.line_start)))
+        .collect::<Vec<_>>();
+
+    view! {
+        <div class="grid gap-4 lg:grid-cols-2">
+            <DiffSection title="📁 Files" rows=file_rows />
+            <DiffSection title="⚙️ Functions" rows=fn_rows />
+            <DiffSection title="🌡 Complexity" rows=cx_rows />
+            <DiffSection title="🔗 Dependencies" rows=edge_rows />
+            <DiffSection title="💀 New Dead Code" rows=dead_rows />
+        </div>
+    }
+}
+
+/// One block of the comparison; each row is (is_improvement, badge, text).
+#[component]
+fn DiffSection(title: &'static str, rows: Vec<(bool, String, String)>) -> impl IntoView {
+    view! {
+        <div class="p-5 rounded-xl" style="background: var(--bg-card); border: 1px solid var(--border);">
+            <h3 class="font-semibold mb-3" style="color: var(--accent-light);">{title}</h3>
+            {if rows.is_empty() {
+                view! { <p class="text-sm" style="color: var(--text-muted);">"No changes."</p> }.into_view()
+            } else {
+                view! {
+                    <ul class="space-y-1">
+                        {rows.into_iter().map(|(good, badge, text)| view! {
+                            <li class="text-sm flex items-center gap-2">
+                                <DeltaBadge good=good text=badge />
+                                <span class="mono" style="color: var(--text-primary); font-size: 0.8rem;">{text}</span>
+                            </li>
+                        }).collect_view()}
+                    </ul>
+                }.into_view()
+            }}
+        </div>
+    }
+}
+
+/// Green or red badge in the style of the complexity score badge.
+#[component]
+fn DeltaBadge(good: bool, text: String) -> impl IntoView {
+    let style = if good {
+        "background: rgba(63,185,80,0.15); color: var(--success);"
+    } else {
+        "background: rgba(248,81,73,0.15); color: var(--danger);"
+    };
+
+    view! {
+        <span class="px-2 py-1 rounded-md text-xs font-bold mono" style=style>{text}</span>
+    }
+}
+
 #[component]
 fn StatCard(label: &'static str, value: String, icon: &'static str) -> impl IntoView {
     view! {